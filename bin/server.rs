@@ -1,13 +1,69 @@
 use log::info;
 use std::error::Error;
+use std::path::PathBuf;
 use systemd_language_server::Backend;
+use systemd_language_server::graph::{self, DependencyGraph, GraphFormat, GraphOutput};
+use systemd_language_server::index::UnitIndex;
 use tokio::io::{stdin, stdout};
 use tower_lsp::{LspService, Server};
 
+const GRAPH_USAGE: &str =
+    "usage: systemd-language-server graph <unit> [--format json|dot] [dir...]";
+
+// `graph <unit> [--format json|dot] [dir...]`: print a unit and its
+// transitive dependencies among the unit files below the directories, the
+// current one by default
+fn graph(args: &[String]) -> anyhow::Result<()> {
+    let mut unit = None;
+    let mut format = GraphFormat::default();
+    let mut dirs = Vec::new();
+
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--format" => {
+                let value = args.next().ok_or_else(|| anyhow::anyhow!(GRAPH_USAGE))?;
+                format = value.parse()?;
+            }
+            _ if arg.starts_with("--format=") => format = arg["--format=".len()..].parse()?,
+            _ if arg.starts_with('-') => {
+                anyhow::bail!("unknown option '{}'\n{}", arg, GRAPH_USAGE)
+            }
+            _ if unit.is_none() => unit = Some(arg.clone()),
+            _ => dirs.push(PathBuf::from(arg)),
+        }
+    }
+    let unit = unit.ok_or_else(|| anyhow::anyhow!(GRAPH_USAGE))?;
+    graph::check_unit(&unit).map_err(anyhow::Error::msg)?;
+    if dirs.is_empty() {
+        dirs.push(PathBuf::from("."));
+    }
+
+    let index = UnitIndex::new();
+    for dir in &dirs {
+        index.index_folder(dir);
+    }
+    match DependencyGraph::build(&index).export(&unit).render(format) {
+        GraphOutput::Dot(dot) => print!("{}", dot),
+        GraphOutput::Json(graph) => println!("{}", serde_json::to_string_pretty(&graph)?),
+    }
+    Ok(())
+}
+
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn Error + Sync + Send>> {
     // Initialize logging
     env_logger::init();
+
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(String::as_str) == Some("graph") {
+        if let Err(error) = graph(&args[1..]) {
+            eprintln!("{}", error);
+            std::process::exit(2);
+        }
+        return Ok(());
+    }
+
     info!("Starting Systemd Language Server...");
 
     // Create standard input/output streams
@@ -15,7 +71,10 @@ async fn main() -> Result<(), Box<dyn Error + Sync + Send>> {
     let stdout = stdout();
 
     // Create LSP service
-    let (service, socket) = LspService::new(Backend::new);
+    let (service, socket) = LspService::build(Backend::new)
+        .custom_method("systemd/effectiveUnit", Backend::effective_unit)
+        .custom_method("systemd/dependencyGraph", Backend::dependency_graph)
+        .finish();
 
     // Start server
     info!("Systemd Language Server started, waiting for client connection...");
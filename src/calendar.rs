@@ -0,0 +1,707 @@
+// Calendar event expressions, as in OnCalendar=, systemd.time(7)
+//
+// Mirrors systemd's calendarspec.c: an expression is parsed into one set of
+// components per field, printed back in the normalized form
+// `systemd-analyze calendar` shows, and searched for its next elapses.
+
+use std::fmt;
+
+use chrono::{
+    DateTime, Datelike, Duration, FixedOffset, Local, NaiveDate, NaiveDateTime, NaiveTime,
+    TimeZone, Timelike, Utc,
+};
+use chrono_tz::Tz;
+
+use crate::parser::Span;
+use crate::validators::ValueError;
+
+const WEEKDAYS: [&str; 7] = [
+    "Monday",
+    "Tuesday",
+    "Wednesday",
+    "Thursday",
+    "Friday",
+    "Saturday",
+    "Sunday",
+];
+
+const ALL_WEEKDAYS: u8 = 0x7f;
+
+const SHORTHANDS: &[(&str, &str)] = &[
+    ("minutely", "*-*-* *:*:00"),
+    ("hourly", "*-*-* *:00:00"),
+    ("daily", "*-*-* 00:00:00"),
+    ("monthly", "*-*-01 00:00:00"),
+    ("weekly", "Mon *-*-* 00:00:00"),
+    ("yearly", "*-01-01 00:00:00"),
+    ("annually", "*-01-01 00:00:00"),
+    ("quarterly", "*-01,04,07,10-01 00:00:00"),
+    ("semiannually", "*-01,07-01 00:00:00"),
+];
+
+// Latest year systemd's calendar search considers
+const MAX_YEAR: i32 = 2199;
+
+const USEC_PER_SEC: u32 = 1_000_000;
+
+// One item of a field's comma-separated list: `start[..stop][/repeat]`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Component {
+    pub start: u32,
+    pub stop: Option<u32>,
+    pub repeat: Option<u32>,
+}
+
+impl Component {
+    // Smallest value of the component that is at least `lo`. A repetition
+    // stepping past `u32::MAX` has no next value.
+    fn next(&self, lo: u32) -> Option<u32> {
+        let value = if lo <= self.start {
+            self.start
+        } else {
+            match (self.stop, self.repeat) {
+                (_, Some(repeat)) => (lo - self.start)
+                    .div_ceil(repeat)
+                    .checked_mul(repeat)
+                    .and_then(|step| self.start.checked_add(step))?,
+                (Some(_), None) => lo,
+                (None, None) => return None,
+            }
+        };
+        match self.stop {
+            Some(stop) if value > stop => None,
+            _ => Some(value),
+        }
+    }
+
+    // The same days counted from the start of a month with `days` days.
+    // Repetitions still step towards the end of the month, so `~07/1`
+    // covers the last seven days.
+    fn counted_from_end(&self, days: u32) -> Component {
+        let start = (days + 1).saturating_sub(self.start);
+        let stop = self.stop.map(|stop| (days + 1).saturating_sub(stop));
+        Component {
+            start: stop.map_or(start, |stop| stop.min(start)),
+            stop: stop.map(|stop| stop.max(start)),
+            repeat: self.repeat,
+        }
+    }
+}
+
+// The components of one field; an empty field is the wildcard `*`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Field(pub Vec<Component>);
+
+impl Field {
+    fn is_wildcard(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn next(&self, lo: u32) -> Option<u32> {
+        if self.is_wildcard() {
+            return Some(lo);
+        }
+        self.0.iter().filter_map(|c| c.next(lo)).min()
+    }
+
+    fn matches(&self, value: u32) -> bool {
+        self.next(value) == Some(value)
+    }
+
+    fn matches_from_end(&self, day: u32, days: u32) -> bool {
+        self.is_wildcard()
+            || self
+                .0
+                .iter()
+                .any(|c| c.counted_from_end(days).next(day) == Some(day))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Zone {
+    Local,
+    Utc,
+    Named(Tz),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CalendarSpec {
+    // Bit 0 is Monday
+    pub weekdays: u8,
+    pub year: Field,
+    pub month: Field,
+    pub day: Field,
+    // Days count back from the end of the month, as in `*-02~03`
+    pub end_of_month: bool,
+    pub hour: Field,
+    pub minute: Field,
+    // In microseconds, to keep fractional seconds
+    pub second: Field,
+    pub zone: Zone,
+}
+
+// Parse a calendar event expression. Error spans are byte ranges of `value`.
+pub fn parse(value: &str) -> Result<CalendarSpec, ValueError> {
+    let mut words: Vec<(usize, &str)> = value
+        .split_whitespace()
+        .map(|word| (word.as_ptr() as usize - value.as_ptr() as usize, word))
+        .collect();
+    if words.is_empty() {
+        return Err(error(
+            0,
+            value.len(),
+            "Empty calendar expression".to_string(),
+        ));
+    }
+
+    // A trailing time zone applies to the whole expression
+    let mut zone = Zone::Local;
+    if words.len() > 1 {
+        let (_, last) = words[words.len() - 1];
+        if last == "UTC" {
+            zone = Zone::Utc;
+            words.pop();
+        } else if let Ok(tz) = last.parse::<Tz>() {
+            zone = Zone::Named(tz);
+            words.pop();
+        }
+    }
+
+    if let [(_, word)] = words[..]
+        && let Some((_, expansion)) = SHORTHANDS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(word))
+    {
+        let mut spec = parse(expansion)?;
+        spec.zone = zone;
+        return Ok(spec);
+    }
+
+    let mut spec = CalendarSpec {
+        weekdays: ALL_WEEKDAYS,
+        year: Field::default(),
+        month: Field::default(),
+        day: Field::default(),
+        end_of_month: false,
+        hour: Field::default(),
+        minute: Field::default(),
+        second: Field::default(),
+        zone,
+    };
+
+    let mut words = words.into_iter().peekable();
+    if let Some(&(offset, word)) = words.peek()
+        && word.starts_with(|c: char| c.is_ascii_alphabetic())
+    {
+        spec.weekdays = parse_weekdays(offset, word)?;
+        words.next();
+    }
+
+    let (mut date, mut time) = (false, false);
+    for (offset, word) in words {
+        if word.contains(':') && !time {
+            parse_time(&mut spec, offset, word)?;
+            time = true;
+        } else if !word.contains(':') && !date && !time {
+            parse_date(&mut spec, offset, word)?;
+            date = true;
+        } else {
+            return Err(error(
+                offset,
+                offset + word.len(),
+                format!("Unexpected '{}' in calendar expression", word),
+            ));
+        }
+    }
+
+    // Without a time the event elapses at midnight
+    if !time {
+        spec.hour = Field(vec![single(0)]);
+        spec.minute = Field(vec![single(0)]);
+        spec.second = Field(vec![single(0)]);
+    }
+
+    Ok(spec)
+}
+
+fn error(start: usize, end: usize, message: String) -> ValueError {
+    ValueError {
+        span: Span::new(start, end),
+        message,
+    }
+}
+
+fn single(value: u32) -> Component {
+    Component {
+        start: value,
+        stop: None,
+        repeat: None,
+    }
+}
+
+fn weekday(offset: usize, name: &str) -> Result<u32, ValueError> {
+    WEEKDAYS
+        .iter()
+        .position(|day| day.eq_ignore_ascii_case(name) || day[..3].eq_ignore_ascii_case(name))
+        .map(|index| index as u32)
+        .ok_or_else(|| {
+            error(
+                offset,
+                offset + name.len(),
+                format!(
+                    "Invalid weekday '{}': expected Mon, Tue, Wed, Thu, Fri, Sat or Sun",
+                    name
+                ),
+            )
+        })
+}
+
+// Weekday lists such as `Mon..Fri` or `Sat,Sun`, optionally followed by a
+// comma as in `Wed, 17:48`
+fn parse_weekdays(offset: usize, word: &str) -> Result<u8, ValueError> {
+    let word = word.strip_suffix(',').unwrap_or(word);
+    let mut bits = 0;
+    let mut item_offset = offset;
+    for item in word.split(',') {
+        let range = item.split_once("..").or_else(|| item.split_once('-'));
+        let (first, last) = match range {
+            Some((first, last)) => {
+                let last_offset = item_offset + item.len() - last.len();
+                (weekday(item_offset, first)?, weekday(last_offset, last)?)
+            }
+            None => {
+                let day = weekday(item_offset, item)?;
+                (day, day)
+            }
+        };
+        if first > last {
+            return Err(error(
+                item_offset,
+                item_offset + item.len(),
+                format!("Weekday range '{}' ends before it starts", item),
+            ));
+        }
+        for day in first..=last {
+            bits |= 1 << day;
+        }
+        item_offset += item.len() + 1;
+    }
+    Ok(bits)
+}
+
+// `[YEAR-]MONTH-DAY`, with `~` instead of the last `-` for days counted
+// from the end of the month
+fn parse_date(spec: &mut CalendarSpec, offset: usize, word: &str) -> Result<(), ValueError> {
+    let separators: Vec<(usize, char)> = word
+        .char_indices()
+        .filter(|(_, c)| matches!(c, '-' | '~'))
+        .collect();
+    let invalid = || {
+        error(
+            offset,
+            offset + word.len(),
+            format!(
+                "Invalid date '{}': expected YEAR-MONTH-DAY or MONTH-DAY, such as *-*-01",
+                word
+            ),
+        )
+    };
+
+    let (year, month_start) = match separators[..] {
+        [_] => (None, 0),
+        [(first, '-'), _] => (Some(&word[..first]), first + 1),
+        _ => return Err(invalid()),
+    };
+    let &(day_separator, separator) = separators.last().ok_or_else(invalid)?;
+
+    if let Some(year) = year {
+        let mut field = parse_field(offset, year, "year", 0, MAX_YEAR as u32, false)?;
+        for component in &mut field.0 {
+            component.start = fix_year(component.start);
+            component.stop = component.stop.map(fix_year);
+        }
+        let out_of_range = field.0.iter().any(|component| {
+            component.start < 1970 || component.stop.is_some_and(|stop| stop < component.start)
+        });
+        if out_of_range {
+            return Err(error(
+                offset,
+                offset + year.len(),
+                format!(
+                    "Invalid year '{}': expected a value between 1970 and {}",
+                    year, MAX_YEAR
+                ),
+            ));
+        }
+        field.0.sort();
+        field.0.dedup();
+        spec.year = field;
+    }
+    spec.month = parse_field(
+        offset + month_start,
+        &word[month_start..day_separator],
+        "month",
+        1,
+        12,
+        false,
+    )?;
+    spec.day = parse_field(
+        offset + day_separator + 1,
+        &word[day_separator + 1..],
+        "day",
+        1,
+        31,
+        false,
+    )?;
+    spec.end_of_month = separator == '~';
+    Ok(())
+}
+
+// Two-digit years, as calendarspec's fix_year(): 0-69 are 2000-2069 and
+// 70-99 are 1970-1999
+fn fix_year(year: u32) -> u32 {
+    match year {
+        0..70 => year + 2000,
+        70..100 => year + 1900,
+        _ => year,
+    }
+}
+
+// `HOUR:MINUTE[:SECOND]`
+fn parse_time(spec: &mut CalendarSpec, offset: usize, word: &str) -> Result<(), ValueError> {
+    let parts: Vec<&str> = word.split(':').collect();
+    if !(2..=3).contains(&parts.len()) {
+        return Err(error(
+            offset,
+            offset + word.len(),
+            format!(
+                "Invalid time '{}': expected HOUR:MINUTE[:SECOND], such as 02:00 or *:0/15",
+                word
+            ),
+        ));
+    }
+
+    spec.hour = parse_field(offset, parts[0], "hour", 0, 23, false)?;
+    let minute_offset = offset + parts[0].len() + 1;
+    spec.minute = parse_field(minute_offset, parts[1], "minute", 0, 59, false)?;
+    spec.second = match parts.get(2) {
+        Some(second) => parse_field(
+            minute_offset + parts[1].len() + 1,
+            second,
+            "second",
+            0,
+            59,
+            true,
+        )?,
+        None => Field(vec![single(0)]),
+    };
+    Ok(())
+}
+
+// A comma-separated list of `start[..stop][/repeat]` items, or `*`.
+// Seconds may have a fraction and are stored in microseconds.
+fn parse_field(
+    offset: usize,
+    text: &str,
+    name: &str,
+    min: u32,
+    max: u32,
+    fractional: bool,
+) -> Result<Field, ValueError> {
+    if text == "*" {
+        return Ok(Field::default());
+    }
+
+    let number = |start: usize, text: &str| -> Result<u32, ValueError> {
+        let invalid = |message: String| error(start, start + text.len(), message);
+        let (whole, value) = parse_number(text, fractional)
+            .ok_or_else(|| invalid(format!("Invalid {} '{}'", name, text)))?;
+        if whole < min || whole > max {
+            return Err(invalid(format!(
+                "Invalid {} '{}': expected a value between {} and {}",
+                name, text, min, max
+            )));
+        }
+        Ok(value)
+    };
+
+    let mut components = Vec::new();
+    let mut item_offset = offset;
+    for item in text.split(',') {
+        let (range, repeat) = match item.split_once('/') {
+            Some((range, repeat)) => (range, Some(repeat)),
+            None => (item, None),
+        };
+        let (start, stop) = match range.split_once("..") {
+            Some((start, stop)) => (start, Some(stop)),
+            None => (range, None),
+        };
+
+        let start_value = number(item_offset, start)?;
+        let stop_offset = item_offset + start.len() + 2;
+        let stop_value = stop.map(|stop| number(stop_offset, stop)).transpose()?;
+        if stop_value.is_some_and(|stop| stop < start_value) {
+            return Err(error(
+                item_offset,
+                item_offset + range.len(),
+                format!("Invalid {} range '{}': ends before it starts", name, range),
+            ));
+        }
+
+        let repeat_offset = item_offset + range.len() + 1;
+        let repeat_value = match repeat {
+            Some(repeat) => {
+                let value = parse_number(repeat, fractional)
+                    .map(|(_, value)| value)
+                    .filter(|v| *v > 0);
+                Some(value.ok_or_else(|| {
+                    error(
+                        repeat_offset,
+                        repeat_offset + repeat.len(),
+                        format!(
+                            "Invalid repetition '{}': expected a positive number",
+                            repeat
+                        ),
+                    )
+                })?)
+            }
+            None => None,
+        };
+
+        components.push(Component {
+            start: start_value,
+            stop: stop_value,
+            repeat: repeat_value,
+        });
+        item_offset += item.len() + 1;
+    }
+
+    components.sort();
+    components.dedup();
+    Ok(Field(components))
+}
+
+// A decimal number and its value, in microseconds for seconds. Digits of
+// a fraction past the sixth only round, as systemd's
+// parse_fractional_part_u() does.
+fn parse_number(text: &str, fractional: bool) -> Option<(u32, u32)> {
+    let (whole, fraction) = match text.split_once('.') {
+        Some((whole, fraction)) if fractional => (whole, fraction),
+        _ => (text, ""),
+    };
+    let digits = |text: &str| text.chars().all(|c| c.is_ascii_digit());
+    if whole.is_empty() || !digits(whole) || !digits(fraction) {
+        return None;
+    }
+    let whole: u32 = whole.parse().ok()?;
+    if !fractional {
+        return Some((whole, whole));
+    }
+
+    let usec: u32 = format!("{:0<6}", &fraction[..fraction.len().min(6)])
+        .parse()
+        .ok()?;
+    let round = fraction
+        .as_bytes()
+        .get(6)
+        .is_some_and(|digit| *digit >= b'5');
+    let value = whole
+        .checked_mul(USEC_PER_SEC)?
+        .checked_add(usec + round as u32)?;
+    Some((whole, value))
+}
+
+fn days_in_month(year: i32, month: u32) -> u32 {
+    let next = if month == 12 {
+        NaiveDate::from_ymd_opt(year + 1, 1, 1)
+    } else {
+        NaiveDate::from_ymd_opt(year, month + 1, 1)
+    };
+    next.and_then(|date| date.pred_opt())
+        .map(|date| date.day())
+        .unwrap_or(31)
+}
+
+impl CalendarSpec {
+    fn matches_date(&self, date: NaiveDate) -> bool {
+        let day_matches = if self.end_of_month {
+            let days = days_in_month(date.year(), date.month());
+            self.day.matches_from_end(date.day(), days)
+        } else {
+            self.day.matches(date.day())
+        };
+        self.weekdays & (1 << date.weekday().num_days_from_monday()) != 0
+            && self.year.matches(date.year() as u32)
+            && self.month.matches(date.month())
+            && day_matches
+    }
+
+    // First wall clock time strictly after `after` matching the expression
+    fn next_civil(&self, after: NaiveDateTime) -> Option<NaiveDateTime> {
+        let start = after + Duration::microseconds(1);
+        let start_usec = start.second() * USEC_PER_SEC + start.nanosecond() / 1000;
+
+        let mut date = start.date();
+        let mut tight = true;
+        while date.year() <= MAX_YEAR {
+            if self.matches_date(date) {
+                let hours = if tight { start.hour() } else { 0 };
+                for hour in hours..24 {
+                    if !self.hour.matches(hour) {
+                        continue;
+                    }
+                    let tight = tight && hour == start.hour();
+                    let minutes = if tight { start.minute() } else { 0 };
+                    for minute in minutes..60 {
+                        if !self.minute.matches(minute) {
+                            continue;
+                        }
+                        let tight = tight && minute == start.minute();
+                        let lo = if tight { start_usec } else { 0 };
+                        // Any second means any whole second
+                        let lo = if self.second.is_wildcard() {
+                            lo.next_multiple_of(USEC_PER_SEC)
+                        } else {
+                            lo
+                        };
+                        let Some(second) = self.second.next(lo).filter(|s| *s < 60 * USEC_PER_SEC)
+                        else {
+                            continue;
+                        };
+                        let time = NaiveTime::from_hms_micro_opt(
+                            hour,
+                            minute,
+                            second / USEC_PER_SEC,
+                            second % USEC_PER_SEC,
+                        )?;
+                        return Some(date.and_time(time));
+                    }
+                }
+            }
+            date = date.succ_opt()?;
+            tight = false;
+        }
+        None
+    }
+
+    fn elapses_in<Z: TimeZone>(
+        &self,
+        zone: &Z,
+        after: DateTime<Utc>,
+        count: usize,
+    ) -> Vec<DateTime<FixedOffset>> {
+        let mut elapses = Vec::new();
+        let mut civil = after.with_timezone(zone).naive_local();
+        while elapses.len() < count {
+            let Some(next) = self.next_civil(civil) else {
+                break;
+            };
+            civil = next;
+            // Times skipped by a DST change never elapse
+            if let Some(time) = zone.from_local_datetime(&next).earliest()
+                && time.with_timezone(&Utc) > after
+            {
+                elapses.push(time.fixed_offset());
+            }
+        }
+        elapses
+    }
+
+    // The next `count` times the expression elapses after `after`
+    pub fn next_elapses(&self, after: DateTime<Utc>, count: usize) -> Vec<DateTime<FixedOffset>> {
+        match &self.zone {
+            Zone::Local => self.elapses_in(&Local, after, count),
+            Zone::Utc => self.elapses_in(&Utc, after, count),
+            Zone::Named(tz) => self.elapses_in(tz, after, count),
+        }
+    }
+}
+
+fn write_weekdays(f: &mut fmt::Formatter<'_>, bits: u8) -> fmt::Result {
+    let mut first = true;
+    let mut day = 0;
+    while day < 7 {
+        if bits & (1 << day) == 0 {
+            day += 1;
+            continue;
+        }
+        let mut last = day;
+        while last + 1 < 7 && bits & (1 << (last + 1)) != 0 {
+            last += 1;
+        }
+
+        if !first {
+            f.write_str(",")?;
+        }
+        first = false;
+        f.write_str(&WEEKDAYS[day][..3])?;
+        match last - day {
+            0 => {}
+            1 => write!(f, ",{}", &WEEKDAYS[last][..3])?,
+            _ => write!(f, "..{}", &WEEKDAYS[last][..3])?,
+        }
+        day = last + 1;
+    }
+    Ok(())
+}
+
+fn write_field(f: &mut fmt::Formatter<'_>, field: &Field, width: usize, usec: bool) -> fmt::Result {
+    if field.is_wildcard() {
+        return f.write_str("*");
+    }
+
+    let value = |f: &mut fmt::Formatter<'_>, value: u32, width: usize| {
+        if !usec {
+            return write!(f, "{:0width$}", value, width = width);
+        }
+        write!(f, "{:0width$}", value / USEC_PER_SEC, width = width)?;
+        match value % USEC_PER_SEC {
+            0 => Ok(()),
+            fraction => write!(f, ".{:06}", fraction),
+        }
+    };
+
+    for (i, component) in field.0.iter().enumerate() {
+        if i > 0 {
+            f.write_str(",")?;
+        }
+        value(f, component.start, width)?;
+        if let Some(stop) = component.stop {
+            f.write_str("..")?;
+            value(f, stop, width)?;
+        }
+        if let Some(repeat) = component.repeat {
+            f.write_str("/")?;
+            value(f, repeat, 0)?;
+        }
+    }
+    Ok(())
+}
+
+// The normalized form, as printed by `systemd-analyze calendar`
+impl fmt::Display for CalendarSpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.weekdays != ALL_WEEKDAYS {
+            write_weekdays(f, self.weekdays)?;
+            f.write_str(" ")?;
+        }
+
+        write_field(f, &self.year, 4, false)?;
+        f.write_str("-")?;
+        write_field(f, &self.month, 2, false)?;
+        f.write_str(if self.end_of_month { "~" } else { "-" })?;
+        write_field(f, &self.day, 2, false)?;
+        f.write_str(" ")?;
+        write_field(f, &self.hour, 2, false)?;
+        f.write_str(":")?;
+        write_field(f, &self.minute, 2, false)?;
+        f.write_str(":")?;
+        write_field(f, &self.second, 2, true)?;
+
+        match &self.zone {
+            Zone::Local => Ok(()),
+            Zone::Utc => f.write_str(" UTC"),
+            Zone::Named(tz) => write!(f, " {}", tz.name()),
+        }
+    }
+}
@@ -0,0 +1,102 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::*;
+
+// Quick fix attached to a diagnostic through its `data` field, so code
+// actions don't have to re-run the checks that produced it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "fix", rename_all = "camelCase")]
+pub enum Fix {
+    // Replace the text at `range` with `text`
+    Replace {
+        range: Range,
+        text: String,
+    },
+    // Delete the lines of an assignment
+    DeleteAssignment {
+        range: Range,
+    },
+    // Insert an empty `key=` assignment at `position`, resetting the
+    // values set before it
+    InsertReset {
+        position: Position,
+        key: String,
+    },
+    // Fill in the default value of an empty assignment at `position`
+    SetDefault {
+        position: Position,
+        key: String,
+        value: String,
+    },
+}
+
+impl Fix {
+    pub fn to_data(&self) -> Option<serde_json::Value> {
+        serde_json::to_value(self).ok()
+    }
+
+    pub fn from_data(data: &serde_json::Value) -> Option<Self> {
+        serde_json::from_value(data.clone()).ok()
+    }
+
+    // Several fixes for one diagnostic, the preferred one first
+    pub fn all_to_data(fixes: &[Fix]) -> Option<serde_json::Value> {
+        serde_json::to_value(fixes).ok()
+    }
+
+    // The fixes of a diagnostic, whether it has one or several
+    pub fn all_from_data(data: &serde_json::Value) -> Vec<Self> {
+        serde_json::from_value(data.clone())
+            .unwrap_or_else(|_| Self::from_data(data).into_iter().collect())
+    }
+
+    fn title(&self) -> String {
+        match self {
+            Fix::Replace { text, .. } => format!("Change to '{}'", text),
+            Fix::DeleteAssignment { .. } => "Delete the empty assignment".to_string(),
+            Fix::InsertReset { key, .. } => format!("Reset {}= first", key),
+            Fix::SetDefault { key, value, .. } => {
+                format!("Set {}= to its default '{}'", key, value)
+            }
+        }
+    }
+
+    fn edit(&self) -> TextEdit {
+        match self {
+            Fix::Replace { range, text } => TextEdit::new(*range, text.clone()),
+            Fix::DeleteAssignment { range } => TextEdit::new(*range, String::new()),
+            Fix::InsertReset { position, key } => {
+                TextEdit::new(Range::new(*position, *position), format!("{}=\n", key))
+            }
+            Fix::SetDefault {
+                position, value, ..
+            } => TextEdit::new(Range::new(*position, *position), value.clone()),
+        }
+    }
+}
+
+// Quick fixes for the diagnostics the client sent with a code action request
+pub fn quick_fixes(uri: &Url, diagnostics: &[Diagnostic]) -> Vec<CodeActionOrCommand> {
+    let mut actions = Vec::new();
+
+    for diagnostic in diagnostics {
+        let Some(data) = &diagnostic.data else {
+            continue;
+        };
+
+        for (i, fix) in Fix::all_from_data(data).into_iter().enumerate() {
+            let changes = HashMap::from([(uri.clone(), vec![fix.edit()])]);
+            actions.push(CodeActionOrCommand::CodeAction(CodeAction {
+                title: fix.title(),
+                kind: Some(CodeActionKind::QUICKFIX),
+                diagnostics: Some(vec![diagnostic.clone()]),
+                edit: Some(WorkspaceEdit::new(changes)),
+                is_preferred: Some(i == 0),
+                ..Default::default()
+            }));
+        }
+    }
+
+    actions
+}
@@ -0,0 +1,88 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use log::warn;
+use serde::Deserialize;
+
+// Name of the per-workspace configuration file
+pub const CONFIG_FILE: &str = ".systemd-ls.json";
+
+// Settings read from the `systemd` object of `.systemd-ls.json`
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct Config {
+    // Directories searched for unit files, in precedence order
+    pub unit_search_paths: Vec<PathBuf>,
+    pub diagnostics: DiagnosticsConfig,
+    // systemd release the units are written for; directives and values
+    // introduced later are reported and hidden from completion
+    pub target_systemd_version: Option<u32>,
+    pub formatting: FormattingConfig,
+}
+
+#[derive(Debug, Clone, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct DiagnosticsConfig {
+    pub enabled: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+pub struct FormattingConfig {
+    // Reorder sections as [Unit], the type's own sections, then [Install]
+    pub sort_sections: bool,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ConfigFile {
+    #[serde(default)]
+    systemd: Config,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            unit_search_paths: vec![
+                PathBuf::from("/etc/systemd/system"),
+                PathBuf::from("/run/systemd/system"),
+                PathBuf::from("/usr/local/lib/systemd/system"),
+                PathBuf::from("/usr/lib/systemd/system"),
+            ],
+            diagnostics: DiagnosticsConfig::default(),
+            target_systemd_version: None,
+            formatting: FormattingConfig::default(),
+        }
+    }
+}
+
+impl Default for DiagnosticsConfig {
+    fn default() -> Self {
+        Self { enabled: true }
+    }
+}
+
+impl Config {
+    pub fn from_json(text: &str) -> anyhow::Result<Self> {
+        let file: ConfigFile = serde_json::from_str(text)?;
+        Ok(file.systemd)
+    }
+
+    // Load the configuration file from a workspace root, falling back to the
+    // defaults when it is missing or invalid
+    pub fn load(root: &Path) -> Self {
+        let path = root.join(CONFIG_FILE);
+        let Ok(text) = fs::read_to_string(&path) else {
+            return Self::default();
+        };
+        Self::from_json(&text).unwrap_or_else(|e| {
+            warn!("Ignoring invalid {}: {}", path.display(), e);
+            Self::default()
+        })
+    }
+
+    // Whether something introduced in `since` exists on the target release
+    pub fn supports(&self, since: u32) -> bool {
+        self.target_systemd_version
+            .is_none_or(|target| since <= target)
+    }
+}
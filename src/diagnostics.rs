@@ -0,0 +1,549 @@
+use std::env;
+use std::ffi::OsStr;
+use std::path::{Path, PathBuf};
+
+use tower_lsp::lsp_types::*;
+
+use crate::code_actions::Fix;
+use crate::config::Config;
+use crate::document::Document;
+use crate::exec;
+use crate::line_index::LineIndex;
+use crate::parser::{Directive, Section, Span, UnitFile};
+use crate::schema::{self, SectionSchema, UnitType, ValueType};
+use crate::specifiers;
+use crate::suggest;
+use crate::units;
+use crate::validators;
+
+// Generate diagnostics for a document. `unit_type` comes from the file name
+// and limits the sections accepted; when unknown every section is accepted.
+pub fn generate_diagnostics(
+    document: &Document,
+    unit_type: Option<UnitType>,
+    config: &Config,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    if !config.diagnostics.enabled {
+        return diagnostics;
+    }
+
+    let unit = &document.unit;
+    let index = &document.index;
+
+    // Add syntax error diagnostics at the offending spans
+    for error in &unit.errors {
+        diagnostics.push(Diagnostic {
+            range: index.range(error.span),
+            severity: Some(DiagnosticSeverity::ERROR),
+            code: None,
+            code_description: None,
+            source: Some("systemd-lsp".into()),
+            message: format!("Systemd unit file syntax error: {}", error.message),
+            related_information: None,
+            tags: None,
+            data: None,
+        });
+    }
+
+    // Check section and key names against the schema
+    let sections: Vec<&SectionSchema> = match unit_type {
+        Some(unit_type) => unit_type.sections().collect(),
+        None => schema::SECTIONS.iter().collect(),
+    };
+    check_unknown_names(unit, index, unit_type, &sections, &mut diagnostics);
+
+    // Check for common systemd configuration errors
+    check_common_errors(unit, index, config, &mut diagnostics);
+
+    diagnostics
+}
+
+// Names starting with X- are reserved for extensions and ignored by systemd
+fn is_extension(name: &str) -> bool {
+    name.starts_with("X-")
+}
+
+// Warn about sections and keys systemd would ignore, suggesting the
+// closest known name as a quick fix
+fn check_unknown_names(
+    unit: &UnitFile,
+    index: &LineIndex,
+    unit_type: Option<UnitType>,
+    sections: &[&SectionSchema],
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for section in &unit.sections {
+        if section.name.is_empty() || is_extension(&section.name) {
+            continue;
+        }
+
+        let Some(schema) = sections.iter().find(|s| s.name == section.name) else {
+            // A section of another unit type is an error, an unknown one
+            // may be a typo or meant for a newer systemd
+            let (message, severity) = match (schema::section(&section.name), unit_type) {
+                (Some(_), Some(unit_type)) => (
+                    format!(
+                        "Section [{}] is not valid in .{} units",
+                        section.name,
+                        unit_type.suffix()
+                    ),
+                    DiagnosticSeverity::ERROR,
+                ),
+                _ => (
+                    format!("Unknown section [{}]", section.name),
+                    DiagnosticSeverity::WARNING,
+                ),
+            };
+            let suggestion = suggest::closest(&section.name, sections.iter().map(|s| s.name));
+            let mut diagnostic = unknown_name(index, section.name_span, message, suggestion);
+            diagnostic.severity = Some(severity);
+            diagnostics.push(diagnostic);
+            continue;
+        };
+
+        check_unknown_keys(section, schema, index, diagnostics);
+    }
+}
+
+fn check_unknown_keys(
+    section: &Section,
+    schema: &SectionSchema,
+    index: &LineIndex,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for directive in section.directives() {
+        let key = directive.key.as_str();
+        if is_extension(key) || schema.directive(key).is_some() {
+            continue;
+        }
+
+        let message = format!("Unknown key '{}' in section [{}]", key, section.name);
+        let suggestion = suggest::closest(key, schema.directives().map(|d| d.name));
+        diagnostics.push(unknown_name(index, directive.key_span, message, suggestion));
+    }
+}
+
+fn unknown_name(
+    index: &LineIndex,
+    span: Span,
+    mut message: String,
+    suggestion: Option<&str>,
+) -> Diagnostic {
+    let range = index.range(span);
+    let fix = suggestion.map(|name| {
+        message.push_str(&format!(". Did you mean '{}'?", name));
+        Fix::Replace {
+            range,
+            text: name.to_string(),
+        }
+    });
+
+    Diagnostic {
+        range,
+        severity: Some(DiagnosticSeverity::WARNING),
+        message,
+        source: Some("systemd-lsp".into()),
+        data: fix.and_then(|fix| fix.to_data()),
+        ..Default::default()
+    }
+}
+
+// Search path systemd itself uses, for when $PATH is unset
+const DEFAULT_PATH: &str = "/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin";
+
+// Find an executable in a `$PATH`-style search path
+pub fn find_executable(command: &str, search_path: &OsStr) -> Option<PathBuf> {
+    if command.is_empty() || command.contains('/') {
+        return None;
+    }
+    env::split_paths(search_path)
+        .filter(|dir| dir.is_absolute())
+        .map(|dir| dir.join(command))
+        .find(|path| is_executable(path))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .is_ok_and(|metadata| metadata.is_file() && metadata.permissions().mode() & 0o111 != 0)
+}
+
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+// Check the command lines of an Exec*= setting. Syntax errors point at the
+// offending token. Bare executable names are resolved from a fixed path
+// since systemd 239, so they only get a hint when they can't be found in
+// $PATH; older releases need the absolute path, offered as a fix.
+fn check_command(
+    directive: &Directive,
+    index: &LineIndex,
+    config: &Config,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    let key = &directive.key;
+    let line = exec::parse(&directive.value);
+    for error in &line.errors {
+        diagnostics.push(Diagnostic {
+            range: index.range(directive.source_span(error.span.start, error.span.end)),
+            severity: Some(DiagnosticSeverity::ERROR),
+            message: format!("{} in {}=", error.message, key),
+            source: Some("systemd-lsp".into()),
+            ..Default::default()
+        });
+    }
+
+    for command in &line.commands {
+        let Some(executable) = &command.executable else {
+            continue;
+        };
+        let path = executable.text.as_str();
+        if path.starts_with('/')
+            || !executable.specifiers.is_empty()
+            || !executable.variables.is_empty()
+        {
+            continue;
+        }
+        let range = index.range(directive.source_span(executable.span.start, executable.span.end));
+
+        if path.contains('/') {
+            diagnostics.push(Diagnostic {
+                range,
+                severity: Some(DiagnosticSeverity::ERROR),
+                message: format!(
+                    "Executable '{}' in {}= must be an absolute path or a plain file name",
+                    path, key
+                ),
+                source: Some("systemd-lsp".into()),
+                ..Default::default()
+            });
+            continue;
+        }
+
+        let search_path = env::var_os("PATH").unwrap_or_else(|| DEFAULT_PATH.into());
+        let found = find_executable(path, &search_path);
+        let (severity, mut message) = if config.supports(239) {
+            // Resolvable bare names are the norm and not worth a diagnostic
+            if found.is_some() {
+                continue;
+            }
+            (
+                DiagnosticSeverity::HINT,
+                format!(
+                    "'{}' is not found in $PATH; systemd looks it up in its fixed search path",
+                    path
+                ),
+            )
+        } else {
+            (
+                DiagnosticSeverity::WARNING,
+                format!(
+                    "{}= needs an absolute path before systemd 239, which resolves plain file names",
+                    key
+                ),
+            )
+        };
+        let fix = found.map(|found| {
+            let found = found.display().to_string();
+            message.push_str(&format!(". Did you mean '{}'?", found));
+            Fix::Replace { range, text: found }
+        });
+        diagnostics.push(Diagnostic {
+            range,
+            severity: Some(severity),
+            message,
+            source: Some("systemd-lsp".into()),
+            data: fix.and_then(|fix| fix.to_data()),
+            ..Default::default()
+        });
+    }
+}
+
+// Check the `%` specifiers of a value. An unknown letter makes systemd
+// ignore the setting; a `%` not followed by a letter was most likely meant
+// as a literal `%%`.
+fn check_specifiers(directive: &Directive, index: &LineIndex, diagnostics: &mut Vec<Diagnostic>) {
+    for specifier in specifiers::scan(&directive.value) {
+        let (span, message, fix) = match specifier.letter {
+            Some(letter) if specifiers::find(letter).is_some() => continue,
+            Some(letter) if letter.is_ascii_alphabetic() => (
+                specifier.span,
+                format!("Unknown specifier '%{}' in {}=", letter, directive.key),
+                None,
+            ),
+            _ => {
+                let span = Span::new(specifier.span.start, specifier.span.start + 1);
+                let message = format!(
+                    "Unescaped '%' in {}=; write '%%' for a literal percent sign",
+                    directive.key
+                );
+                (span, message, Some("%%"))
+            }
+        };
+        let range = index.range(directive.source_span(span.start, span.end));
+        let fix = fix.map(|text| Fix::Replace {
+            range,
+            text: text.to_string(),
+        });
+        diagnostics.push(Diagnostic {
+            range,
+            severity: Some(DiagnosticSeverity::ERROR),
+            message,
+            source: Some("systemd-lsp".into()),
+            data: fix.and_then(|fix| fix.to_data()),
+            ..Default::default()
+        });
+    }
+}
+
+// Check for common systemd configuration errors
+fn check_common_errors(
+    unit: &UnitFile,
+    index: &LineIndex,
+    config: &Config,
+    diagnostics: &mut Vec<Diagnostic>,
+) {
+    for (section, directive) in unit.directives() {
+        let key = directive.key.as_str();
+        let value = directive.value.as_str();
+        let schema = schema::lookup(&section.name, key);
+
+        // Check the directive exists on the target systemd release
+        if let (Some(schema), Some(target)) = (schema, config.target_systemd_version)
+            && !config.supports(schema.since)
+        {
+            diagnostics.push(Diagnostic {
+                range: index.range(directive.key_span),
+                severity: Some(DiagnosticSeverity::WARNING),
+                message: format!(
+                    "{}= was added in systemd {} and is ignored by systemd {}",
+                    key, schema.since, target
+                ),
+                source: Some("systemd-lsp".into()),
+                ..Default::default()
+            });
+        }
+
+        // Check for empty values. Empty assignments reset list settings.
+        if value.is_empty() {
+            if !schema.is_some_and(|schema| schema.empty_resets) {
+                let range = index.range(directive.span);
+                // The lines of the assignment with their line break, if any
+                let text = index.text();
+                let end = text[directive.span.end..]
+                    .find('\n')
+                    .map_or(text.len(), |len| directive.span.end + len + 1);
+                let mut fixes = vec![Fix::DeleteAssignment {
+                    range: Range::new(Position::new(range.start.line, 0), index.position(end)),
+                }];
+                // Spelling out the default resets the setting explicitly
+                if let Some(schema) = schema.filter(|schema| !schema.default.is_empty()) {
+                    fixes.push(Fix::SetDefault {
+                        position: range.end,
+                        key: key.to_string(),
+                        value: schema.default.to_string(),
+                    });
+                }
+                diagnostics.push(Diagnostic {
+                    range,
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    message: format!("Key '{}' has an empty value", key),
+                    source: Some("systemd-lsp".into()),
+                    data: Fix::all_to_data(&fixes),
+                    ..Default::default()
+                });
+            }
+            continue;
+        }
+
+        let Some(schema) = schema else {
+            continue;
+        };
+
+        if schema.value.expands_specifiers() {
+            check_specifiers(directive, index, diagnostics);
+        }
+
+        if schema.value == ValueType::Command {
+            check_command(directive, index, config, diagnostics);
+            continue;
+        }
+
+        // Check the value against the grammar of its type
+        if let Err(error) = validators::validate(schema.value, value) {
+            diagnostics.push(Diagnostic {
+                range: index.range(directive.source_span(error.span.start, error.span.end)),
+                severity: Some(DiagnosticSeverity::ERROR),
+                message: format!("{} in {}=", error.message, key),
+                source: Some("systemd-lsp".into()),
+                ..Default::default()
+            });
+            continue;
+        }
+
+        // Check enum values against the schema
+        let values = schema.enum_values();
+        if values.is_empty() {
+            continue;
+        }
+        match schema.find_enum_value(value) {
+            None => {
+                let valid: Vec<&str> = values.iter().map(|v| v.name).collect();
+                let range = index.range(directive.value_span);
+                let fix = suggest::closest(value, valid.iter().copied()).map(|name| Fix::Replace {
+                    range,
+                    text: name.to_string(),
+                });
+                diagnostics.push(Diagnostic {
+                    range,
+                    severity: Some(DiagnosticSeverity::ERROR),
+                    message: format!(
+                        "Invalid value '{}' for {}=. Valid values: {}",
+                        value,
+                        key,
+                        valid.join(", ")
+                    ),
+                    source: Some("systemd-lsp".into()),
+                    data: fix.and_then(|fix| fix.to_data()),
+                    ..Default::default()
+                });
+            }
+            Some(enum_value) if !config.supports(enum_value.since) => {
+                diagnostics.push(Diagnostic {
+                    range: index.range(directive.value_span),
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    message: format!(
+                        "{}={} was added in systemd {} and is not accepted by systemd {}",
+                        key,
+                        value,
+                        enum_value.since,
+                        config.target_systemd_version.unwrap_or_default()
+                    ),
+                    source: Some("systemd-lsp".into()),
+                    ..Default::default()
+                });
+            }
+            Some(_) => {}
+        }
+    }
+}
+
+// Check a drop-in against the unit it extends. `base` is the unit file, if
+// it was found.
+pub fn dropin_diagnostics(
+    document: &Document,
+    unit_name: &str,
+    base: Option<&Document>,
+) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let Some(base) = base else {
+        return diagnostics;
+    };
+
+    // A second ExecStart= is only allowed for Type=oneshot services, so
+    // drop-ins have to reset the command before replacing it
+    let service_type = document
+        .unit
+        .get("Service", "Type")
+        .or_else(|| base.unit.get("Service", "Type"))
+        .unwrap_or("simple");
+    let base_has_command = base
+        .unit
+        .get_all("Service", "ExecStart")
+        .iter()
+        .any(|value| !value.is_empty());
+    let first_exec_start = document
+        .unit
+        .directives()
+        .find(|(section, directive)| section.name == "Service" && directive.key == "ExecStart");
+
+    if let Some((_, directive)) = first_exec_start
+        && base_has_command
+        && service_type != "oneshot"
+        && !directive.value.is_empty()
+    {
+        let range = document.index.range(directive.key_span);
+        let fix = Fix::InsertReset {
+            position: Position::new(range.start.line, 0),
+            key: directive.key.clone(),
+        };
+        diagnostics.push(Diagnostic {
+            range,
+            severity: Some(DiagnosticSeverity::WARNING),
+            message: format!(
+                "ExecStart= in a drop-in for {} adds a second command, which only Type=oneshot services allow. Add an empty 'ExecStart=' line before it to replace the command",
+                unit_name
+            ),
+            source: Some("systemd-lsp".into()),
+            data: fix.to_data(),
+            ..Default::default()
+        });
+    }
+
+    diagnostics
+}
+
+// Checks that depend on whether the file belongs to a template unit: `%i`
+// and `%I` are empty outside instances, and DefaultInstance= only applies
+// to templates. Instance names count as templates, as their files are
+// usually links to the template.
+pub fn template_diagnostics(document: &Document, unit_name: &str) -> Vec<Diagnostic> {
+    let mut diagnostics = Vec::new();
+    let index = &document.index;
+    let has_instance = unit_name.contains('@');
+
+    for (section, directive) in document.unit.directives() {
+        if !has_instance {
+            for specifier in specifiers::scan(&directive.value) {
+                let Some(letter @ ('i' | 'I')) = specifier.letter else {
+                    continue;
+                };
+                let span = directive.source_span(specifier.span.start, specifier.span.end);
+                diagnostics.push(Diagnostic {
+                    range: index.range(span),
+                    severity: Some(DiagnosticSeverity::WARNING),
+                    message: format!(
+                        "%{} expands to an empty string: {} is not a template unit",
+                        letter, unit_name
+                    ),
+                    source: Some("systemd-lsp".into()),
+                    ..Default::default()
+                });
+            }
+        }
+
+        if section.name != "Install" || directive.key != "DefaultInstance" {
+            continue;
+        }
+        let value = directive.value.as_str();
+        if !has_instance {
+            diagnostics.push(Diagnostic {
+                range: index.range(directive.key_span),
+                severity: Some(DiagnosticSeverity::WARNING),
+                message: format!(
+                    "DefaultInstance= only applies to template units such as foo@.service, not {}",
+                    unit_name
+                ),
+                source: Some("systemd-lsp".into()),
+                ..Default::default()
+            });
+        } else if !value.is_empty() && !value.contains('%') && !units::is_valid_instance(value) {
+            diagnostics.push(Diagnostic {
+                range: index.range(directive.value_span),
+                severity: Some(DiagnosticSeverity::ERROR),
+                message: format!(
+                    "Invalid instance name '{}': use letters, digits and ':-_.\\@', escaping other characters with systemd-escape",
+                    value
+                ),
+                source: Some("systemd-lsp".into()),
+                ..Default::default()
+            });
+        }
+    }
+
+    diagnostics
+}
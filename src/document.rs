@@ -0,0 +1,109 @@
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+
+use tower_lsp::lsp_types::{TextDocumentContentChangeEvent, Url};
+
+use crate::line_index::LineIndex;
+use crate::parser::{self, UnitFile};
+
+// An open buffer together with its parse tree
+#[derive(Debug)]
+pub struct Document {
+    pub version: i32,
+    pub unit: UnitFile,
+    pub index: LineIndex,
+}
+
+impl Document {
+    pub fn new(text: &str, version: i32) -> Self {
+        Self {
+            version,
+            unit: parser::parse(text),
+            index: LineIndex::new(text),
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        self.index.text()
+    }
+
+    // Apply content changes in order, producing the document at `version`.
+    // Ranged changes are incremental edits; a change without a range
+    // replaces the whole text. A reversed range is treated as its
+    // normalized form rather than trusted to be ordered.
+    pub fn apply_changes(&self, changes: &[TextDocumentContentChangeEvent], version: i32) -> Self {
+        let mut text = self.text().to_string();
+        // `self.index` describes the text until the first change lands; after
+        // that a fresh index is built only if another ranged change needs it
+        let mut rebuilt: Option<LineIndex> = None;
+        for (i, change) in changes.iter().enumerate() {
+            match change.range {
+                Some(range) => {
+                    if i > 0 {
+                        rebuilt = Some(LineIndex::new(&text));
+                    }
+                    let span = rebuilt.as_ref().unwrap_or(&self.index).span(range);
+                    let (start, end) = (span.start.min(span.end), span.start.max(span.end));
+                    text.replace_range(start..end, &change.text);
+                }
+                None => text = change.text.clone(),
+            }
+        }
+        Self::new(&text, version)
+    }
+}
+
+// Concurrent store of the documents the client has opened.
+//
+// Entries are immutable snapshots: readers get an `Arc<Document>` that stays
+// valid while a newer version is being inserted.
+#[derive(Debug, Default)]
+pub struct DocumentStore {
+    documents: RwLock<HashMap<Url, Arc<Document>>>,
+}
+
+impl DocumentStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, uri: &Url) -> Option<Arc<Document>> {
+        self.documents.read().unwrap().get(uri).cloned()
+    }
+
+    // Insert or replace a document, returning the stored snapshot
+    pub fn insert(&self, uri: Url, text: &str, version: i32) -> Arc<Document> {
+        let document = Arc::new(Document::new(text, version));
+        self.documents
+            .write()
+            .unwrap()
+            .insert(uri, document.clone());
+        document
+    }
+
+    // Apply a batch of changes to an open document. Returns `None` if the
+    // document is not open or the batch is older than the stored version.
+    pub fn update(
+        &self,
+        uri: &Url,
+        changes: &[TextDocumentContentChangeEvent],
+        version: i32,
+    ) -> Option<Arc<Document>> {
+        let mut documents = self.documents.write().unwrap();
+        let current = documents.get(uri)?;
+        if version < current.version {
+            return None;
+        }
+        let document = Arc::new(current.apply_changes(changes, version));
+        documents.insert(uri.clone(), document.clone());
+        Some(document)
+    }
+
+    pub fn remove(&self, uri: &Url) -> Option<Arc<Document>> {
+        self.documents.write().unwrap().remove(uri)
+    }
+
+    pub fn uris(&self) -> Vec<Url> {
+        self.documents.read().unwrap().keys().cloned().collect()
+    }
+}
@@ -0,0 +1,191 @@
+// Drop-in files (`foo.service.d/*.conf`) and the effective unit they produce.
+//
+// Follows systemd's unit_file_find_dropin_paths(): drop-ins are collected
+// from every search directory, a file shadows same-named files in lower
+// precedence directories, and the result is applied in file name order.
+
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::{Range, TextDocumentIdentifier, Url};
+
+use crate::document::Document;
+use crate::index::{self, UnitIndex};
+use crate::schema::{self, UnitType};
+use crate::units;
+
+// Names of the drop-in directories systemd reads for a unit, least
+// specific first, e.g. for `foo-bar@baz.service`: `service.d`,
+// `foo-.service.d`, `foo-bar@.service.d` and `foo-bar@baz.service.d`
+pub fn dropin_dir_names(unit_name: &str) -> Vec<String> {
+    let Some(unit_type) = UnitType::from_name(unit_name) else {
+        return Vec::new();
+    };
+    let suffix = unit_type.suffix();
+    let stem = &unit_name[..unit_name.len() - suffix.len() - 1];
+    let prefix_stem = stem.split_once('@').map_or(stem, |(prefix, _)| prefix);
+
+    let mut names = vec![format!("{}.d", suffix)];
+    for (i, _) in prefix_stem.match_indices('-') {
+        names.push(format!("{}-.{}.d", &prefix_stem[..i], suffix));
+    }
+    if let Some(template) = units::template_of(unit_name) {
+        names.push(format!("{}.d", template));
+    }
+    names.push(format!("{}.d", unit_name));
+    names
+}
+
+// Unit a drop-in file belongs to, from its `.d` directory. Type-wide and
+// prefix drop-ins extend many units and have none.
+pub fn extended_unit(path: &Path) -> Option<String> {
+    if path.extension()? != "conf" {
+        return None;
+    }
+    let dir = path.parent()?.file_name()?.to_str()?;
+    let unit = dir.strip_suffix(".d")?;
+    let stem = unit.rsplit_once('.')?.0;
+    if stem.is_empty() || stem.ends_with('-') || UnitType::from_name(unit).is_none() {
+        return None;
+    }
+    Some(unit.to_string())
+}
+
+// Directories of the indexed workspace holding drop-in directories that
+// apply to a unit, such as `units` for `units/app.service.d/env.conf`
+pub fn workspace_dirs(index: &UnitIndex, unit_name: &str) -> Vec<PathBuf> {
+    let names = dropin_dir_names(unit_name);
+    let mut dirs: Vec<PathBuf> = index
+        .uris()
+        .iter()
+        .filter(|uri| index::unit_name(uri).is_none())
+        .filter_map(|uri| {
+            let path = uri.to_file_path().ok()?;
+            let dropin_dir = path.parent()?;
+            let name = dropin_dir.file_name()?.to_str()?;
+            names
+                .iter()
+                .any(|dir_name| dir_name == name)
+                .then(|| dropin_dir.parent().map(Path::to_path_buf))?
+        })
+        .collect();
+    dirs.sort();
+    dirs.dedup();
+    dirs
+}
+
+// Drop-in files for a unit in applying order. `dirs` are searched in order
+// of precedence; within one of them the unit's own directory beats the
+// template's, which beats the prefix and type-wide ones.
+pub fn find_dropins(unit_name: &str, dirs: &[PathBuf]) -> Vec<PathBuf> {
+    let names = dropin_dir_names(unit_name);
+    let mut files: BTreeMap<String, PathBuf> = BTreeMap::new();
+
+    for dir in dirs {
+        for name in names.iter().rev() {
+            let Ok(entries) = fs::read_dir(dir.join(name)) else {
+                continue;
+            };
+            for entry in entries.flatten() {
+                let path = entry.path();
+                let file_name = entry.file_name().to_string_lossy().to_string();
+                if file_name.ends_with(".conf") && path.is_file() {
+                    files.entry(file_name).or_insert(path);
+                }
+            }
+        }
+    }
+
+    files.into_values().collect()
+}
+
+// One assignment in the effective unit, with the file it comes from
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectiveDirective {
+    pub key: String,
+    pub value: String,
+    pub uri: Url,
+    pub range: Range,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EffectiveSection {
+    pub name: String,
+    pub directives: Vec<EffectiveDirective>,
+}
+
+// Apply a unit file and its drop-ins in order. Later assignments override
+// earlier ones; list settings accumulate, and an empty assignment resets
+// them.
+pub fn merge(files: &[(Url, &Document)]) -> Vec<EffectiveSection> {
+    let mut sections: Vec<EffectiveSection> = Vec::new();
+
+    for (uri, document) in files {
+        for (section, directive) in document.unit.directives() {
+            let index = match sections.iter().position(|s| s.name == section.name) {
+                Some(index) => index,
+                None => {
+                    sections.push(EffectiveSection {
+                        name: section.name.clone(),
+                        directives: Vec::new(),
+                    });
+                    sections.len() - 1
+                }
+            };
+            let directives = &mut sections[index].directives;
+
+            let repeatable = schema::lookup(&section.name, &directive.key)
+                .is_some_and(|schema| schema.repeatable);
+            if !repeatable || directive.value.is_empty() {
+                directives.retain(|d| d.key != directive.key);
+            }
+            if !directive.value.is_empty() {
+                directives.push(EffectiveDirective {
+                    key: directive.key.clone(),
+                    value: directive.value.clone(),
+                    uri: uri.clone(),
+                    range: document.index.range(directive.span),
+                });
+            }
+        }
+    }
+
+    sections
+}
+
+// Every file as `systemctl cat` prints them, each under a comment naming it
+pub fn cat(files: &[(Url, &Document)]) -> String {
+    files
+        .iter()
+        .map(|(uri, document)| {
+            let path = uri
+                .to_file_path()
+                .map(|path| path.display().to_string())
+                .unwrap_or_else(|_| uri.to_string());
+            format!("# {}\n{}", path, document.text().trim_end())
+        })
+        .collect::<Vec<_>>()
+        .join("\n\n")
+        + "\n"
+}
+
+// Parameters of the `systemd/effectiveUnit` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveUnitParams {
+    pub text_document: TextDocumentIdentifier,
+}
+
+// Result of the `systemd/effectiveUnit` request
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EffectiveUnit {
+    pub unit: String,
+    // The unit file followed by its drop-ins, in applying order
+    pub files: Vec<Url>,
+    // The files concatenated like `systemctl cat` prints them
+    pub text: String,
+    pub sections: Vec<EffectiveSection>,
+}
@@ -0,0 +1,343 @@
+// Command lines of Exec*= settings, split the way systemd's
+// config_parse_exec() does.
+//
+// A line holds one or more commands separated by a lone `;`. Each command
+// starts with optional prefixes, followed by the executable and its
+// arguments. Words are unquoted and C-unescaped; `%` specifiers and `$VAR`
+// references are recorded by position, as systemd expands them later.
+// Spans are logical offsets into the directive value.
+
+use crate::parser::Span;
+use crate::validators::ValueError;
+
+// A `$VAR` or `${VAR}` reference in an argument
+#[derive(Debug, Clone, PartialEq)]
+pub struct Variable {
+    pub name: String,
+    pub span: Span,
+    // `$VAR` as a whole word is split at whitespace into several arguments
+    pub split: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Word {
+    pub span: Span,
+    // The word after unquoting and unescaping
+    pub text: String,
+    // `%` specifiers, `%%` excluded
+    pub specifiers: Vec<Span>,
+    pub variables: Vec<Variable>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Command {
+    // The prefix characters, e.g. `-@`, and where they are
+    pub prefixes: String,
+    pub prefix_span: Span,
+    pub executable: Option<Word>,
+    // With the `@` prefix, the first argument is argv[0]
+    pub arguments: Vec<Word>,
+}
+
+impl Command {
+    pub fn has_prefix(&self, prefix: char) -> bool {
+        self.prefixes.contains(prefix)
+    }
+
+    // Whether `$VAR` references are expanded, i.e. no `:` prefix
+    pub fn expands_variables(&self) -> bool {
+        !self.has_prefix(':')
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CommandLine {
+    pub commands: Vec<Command>,
+    // Lone `;` separating commands
+    pub separators: Vec<Span>,
+    pub errors: Vec<ValueError>,
+}
+
+// Characters that may prefix the command of an Exec*= line
+pub const PREFIXES: &[char] = &['@', '-', ':', '+', '!'];
+
+// Parse an Exec*= value
+pub fn parse(value: &str) -> CommandLine {
+    let mut line = CommandLine::default();
+    let mut words = Vec::new();
+    let mut start = 0;
+
+    while let Some((word, next)) = next_word(value, start, &mut line.errors) {
+        start = next;
+        if &value[word.span.start..word.span.end] == ";" {
+            if words.is_empty() {
+                line.errors.push(ValueError {
+                    span: word.span,
+                    message: "Missing command before ';'".to_string(),
+                });
+            } else {
+                let words = std::mem::take(&mut words);
+                line.commands.push(command(value, words, &mut line.errors));
+            }
+            line.separators.push(word.span);
+            continue;
+        }
+        words.push(word);
+    }
+    if !words.is_empty() {
+        line.commands.push(command(value, words, &mut line.errors));
+    }
+
+    line
+}
+
+// Split the prefixes off the first word and check the rest of a command
+fn command(value: &str, mut words: Vec<Word>, errors: &mut Vec<ValueError>) -> Command {
+    let first = words.remove(0);
+    let raw_start = first.span.start;
+    let prefixes: String = value[raw_start..first.span.end]
+        .chars()
+        .take_while(|c| PREFIXES.contains(c))
+        .collect();
+    let prefix_span = Span::new(raw_start, raw_start + prefixes.len());
+    check_prefixes(&prefixes, raw_start, errors);
+
+    let executable = if prefixes.len() == first.text.len() {
+        // The prefixes stand alone, or are all there is
+        if words.is_empty() {
+            errors.push(ValueError {
+                span: prefix_span,
+                message: "Missing executable after the prefix".to_string(),
+            });
+            None
+        } else {
+            Some(words.remove(0))
+        }
+    } else {
+        Some(Word {
+            span: Span::new(prefix_span.end, first.span.end),
+            text: first.text[prefixes.len()..].to_string(),
+            ..first
+        })
+    };
+
+    let command = Command {
+        prefixes,
+        prefix_span,
+        executable,
+        arguments: words,
+    };
+
+    if let Some(executable) = &command.executable {
+        if command.expands_variables()
+            && let Some(variable) = executable.variables.first()
+        {
+            errors.push(ValueError {
+                span: variable.span,
+                message: "The executable can't be a variable".to_string(),
+            });
+        }
+        if command.has_prefix('@') && command.arguments.is_empty() {
+            errors.push(ValueError {
+                span: executable.span,
+                message: "The '@' prefix needs argv[0] after the executable".to_string(),
+            });
+        }
+    }
+
+    command
+}
+
+// Prefixes may come in any order, each at most once, but `+`, `!` and `!!`
+// exclude each other
+fn check_prefixes(prefixes: &str, start: usize, errors: &mut Vec<ValueError>) {
+    let mut seen = String::new();
+    for (i, c) in prefixes.char_indices() {
+        let span = Span::new(start + i, start + i + 1);
+        let exclusive = "The '+', '!' and '!!' prefixes can't be combined";
+        let message = match c {
+            '!' if seen.contains('+') => Some(exclusive.to_string()),
+            // A second `!` makes `!!`
+            '!' if seen.matches('!').count() == 1 => None,
+            '!' if seen.contains('!') => Some("Duplicate '!!' prefix".to_string()),
+            '+' if seen.contains('!') => Some(exclusive.to_string()),
+            _ if seen.contains(c) => Some(format!("Duplicate '{}' prefix", c)),
+            _ => None,
+        };
+        seen.push(c);
+        if let Some(message) = message {
+            errors.push(ValueError { span, message });
+        }
+    }
+}
+
+// The next word starting at or after `start`, and where the one after it
+// may start
+fn next_word(value: &str, start: usize, errors: &mut Vec<ValueError>) -> Option<(Word, usize)> {
+    let start = start + value[start..].find(|c: char| !c.is_whitespace())?;
+
+    // `\;` is a literal semicolon rather than a separator
+    let end = value[start..]
+        .find(char::is_whitespace)
+        .map_or(value.len(), |len| start + len);
+    if &value[start..end] == "\\;" {
+        let word = Word {
+            span: Span::new(start, end),
+            text: ";".to_string(),
+            specifiers: Vec::new(),
+            variables: Vec::new(),
+        };
+        return Some((word, end));
+    }
+
+    let mut text = String::new();
+    let mut quote: Option<(char, usize)> = None;
+    let mut i = start;
+    while i < value.len() {
+        let c = value[i..].chars().next()?;
+        match (c, quote) {
+            (c, None) if c.is_whitespace() => break,
+            ('\'' | '"', None) => quote = Some((c, i)),
+            (c, Some((open, _))) if c == open => quote = None,
+            ('\\', _) => {
+                let (len, unescaped) = unescape(&value[i + 1..]);
+                match unescaped {
+                    Some(unescaped) => text.push(unescaped),
+                    None if len == 0 => errors.push(ValueError {
+                        span: Span::new(i, i + 1),
+                        message: "Trailing backslash".to_string(),
+                    }),
+                    None => errors.push(ValueError {
+                        span: Span::new(i, i + 1 + len),
+                        message: format!("Invalid escape '{}'", &value[i..i + 1 + len]),
+                    }),
+                }
+                i += 1 + len;
+                continue;
+            }
+            (c, _) => text.push(c),
+        }
+        i += c.len_utf8();
+    }
+    if let Some((open, position)) = quote {
+        errors.push(ValueError {
+            span: Span::new(position, i),
+            message: format!(
+                "Unterminated {} quote",
+                if open == '"' { "double" } else { "single" }
+            ),
+        });
+    }
+
+    let span = Span::new(start, i);
+    let mut word = Word {
+        span,
+        text,
+        specifiers: Vec::new(),
+        variables: Vec::new(),
+    };
+    scan_expansions(&value[start..i], start, &mut word);
+    Some((word, i))
+}
+
+// Record the specifiers and variable references of a word
+fn scan_expansions(raw: &str, start: usize, word: &mut Word) {
+    let bytes = raw.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'%' => {
+                if bytes.get(i + 1) != Some(&b'%') {
+                    let len = raw[i + 1..].chars().next().map_or(0, char::len_utf8);
+                    word.specifiers
+                        .push(Span::new(start + i, start + i + 1 + len));
+                }
+                i += 2;
+                continue;
+            }
+            b'$' if bytes.get(i + 1) == Some(&b'$') => {
+                i += 2;
+                continue;
+            }
+            b'$' if bytes.get(i + 1) == Some(&b'{') => {
+                if let Some(len) = raw[i + 2..].find('}')
+                    && is_variable_name(&raw[i + 2..i + 2 + len])
+                {
+                    word.variables.push(Variable {
+                        name: raw[i + 2..i + 2 + len].to_string(),
+                        span: Span::new(start + i, start + i + 3 + len),
+                        split: false,
+                    });
+                    i += 3 + len;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+
+    // `$VAR` is only expanded as a whole word
+    let name = word.text.strip_prefix('$').unwrap_or_default();
+    if is_variable_name(name) && raw.ends_with(name) {
+        let prefix = raw.len() - name.len() - 1;
+        word.variables.push(Variable {
+            name: name.to_string(),
+            span: Span::new(start + prefix, start + raw.len()),
+            split: true,
+        });
+    }
+}
+
+fn is_variable_name(name: &str) -> bool {
+    !name.is_empty()
+        && !name.starts_with(|c: char| c.is_ascii_digit())
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+// Decode the C escape after a backslash, as systemd's cunescape_one().
+// Returns the length of the escape after the backslash and the character,
+// or `None` if the escape is invalid.
+fn unescape(text: &str) -> (usize, Option<char>) {
+    let Some(c) = text.chars().next() else {
+        return (0, None);
+    };
+    let simple = match c {
+        'a' => Some('\x07'),
+        'b' => Some('\x08'),
+        'f' => Some('\x0c'),
+        'n' => Some('\n'),
+        'r' => Some('\r'),
+        't' => Some('\t'),
+        'v' => Some('\x0b'),
+        's' => Some(' '),
+        '\\' | '"' | '\'' => Some(c),
+        _ => None,
+    };
+    if let Some(simple) = simple {
+        return (1, Some(simple));
+    }
+
+    let (digits, radix) = match c {
+        'x' => (2, 16),
+        'u' => (4, 16),
+        'U' => (8, 16),
+        '0'..='7' => (3, 8),
+        _ => return (c.len_utf8(), None),
+    };
+    let skip = if radix == 8 { 0 } else { 1 };
+    let number: String = text[skip..]
+        .chars()
+        .take(digits)
+        .take_while(|c| c.is_digit(radix))
+        .collect();
+    let len = skip + number.len();
+    if number.len() < digits {
+        return (len.max(1), None);
+    }
+    let decoded = u32::from_str_radix(&number, radix)
+        .ok()
+        .filter(|&code| code != 0)
+        .and_then(char::from_u32);
+    (len, decoded)
+}
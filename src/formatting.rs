@@ -0,0 +1,303 @@
+// Formatting of unit files.
+//
+// `Key = value` becomes `Key=value`, trailing whitespace is dropped, runs
+// of blank lines collapse to one and sections are separated by exactly one
+// blank line. Continuation lines are kept as written, apart from trailing
+// whitespace. Comments after the last directive of a section, or right
+// above the first header, describe the next section and move with it when
+// sections are reordered.
+
+use tower_lsp::lsp_types::{Position, Range, TextEdit};
+
+use crate::config::FormattingConfig;
+use crate::document::Document;
+use crate::parser::{self, Entry, Section};
+
+// Lines of a run of entries. `None` stands for a blank line.
+fn entry_lines(text: &str, entries: &[Entry]) -> Vec<Option<String>> {
+    let mut lines = Vec::new();
+    for entry in entries {
+        match entry {
+            Entry::Blank(_) => lines.push(None),
+            Entry::Comment(comment) => lines.push(Some(comment.text.clone())),
+            Entry::Invalid(span) => lines.push(Some(text[span.start..span.end].trim().to_string())),
+            Entry::Directive(directive) => {
+                let after_key = text[directive.key_span.end..directive.span.end].trim_start();
+                let value = after_key.strip_prefix('=').unwrap_or(after_key);
+                let assignment = format!("{}={}", directive.key, value.trim_start());
+                lines.extend(
+                    assignment
+                        .split('\n')
+                        .map(|line| Some(line.trim_end().to_string())),
+                );
+            }
+        }
+    }
+    lines
+}
+
+// Drop leading and trailing blank lines and collapse runs of them
+fn collapse_blank_lines(lines: Vec<Option<String>>) -> Vec<String> {
+    let mut result: Vec<String> = Vec::new();
+    let mut blank = false;
+    for line in lines {
+        match line {
+            None => blank = !result.is_empty(),
+            Some(line) => {
+                if blank {
+                    result.push(String::new());
+                    blank = false;
+                }
+                result.push(line);
+            }
+        }
+    }
+    result
+}
+
+// A section with the comments describing it
+struct Block {
+    lead: Vec<Option<String>>,
+    header: String,
+    body: Vec<Option<String>>,
+}
+
+fn header(text: &str, section: &Section) -> String {
+    let raw = text[section.header_span.start..section.header_span.end].trim();
+    if raw.ends_with(']') {
+        format!("[{}]", section.name)
+    } else {
+        raw.to_string()
+    }
+}
+
+// Position of a section in the canonical order
+fn section_rank(name: &str) -> u8 {
+    match name {
+        "Unit" => 0,
+        "Install" => 2,
+        _ => 1,
+    }
+}
+
+// Format a unit file
+pub fn format(text: &str, config: &FormattingConfig) -> String {
+    let unit = parser::parse(text);
+    let mut blocks: Vec<Block> = Vec::new();
+
+    // Comments right above the first header describe that section
+    let mut preamble = &unit.preamble[..];
+    if !unit.sections.is_empty() {
+        let comments = preamble
+            .iter()
+            .rev()
+            .take_while(|entry| matches!(entry, Entry::Comment(_)))
+            .count();
+        preamble = &preamble[..preamble.len() - comments];
+    }
+    let mut lead = entry_lines(text, &unit.preamble[preamble.len()..]);
+
+    for (i, section) in unit.sections.iter().enumerate() {
+        let mut body = entry_lines(text, &section.entries);
+
+        // Trailing comments belong to the next section
+        if i + 1 < unit.sections.len() {
+            let last_directive = section
+                .entries
+                .iter()
+                .rposition(|entry| matches!(entry, Entry::Directive(_) | Entry::Invalid(_)));
+            let trailing_start = match last_directive {
+                Some(index) => entry_lines(text, &section.entries[..=index]).len(),
+                None => body.len(),
+            };
+            let next_lead = body.split_off(trailing_start);
+            blocks.push(Block {
+                lead: std::mem::replace(&mut lead, next_lead),
+                header: header(text, section),
+                body,
+            });
+        } else {
+            blocks.push(Block {
+                lead: std::mem::take(&mut lead),
+                header: header(text, section),
+                body,
+            });
+        }
+    }
+
+    if config.sort_sections {
+        blocks.sort_by_key(|block| {
+            let name = block.header.trim_start_matches('[').trim_end_matches(']');
+            section_rank(name)
+        });
+    }
+
+    let mut lines = collapse_blank_lines(entry_lines(text, preamble));
+    for block in blocks {
+        if !lines.is_empty() {
+            lines.push(String::new());
+        }
+        lines.extend(collapse_blank_lines(block.lead));
+        lines.push(block.header);
+        lines.extend(collapse_blank_lines(block.body));
+    }
+
+    if lines.is_empty() {
+        return String::new();
+    }
+    lines.join("\n") + "\n"
+}
+
+// A run of old lines replaced by new ones
+#[derive(Debug, PartialEq)]
+struct Hunk {
+    old_start: usize,
+    old_end: usize,
+    new_lines: Vec<String>,
+}
+
+// Most cells of the longest common subsequence table `diff` builds; past
+// that the changed lines are replaced as a whole
+const MAX_DIFF_CELLS: usize = 1 << 22;
+
+// Line diff from the longest common subsequence. Lines shared at both ends
+// are skipped first, so the quadratic table only covers the changed middle.
+fn diff(old: &[&str], new: &[&str]) -> Vec<Hunk> {
+    let prefix = old
+        .iter()
+        .zip(new)
+        .take_while(|(old, new)| old == new)
+        .count();
+    let suffix = old[prefix..]
+        .iter()
+        .rev()
+        .zip(new[prefix..].iter().rev())
+        .take_while(|(old, new)| old == new)
+        .count();
+    let old = &old[prefix..old.len() - suffix];
+    let new = &new[prefix..new.len() - suffix];
+    let (n, m) = (old.len(), new.len());
+
+    if n == 0 && m == 0 {
+        return Vec::new();
+    }
+    if (n + 1).saturating_mul(m + 1) > MAX_DIFF_CELLS {
+        return vec![Hunk {
+            old_start: prefix,
+            old_end: prefix + n,
+            new_lines: new.iter().map(|line| line.to_string()).collect(),
+        }];
+    }
+
+    let mut common = vec![vec![0u32; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut hunks = Vec::new();
+    let mut current: Option<Hunk> = None;
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && old[i] == new[j] {
+            hunks.extend(current.take());
+            i += 1;
+            j += 1;
+            continue;
+        }
+        let hunk = current.get_or_insert_with(|| Hunk {
+            old_start: prefix + i,
+            old_end: prefix + i,
+            new_lines: Vec::new(),
+        });
+        if j < m && (i == n || common[i][j + 1] >= common[i + 1][j]) {
+            hunk.new_lines.push(new[j].to_string());
+            j += 1;
+        } else {
+            i += 1;
+            hunk.old_end = prefix + i;
+        }
+    }
+    hunks.extend(current);
+    hunks
+}
+
+// Edits formatting a document. With a range, only changes touching the
+// lines it covers are kept; sections are never reordered then, as moving
+// a section takes edits far apart from each other.
+pub fn edits(
+    document: &Document,
+    config: &FormattingConfig,
+    range: Option<Range>,
+) -> Vec<TextEdit> {
+    let text = document.text();
+    let config = match range {
+        Some(_) => FormattingConfig {
+            sort_sections: false,
+        },
+        None => config.clone(),
+    };
+    let formatted = format(text, &config);
+
+    let old: Vec<&str> = text.lines().collect();
+    let new: Vec<&str> = formatted.lines().collect();
+    // Whether the old lines `start..end` meet the range. An insertion
+    // between two lines meets it if either of them is in it.
+    let touches = |start: usize, end: usize| {
+        range.is_none_or(|range| {
+            let (first, last) = (range.start.line as usize, range.end.line as usize);
+            if start == end {
+                start >= first && start <= last + 1
+            } else {
+                start <= last && end > first
+            }
+        })
+    };
+    let end_of_text = document.index.position(text.len());
+    let missing_newline = !text.is_empty() && !text.ends_with('\n');
+    // Keep the line ending the document uses
+    let newline = if text.contains("\r\n") { "\r\n" } else { "\n" };
+
+    let mut edits = Vec::new();
+    let mut fixed_last_line = false;
+    for hunk in diff(&old, &new) {
+        if !touches(hunk.old_start, hunk.old_end) {
+            continue;
+        }
+        let mut new_text: String = hunk
+            .new_lines
+            .iter()
+            .map(|line| format!("{}{}", line, newline))
+            .collect();
+
+        let start = if hunk.old_start == old.len() && missing_newline {
+            // Appending after a last line without a newline
+            new_text.insert_str(0, newline);
+            end_of_text
+        } else {
+            Position::new(hunk.old_start as u32, 0)
+        };
+        let end = if hunk.old_end < old.len() {
+            Position::new(hunk.old_end as u32, 0)
+        } else {
+            fixed_last_line = true;
+            end_of_text
+        };
+        edits.push(TextEdit::new(Range::new(start, end), new_text));
+    }
+
+    // Terminate the last line
+    if missing_newline && !fixed_last_line && touches(old.len() - 1, old.len()) {
+        edits.push(TextEdit::new(
+            Range::new(end_of_text, end_of_text),
+            newline.to_string(),
+        ));
+    }
+
+    edits
+}
@@ -0,0 +1,464 @@
+// Dependency graph of the units in the workspace index.
+//
+// Edges come from dependency directives, from [Install] settings read in
+// reverse (`WantedBy=b.target` in a.service makes b.target want a.service)
+// and from links in `.wants/`, `.requires/` and `.upholds/` directories.
+// Drop-ins add edges to the unit they extend.
+
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap, HashSet, VecDeque};
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+use tower_lsp::lsp_types::*;
+
+use crate::dropins;
+use crate::index::{self, IndexedFile, UnitIndex};
+use crate::schema::UnitType;
+use crate::units;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum EdgeKind {
+    Requires,
+    Requisite,
+    Wants,
+    BindsTo,
+    PartOf,
+    Upholds,
+    Conflicts,
+    Before,
+    After,
+}
+
+impl EdgeKind {
+    // The edge a directive creates, and whether it points from the listed
+    // units to the unit itself, as [Install] settings do
+    fn from_key(key: &str) -> Option<(Self, bool)> {
+        let edge = match key {
+            "Requires" => (Self::Requires, false),
+            "Requisite" => (Self::Requisite, false),
+            "Wants" => (Self::Wants, false),
+            "BindsTo" => (Self::BindsTo, false),
+            "PartOf" => (Self::PartOf, false),
+            "Upholds" => (Self::Upholds, false),
+            "Conflicts" => (Self::Conflicts, false),
+            "Before" => (Self::Before, false),
+            "After" => (Self::After, false),
+            "WantedBy" => (Self::Wants, true),
+            "RequiredBy" => (Self::Requires, true),
+            "UpheldBy" => (Self::Upholds, true),
+            _ => return None,
+        };
+        Some(edge)
+    }
+
+    // Name of the directive, such as `After`
+    pub fn key(&self) -> &'static str {
+        match self {
+            Self::Requires => "Requires",
+            Self::Requisite => "Requisite",
+            Self::Wants => "Wants",
+            Self::BindsTo => "BindsTo",
+            Self::PartOf => "PartOf",
+            Self::Upholds => "Upholds",
+            Self::Conflicts => "Conflicts",
+            Self::Before => "Before",
+            Self::After => "After",
+        }
+    }
+
+    // Edge colors of `systemd-analyze dot`
+    fn color(&self) -> &'static str {
+        match self {
+            Self::Requires | Self::BindsTo => "black",
+            Self::Requisite => "darkblue",
+            Self::Wants | Self::Upholds => "grey66",
+            Self::PartOf => "purple",
+            Self::Conflicts => "red",
+            Self::Before | Self::After => "green",
+        }
+    }
+
+    // For ordering edges, the unit started first and the one started after
+    // it
+    fn order<'a>(&self, edge: &'a Edge) -> Option<(&'a str, &'a str)> {
+        match self {
+            Self::Before => Some((&edge.from, &edge.to)),
+            Self::After => Some((&edge.to, &edge.from)),
+            _ => None,
+        }
+    }
+}
+
+// `from` has a dependency of type `kind` on `to`
+#[derive(Debug, Clone, PartialEq)]
+pub struct Edge {
+    pub from: String,
+    pub to: String,
+    pub kind: EdgeKind,
+    // Where the dependency is declared: the unit name in a directive, or
+    // the link in a `.wants/` directory
+    pub location: Location,
+    // The directive declaring it, which differs from `kind` for [Install]
+    // settings and links
+    pub key: String,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct DependencyGraph {
+    edges: Vec<Edge>,
+    // Units with a file of their own
+    defined: HashSet<String>,
+}
+
+impl DependencyGraph {
+    pub fn build(index: &UnitIndex) -> Self {
+        let mut graph = Self::default();
+        let mut uris = index.uris();
+        uris.sort();
+
+        for uri in uris {
+            let Some(file) = index.get(&uri) else {
+                continue;
+            };
+            let unit = match index::unit_name(&uri) {
+                Some(name) => name.to_string(),
+                None => match uri
+                    .to_file_path()
+                    .ok()
+                    .and_then(|path| dropins::extended_unit(&path))
+                {
+                    Some(unit) => unit,
+                    None => continue,
+                },
+            };
+            if index::unit_name(&uri).is_some() {
+                graph.defined.insert(unit.clone());
+            }
+
+            for reference in &file.references {
+                let Some((kind, reverse)) = EdgeKind::from_key(&reference.key) else {
+                    continue;
+                };
+                let (from, to) = if reverse {
+                    (reference.name.clone(), unit.clone())
+                } else {
+                    (unit.clone(), reference.name.clone())
+                };
+                graph.edges.push(Edge {
+                    from,
+                    to,
+                    kind,
+                    location: Location::new(uri.clone(), reference.range),
+                    key: reference.key.clone(),
+                });
+            }
+        }
+
+        for (uri, link) in index.links() {
+            let Some((kind, _)) = EdgeKind::from_key(&link.key) else {
+                continue;
+            };
+            graph.edges.push(Edge {
+                from: link.unit,
+                to: link.name,
+                kind,
+                location: Location::new(uri, Range::default()),
+                key: link.key,
+            });
+        }
+
+        graph
+    }
+
+    // Move the edges declared in `uri` to where the file now has them, after
+    // an edit that left its dependencies as they were
+    pub fn relocate(&mut self, uri: &Url, file: &IndexedFile) {
+        let ranges = file
+            .references
+            .iter()
+            .filter(|reference| EdgeKind::from_key(&reference.key).is_some())
+            .map(|reference| reference.range);
+        let edges = self
+            .edges
+            .iter_mut()
+            .filter(|edge| edge.location.uri == *uri);
+        for (edge, range) in edges.zip(ranges) {
+            edge.location.range = range;
+        }
+    }
+
+    pub fn edges(&self) -> &[Edge] {
+        &self.edges
+    }
+
+    // The node standing for a unit: instances without a file of their own
+    // get their dependencies from the template
+    fn node<'a>(&self, name: &'a str) -> Cow<'a, str> {
+        if !self.defined.contains(name)
+            && let Some(template) = units::template_of(name)
+            && self.defined.contains(&template)
+        {
+            return template.into();
+        }
+        name.into()
+    }
+
+    // Edges leaving a unit. An instance without a file of its own has the
+    // edges of its template.
+    pub fn edges_from<'a>(&'a self, unit: &'a str) -> impl Iterator<Item = &'a Edge> + 'a {
+        let node = self.node(unit).into_owned();
+        self.edges.iter().filter(move |edge| edge.from == node)
+    }
+
+    // A unit and everything it reaches through the exported edge kinds
+    pub fn export(&self, unit: &str) -> GraphExport {
+        let mut nodes = BTreeSet::from([unit.to_string()]);
+        let mut edges = BTreeSet::new();
+        let mut pending = VecDeque::from([unit.to_string()]);
+
+        while let Some(node) = pending.pop_front() {
+            for edge in self.edges_from(&node) {
+                if !EXPORTED_KINDS.contains(&edge.kind) {
+                    continue;
+                }
+                edges.insert(ExportedEdge {
+                    from: node.clone(),
+                    to: edge.to.clone(),
+                    kind: edge.kind,
+                });
+                if nodes.insert(edge.to.clone()) {
+                    pending.push_back(edge.to.clone());
+                }
+            }
+        }
+
+        GraphExport {
+            unit: unit.to_string(),
+            nodes: nodes.into_iter().collect(),
+            edges: edges.into_iter().collect(),
+        }
+    }
+
+    // The nodes an ordering edge orders. Ordering between a template and
+    // one of its instances, e.g. `After=worker@primary.service` in
+    // `worker@.service`, orders two different units and would only loop
+    // once the instance is folded into the template, so it is left out.
+    fn ordered_nodes(&self, edge: &Edge) -> Option<(String, String)> {
+        let (first, then) = edge.kind.order(edge)?;
+        let nodes = (self.node(first), self.node(then));
+        if nodes.0 == nodes.1 && first != then {
+            return None;
+        }
+        Some((nodes.0.into_owned(), nodes.1.into_owned()))
+    }
+
+    // Ordering edges by the unit started first
+    fn ordering(&self) -> HashMap<String, Vec<(String, &Edge)>> {
+        let mut ordering: HashMap<String, Vec<(String, &Edge)>> = HashMap::new();
+        for edge in &self.edges {
+            if let Some((first, then)) = self.ordered_nodes(edge) {
+                ordering.entry(first).or_default().push((then, edge));
+            }
+        }
+        ordering
+    }
+
+    // The shortest ordering cycle through an ordering edge, starting with
+    // it, if there is one
+    fn cycle_through<'a>(
+        &self,
+        ordering: &HashMap<String, Vec<(String, &'a Edge)>>,
+        edge: &'a Edge,
+    ) -> Option<Vec<&'a Edge>> {
+        let (first, then) = self.ordered_nodes(edge)?;
+
+        // Breadth-first search from `then` back to `first`
+        let mut previous: HashMap<&str, (&str, &Edge)> = HashMap::new();
+        let mut pending = VecDeque::from([then.as_str()]);
+        let mut seen = HashSet::from([then.as_str()]);
+        while let Some(node) = pending.pop_front() {
+            if node == first {
+                let mut path = Vec::new();
+                let mut node = node;
+                while node != then {
+                    let (before, edge) = previous[node];
+                    path.push(edge);
+                    node = before;
+                }
+                path.push(edge);
+                path.reverse();
+                return Some(path);
+            }
+            for (next, next_edge) in ordering.get(node).into_iter().flatten() {
+                if seen.insert(next) {
+                    previous.insert(next, (node, next_edge));
+                    pending.push_back(next);
+                }
+            }
+        }
+        None
+    }
+
+    // Files declaring an edge of some ordering cycle
+    pub fn cycle_uris(&self) -> BTreeSet<Url> {
+        let ordering = self.ordering();
+        self.edges
+            .iter()
+            .filter_map(|edge| self.cycle_through(&ordering, edge))
+            .flatten()
+            .map(|edge| edge.location.uri.clone())
+            .collect()
+    }
+
+    // Ordering cycles each ordering edge declared in `uri` is part of
+    pub fn ordering_cycles(&self, uri: &Url) -> Vec<(&Edge, Vec<&Edge>)> {
+        let ordering = self.ordering();
+        self.edges
+            .iter()
+            .filter(|edge| edge.location.uri == *uri)
+            .filter_map(|edge| Some((edge, self.cycle_through(&ordering, edge)?)))
+            .collect()
+    }
+}
+
+// Edge kinds followed when exporting the graph of a unit
+const EXPORTED_KINDS: &[EdgeKind] = &[
+    EdgeKind::Requires,
+    EdgeKind::Wants,
+    EdgeKind::PartOf,
+    EdgeKind::After,
+    EdgeKind::Before,
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct ExportedEdge {
+    pub from: String,
+    pub to: String,
+    pub kind: EdgeKind,
+}
+
+// A unit with its transitive dependencies, as the `systemd/dependencyGraph`
+// request and the `graph` command export it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphExport {
+    pub unit: String,
+    pub nodes: Vec<String>,
+    pub edges: Vec<ExportedEdge>,
+}
+
+fn dot_quote(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl GraphExport {
+    // Graphviz DOT, with edges labelled by type and colored like
+    // `systemd-analyze dot` does
+    pub fn to_dot(&self) -> String {
+        let mut dot = format!("digraph {} {{\n", dot_quote(&self.unit));
+        for node in &self.nodes {
+            dot.push_str(&format!("    {};\n", dot_quote(node)));
+        }
+        for edge in &self.edges {
+            dot.push_str(&format!(
+                "    {} -> {} [label=\"{}\", color=\"{}\"];\n",
+                dot_quote(&edge.from),
+                dot_quote(&edge.to),
+                edge.kind.key(),
+                edge.kind.color()
+            ));
+        }
+        dot.push_str("}\n");
+        dot
+    }
+
+    pub fn render(self, format: GraphFormat) -> GraphOutput {
+        match format {
+            GraphFormat::Dot => GraphOutput::Dot(self.to_dot()),
+            GraphFormat::Json => GraphOutput::Json(self),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GraphFormat {
+    #[default]
+    Json,
+    Dot,
+}
+
+impl FromStr for GraphFormat {
+    type Err = anyhow::Error;
+
+    fn from_str(text: &str) -> anyhow::Result<Self> {
+        match text {
+            "json" => Ok(Self::Json),
+            "dot" => Ok(Self::Dot),
+            _ => anyhow::bail!("unknown graph format '{}', expected 'dot' or 'json'", text),
+        }
+    }
+}
+
+// Check the unit a graph is asked for is a unit name
+pub fn check_unit(unit: &str) -> Result<(), String> {
+    match UnitType::from_name(unit) {
+        Some(_) => Ok(()),
+        None => Err(format!("'{}' is not a unit name", unit)),
+    }
+}
+
+// Parameters of the `systemd/dependencyGraph` request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DependencyGraphParams {
+    pub unit: String,
+    #[serde(default)]
+    pub format: GraphFormat,
+}
+
+// Result of the `systemd/dependencyGraph` request: the DOT text, or the
+// graph as a JSON object
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(untagged)]
+pub enum GraphOutput {
+    Dot(String),
+    Json(GraphExport),
+}
+
+// Report the ordering cycles the directives of a file take part in, on
+// every unit name creating an edge of the cycle
+pub fn cycle_diagnostics(graph: &DependencyGraph, uri: &Url) -> Vec<Diagnostic> {
+    graph
+        .ordering_cycles(uri)
+        .into_iter()
+        .map(|(edge, cycle)| {
+            let mut units: Vec<&str> = cycle
+                .iter()
+                .filter_map(|edge| edge.kind.order(edge).map(|(first, _)| first))
+                .collect();
+            units.push(units[0]);
+
+            let related = cycle
+                .iter()
+                .skip(1)
+                .map(|other| DiagnosticRelatedInformation {
+                    location: other.location.clone(),
+                    message: format!("{}={} in {}", other.key, other.to, other.from),
+                })
+                .collect();
+
+            Diagnostic {
+                range: edge.location.range,
+                severity: Some(DiagnosticSeverity::WARNING),
+                message: format!(
+                    "Ordering cycle, each unit starting before the next: {}. systemd breaks it at boot by dropping one of the jobs",
+                    units.join(" → ")
+                ),
+                source: Some("systemd-lsp".into()),
+                related_information: Some(related),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
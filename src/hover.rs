@@ -0,0 +1,101 @@
+// Markdown documentation shown on hover, with links to the upstream man
+// pages
+
+use crate::schema::{Directive, EnumValue, SectionSchema, ValueType};
+use crate::specifiers::Specifier;
+
+const MAN_PAGES: &str = "https://www.freedesktop.org/software/systemd/man/latest/";
+
+// Link to a man page such as `systemd.exec(5)`, at a directive's entry
+pub fn man_link(page: &str, key: Option<&str>) -> String {
+    let name = page.split_once('(').map_or(page, |(name, _)| name);
+    let anchor = key.map(|key| format!("#{}=", key)).unwrap_or_default();
+    format!("[{}]({}{}.html{})", page, MAN_PAGES, name, anchor)
+}
+
+fn since(version: u32) -> String {
+    if version == 0 {
+        String::new()
+    } else {
+        format!(" (since systemd {})", version)
+    }
+}
+
+pub fn section_markdown(section: &SectionSchema) -> String {
+    let mut text = format!("**[{}]**\n\n{}", section.name, section.doc);
+    if let Some(table) = section.tables.first() {
+        text.push_str(&format!("\n\nSee {}.", man_link(table.man, None)));
+    }
+    text
+}
+
+// Documentation of a directive: its man page, accepted values, default and
+// the release it appeared in
+pub fn directive_markdown(section: &SectionSchema, directive: &Directive) -> String {
+    let mut text = format!("**{}=**", directive.name);
+    if let Some(table) = section.table(directive.name) {
+        text.push_str(&format!(" · {}", man_link(table.man, Some(directive.name))));
+    }
+    text.push_str(&format!("\n\n{}\n\n", directive.doc));
+
+    text.push_str(&format!("**Accepts:** {}", directive.value.describe()));
+    if let ValueType::Enum(values) = directive.value {
+        text.push('\n');
+        for value in values {
+            text.push_str(&format!("\n- `{}`", value.name));
+            if !value.doc.is_empty() {
+                text.push_str(&format!(": {}", value.doc));
+            }
+            text.push_str(&since(value.since));
+        }
+    }
+    if directive.repeatable {
+        text.push_str("\n\nMay be given more than once; an empty assignment resets the list.");
+    }
+    if !directive.default.is_empty() {
+        text.push_str(&format!("\n\n**Default:** `{}`", directive.default));
+    }
+    if directive.since > 0 {
+        text.push_str(&format!("\n\n**Added in:** systemd {}", directive.since));
+    }
+    text
+}
+
+// Documentation of one value of an enum directive
+pub fn value_markdown(directive: &Directive, value: &EnumValue) -> String {
+    let mut text = format!(
+        "**{}={}**{}",
+        directive.name,
+        value.name,
+        since(value.since)
+    );
+    if !value.doc.is_empty() {
+        text.push_str(&format!("\n\n{}", value.doc));
+    }
+    if directive.default == value.name {
+        text.push_str("\n\nThis is the default.");
+    }
+    text
+}
+
+// Documentation of a `%` specifier, with what it expands to in `unit` if
+// that is known from the unit name and file alone
+pub fn specifier_markdown(specifier: &Specifier, expansion: Option<(&str, String)>) -> String {
+    let mut text = format!(
+        "**%{}** · [systemd.unit(5)]({}systemd.unit.html#Specifiers)\n\n{}",
+        specifier.letter, MAN_PAGES, specifier.doc
+    );
+    match expansion {
+        Some((unit, expansion)) if expansion.is_empty() => {
+            text.push_str(&format!("\n\nExpands to an empty string in `{}`.", unit));
+        }
+        Some((unit, expansion)) => {
+            text.push_str(&format!(
+                "\n\n**Expands to:** `{}` in `{}`",
+                expansion, unit
+            ));
+        }
+        None => text.push_str("\n\nResolved by systemd when the unit is loaded."),
+    }
+    text
+}
@@ -0,0 +1,319 @@
+// Index of the unit files in the workspace and the units they refer to.
+//
+// Files are indexed from disk when the workspace is opened; open documents
+// replace their on-disk entry on every change so results follow the buffer.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::RwLock;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use tower_lsp::lsp_types::{Location, Range, SymbolInformation, SymbolKind, Url};
+
+use crate::document::Document;
+use crate::units;
+use crate::workspace;
+
+// A unit name written in a dependency directive
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedReference {
+    pub name: String,
+    // Directive the reference appears in, such as `After`
+    pub key: String,
+    pub range: Range,
+}
+
+// An assignment in an indexed file, for workspace symbol search
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedDirective {
+    pub section: String,
+    pub key: String,
+    pub value: String,
+    pub range: Range,
+}
+
+// A link in a `.wants/`, `.requires/` or `.upholds/` directory
+#[derive(Debug, Clone, PartialEq)]
+pub struct IndexedLink {
+    // Unit owning the directory
+    pub unit: String,
+    // Directive the link acts like, such as `Wants`
+    pub key: String,
+    // Name of the link, the unit depended on
+    pub name: String,
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct IndexedFile {
+    pub references: Vec<IndexedReference>,
+    pub directives: Vec<IndexedDirective>,
+}
+
+impl IndexedFile {
+    pub fn new(document: &Document) -> Self {
+        let directives = document
+            .unit
+            .directives()
+            .map(|(section, directive)| IndexedDirective {
+                section: section.name.clone(),
+                key: directive.key.clone(),
+                value: directive.value.clone(),
+                range: document.index.range(directive.span),
+            })
+            .collect();
+        let references = document
+            .unit
+            .directives()
+            .flat_map(|(section, directive)| {
+                units::references(section, directive)
+                    .into_iter()
+                    .map(|reference| IndexedReference {
+                        name: reference.name.to_string(),
+                        key: directive.key.clone(),
+                        range: document.index.range(reference.span),
+                    })
+            })
+            .collect();
+        Self {
+            references,
+            directives,
+        }
+    }
+
+    // The directives and unit names of the references, without where they
+    // are, which edits elsewhere in the file move
+    fn dependencies(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.references
+            .iter()
+            .map(|reference| (reference.key.as_str(), reference.name.as_str()))
+    }
+
+    // Values assigned to a directive of a section
+    fn values<'a>(&'a self, section: &'a str, key: &'a str) -> impl Iterator<Item = &'a str> {
+        self.directives
+            .iter()
+            .filter(move |d| d.section == section && d.key == key)
+            .map(|d| d.value.as_str())
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct UnitIndex {
+    files: RwLock<HashMap<Url, IndexedFile>>,
+    links: RwLock<HashMap<Url, IndexedLink>>,
+    // Bumped whenever the units or the dependencies between them change,
+    // so the dependency graph is only rebuilt when it has to be
+    generation: AtomicU64,
+}
+
+// Unit name of a file, `None` for drop-ins
+pub fn unit_name(uri: &Url) -> Option<&str> {
+    let name = uri.path_segments()?.next_back()?;
+    (!name.ends_with(".conf")).then_some(name)
+}
+
+// Whether a reference written as `reference` points at the unit `name`.
+// Instances refer to their template as well.
+pub fn refers_to(reference: &str, name: &str) -> bool {
+    reference == name || units::template_of(reference).is_some_and(|template| template == name)
+}
+
+impl UnitIndex {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    // Index every unit file below a workspace folder. Files already indexed
+    // from an open buffer are left alone. Links in `.wants/` and similar
+    // directories are recorded as dependencies rather than unit files.
+    pub fn index_folder(&self, root: &Path) {
+        for path in workspace::unit_files(root) {
+            let Ok(uri) = Url::from_file_path(&path) else {
+                continue;
+            };
+            if let Some((unit, key)) = workspace::dependency_link(&path)
+                && let Some(name) = unit_name(&uri)
+            {
+                let link = IndexedLink {
+                    unit,
+                    key: key.to_string(),
+                    name: name.to_string(),
+                };
+                self.links.write().unwrap().insert(uri, link);
+                continue;
+            }
+            let Ok(text) = fs::read_to_string(&path) else {
+                continue;
+            };
+            let file = IndexedFile::new(&Document::new(&text, 0));
+            self.files.write().unwrap().entry(uri).or_insert(file);
+        }
+        self.generation.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn update(&self, uri: Url, document: &Document) {
+        let file = IndexedFile::new(document);
+        let previous = self.files.write().unwrap().insert(uri, file.clone());
+        if previous.is_none_or(|previous| !previous.dependencies().eq(file.dependencies())) {
+            self.generation.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    pub fn remove(&self, uri: &Url) {
+        if self.files.write().unwrap().remove(uri).is_some() {
+            self.generation.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    // Changes whenever a unit file is added or removed, or its references
+    // to other units change. Moving a reference doesn't change it.
+    pub fn generation(&self) -> u64 {
+        self.generation.load(Ordering::Relaxed)
+    }
+
+    pub fn get(&self, uri: &Url) -> Option<IndexedFile> {
+        self.files.read().unwrap().get(uri).cloned()
+    }
+
+    pub fn links(&self) -> Vec<(Url, IndexedLink)> {
+        let links = self.links.read().unwrap();
+        let mut links: Vec<(Url, IndexedLink)> = links
+            .iter()
+            .map(|(uri, link)| (uri.clone(), link.clone()))
+            .collect();
+        links.sort_by(|a, b| a.0.cmp(&b.0));
+        links
+    }
+
+    pub fn uris(&self) -> Vec<Url> {
+        self.files.read().unwrap().keys().cloned().collect()
+    }
+
+    // Names of every unit defined in the workspace, sorted
+    pub fn unit_names(&self) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .files
+            .read()
+            .unwrap()
+            .keys()
+            .filter_map(|uri| unit_name(uri).map(str::to_string))
+            .collect();
+        names.sort();
+        names.dedup();
+        names
+    }
+
+    // Files defining the unit `name`
+    pub fn definitions(&self, name: &str) -> Vec<Url> {
+        let mut uris: Vec<Url> = self
+            .files
+            .read()
+            .unwrap()
+            .keys()
+            .filter(|uri| unit_name(uri) == Some(name))
+            .cloned()
+            .collect();
+        uris.sort();
+        uris
+    }
+
+    // Instances of the template `name` referenced anywhere in the
+    // workspace, sorted
+    pub fn instances(&self, name: &str) -> Vec<String> {
+        let mut instances: Vec<String> = self
+            .files
+            .read()
+            .unwrap()
+            .values()
+            .flat_map(|file| &file.references)
+            .filter(|reference| reference.name != name && refers_to(&reference.name, name))
+            .map(|reference| reference.name.clone())
+            .collect();
+        instances.sort();
+        instances.dedup();
+        instances
+    }
+
+    // Every place in the workspace referring to the unit `name`, sorted by
+    // file and position
+    pub fn references(&self, name: &str) -> Vec<Location> {
+        let files = self.files.read().unwrap();
+        let mut locations: Vec<Location> = files
+            .iter()
+            .flat_map(|(uri, file)| {
+                file.references
+                    .iter()
+                    .filter(|reference| refers_to(&reference.name, name))
+                    .map(|reference| Location::new(uri.clone(), reference.range))
+            })
+            .collect();
+        locations.sort_by(|a, b| {
+            (a.uri.as_str(), a.range.start.line, a.range.start.character).cmp(&(
+                b.uri.as_str(),
+                b.range.start.line,
+                b.range.start.character,
+            ))
+        });
+        locations
+    }
+
+    // Workspace symbols matching a query, case-insensitively: units whose
+    // name, Description= or Alias= matches, then directives whose key does.
+    // An empty query lists every unit.
+    pub fn symbols(&self, query: &str) -> Vec<SymbolInformation> {
+        let query = query.to_lowercase();
+        let matches = |text: &str| text.to_lowercase().contains(&query);
+
+        let files = self.files.read().unwrap();
+        let mut uris: Vec<&Url> = files.keys().collect();
+        uris.sort();
+
+        let mut units = Vec::new();
+        let mut directives = Vec::new();
+        for uri in uris {
+            let file = &files[uri];
+            let name = uri.path_segments().and_then(|mut s| s.next_back());
+
+            if let Some(unit) = unit_name(uri) {
+                let description = file.values("Unit", "Description").last();
+                let is_match = matches(unit)
+                    || description.is_some_and(matches)
+                    || file
+                        .values("Install", "Alias")
+                        .flat_map(str::split_whitespace)
+                        .any(matches);
+                if is_match {
+                    // The description is shown next to the unit name
+                    #[allow(deprecated)]
+                    units.push(SymbolInformation {
+                        name: unit.to_string(),
+                        kind: SymbolKind::FILE,
+                        tags: None,
+                        deprecated: None,
+                        location: Location::new(uri.clone(), Range::default()),
+                        container_name: description.map(str::to_string),
+                    });
+                }
+            }
+
+            if query.is_empty() {
+                continue;
+            }
+            for directive in file.directives.iter().filter(|d| matches(&d.key)) {
+                #[allow(deprecated)]
+                directives.push(SymbolInformation {
+                    name: format!("{}={}", directive.key, directive.value),
+                    kind: SymbolKind::PROPERTY,
+                    tags: None,
+                    deprecated: None,
+                    location: Location::new(uri.clone(), directive.range),
+                    container_name: name.map(str::to_string),
+                });
+            }
+        }
+
+        units.extend(directives);
+        units
+    }
+}
@@ -1,136 +1,260 @@
-use ini::configparser::ini::Ini;
-use log::info;
-use std::collections::HashMap;
-use tower_lsp::jsonrpc::Result;
+use chrono::{DateTime, FixedOffset, Utc};
+use config::Config;
+use document::{Document, DocumentStore};
+use dropins::{EffectiveUnit, EffectiveUnitParams};
+use graph::{DependencyGraphParams, GraphOutput};
+use index::UnitIndex;
+use line_index::LineIndex;
+use log::{error, info};
+use parser::UnitFile;
+use schema::{UnitType, ValueType};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tower_lsp::jsonrpc::{Error, Result};
 use tower_lsp::lsp_types::*;
 use tower_lsp::{Client, LanguageServer};
 
+pub mod calendar;
+pub mod code_actions;
+pub mod config;
+pub mod diagnostics;
+pub mod document;
+pub mod dropins;
+pub mod exec;
+pub mod formatting;
+pub mod graph;
+pub mod hover;
+pub mod index;
+pub mod line_index;
+pub mod parser;
+pub mod rename;
+pub mod schema;
+pub mod semantic_tokens;
+pub mod specifiers;
+pub mod suggest;
+pub mod symbols;
+pub mod units;
+pub mod validators;
+pub mod workspace;
+
 pub struct Backend {
     client: Client,
     // Store opened file contents
-    documents: HashMap<Url, String>,
+    documents: DocumentStore,
+    // Settings from the workspace's .systemd-ls.json
+    config: RwLock<Config>,
+    // Root directories of the workspace folders the client opened
+    workspace_folders: RwLock<Vec<PathBuf>>,
+    // Unit files in the workspace folders and the units they reference
+    index: Arc<UnitIndex>,
+    // Dependency graph of the index, with the index generation it was
+    // built from
+    graph: RwLock<(u64, Arc<graph::DependencyGraph>)>,
 }
 
 impl Backend {
     pub fn new(client: Client) -> Self {
         Self {
             client,
-            documents: HashMap::new(),
+            documents: DocumentStore::new(),
+            config: RwLock::new(Config::default()),
+            workspace_folders: RwLock::new(Vec::new()),
+            index: Arc::new(UnitIndex::new()),
+            graph: RwLock::new((0, Arc::default())),
         }
     }
 
-    // Parse systemd unit file
-    fn parse_unit_file(&self, content: &str) -> anyhow::Result<Ini> {
-        let mut ini = Ini::new();
-        if let Err(e) = ini.read(content.to_string()) {
-            return Err(anyhow::anyhow!(e));
-        }
-        Ok(ini)
+    fn config(&self) -> Config {
+        self.config.read().unwrap().clone()
     }
 
-    // Generate diagnostics
-    fn generate_diagnostics(&self, content: &str) -> Vec<Diagnostic> {
-        let mut diagnostics = Vec::new();
+    // Diagnostics for a document, checking drop-ins against their unit
+    fn diagnose(&self, uri: &Url, document: &Document) -> Vec<Diagnostic> {
+        let config = self.config();
+        let mut diagnostics =
+            diagnostics::generate_diagnostics(document, UnitType::from_uri(uri), &config);
+
+        if config.diagnostics.enabled
+            && let Ok(path) = uri.to_file_path()
+            && let Some(unit_name) = dropins::extended_unit(&path)
+        {
+            let base = self
+                .find_unit_file(&unit_name, &path)
+                .and_then(|path| self.load_document(&path));
+            diagnostics.extend(diagnostics::dropin_diagnostics(
+                document,
+                &unit_name,
+                base.as_ref().map(|(_, base)| base.as_ref()),
+            ));
+        }
+        if config.diagnostics.enabled
+            && let Some(unit_name) = unit_name_for(uri)
+        {
+            diagnostics.extend(diagnostics::template_diagnostics(document, &unit_name));
+        }
+        if config.diagnostics.enabled {
+            diagnostics.extend(graph::cycle_diagnostics(&self.graph(), uri));
+        }
+
+        diagnostics
+    }
 
-        match self.parse_unit_file(content) {
-            Ok(_) => {
-                // File format is correct, no diagnostics needed
+    // The dependency graph of the index, rebuilt only when the index has
+    // changed since the last build
+    fn graph(&self) -> Arc<graph::DependencyGraph> {
+        let generation = self.index.generation();
+        {
+            let cached = self.graph.read().unwrap();
+            if cached.0 == generation {
+                return Arc::clone(&cached.1);
             }
-            Err(e) => {
-                // Add syntax error diagnostic
-                let diagnostic = Diagnostic {
-                    range: Range {
-                        start: Position::new(0, 0),
-                        end: Position::new(0, 1),
-                    },
-                    severity: Some(DiagnosticSeverity::ERROR),
-                    code: None,
-                    code_description: None,
-                    source: Some("systemd-lsp".into()),
-                    message: format!("Systemd unit file syntax error: {}", e),
-                    related_information: None,
-                    tags: None,
-                    data: None,
-                };
-                diagnostics.push(diagnostic);
+        }
+        let graph = Arc::new(graph::DependencyGraph::build(&self.index));
+        *self.graph.write().unwrap() = (generation, Arc::clone(&graph));
+        graph
+    }
+
+    // Index a file, or drop it from the index without a document. Returns
+    // the dependency graph from before the change if the dependencies
+    // between units changed; otherwise the cached graph is kept, with the
+    // edges of the file moved to where they are now.
+    fn update_index(
+        &self,
+        uri: &Url,
+        document: Option<&Document>,
+    ) -> Option<Arc<graph::DependencyGraph>> {
+        let before = self.graph();
+        let generation = self.index.generation();
+        match document {
+            Some(document) => self.index.update(uri.clone(), document),
+            None => self.index.remove(uri),
+        }
+        if self.index.generation() != generation {
+            return Some(before);
+        }
+
+        if let Some(file) = self.index.get(uri) {
+            let mut cached = self.graph.write().unwrap();
+            if cached.0 == generation {
+                Arc::make_mut(&mut cached.1).relocate(uri, &file);
             }
         }
+        None
+    }
 
-        // Check for common systemd configuration errors
-        self.check_common_errors(content, &mut diagnostics);
+    // A change to `uri` can create or break ordering cycles through other
+    // files, so diagnose the open documents taking part in a cycle before
+    // or after the change again
+    async fn republish_cycles(
+        &self,
+        uri: &Url,
+        before: &Arc<graph::DependencyGraph>,
+        after: &Arc<graph::DependencyGraph>,
+    ) {
+        let mut uris = before.cycle_uris();
+        uris.extend(after.cycle_uris());
+        uris.remove(uri);
 
-        diagnostics
+        for uri in uris {
+            let Some(document) = self.documents.get(&uri) else {
+                continue;
+            };
+            let diagnostics = self.diagnose(&uri, &document);
+            self.client
+                .publish_diagnostics(uri, diagnostics, Some(document.version))
+                .await;
+        }
     }
 
-    // Check for common systemd configuration errors
-    fn check_common_errors(&self, content: &str, diagnostics: &mut Vec<Diagnostic>) {
-        let lines: Vec<&str> = content.lines().collect();
-
-        for (i, line) in lines.iter().enumerate() {
-            let line_num = i as u32;
-
-            // Check line format
-            if line.contains('=') && !line.trim().starts_with('#') && !line.trim().starts_with('[')
-            {
-                let parts: Vec<&str> = line.splitn(2, '=').collect();
-                if parts.len() == 2 {
-                    let key = parts[0].trim();
-                    let value = parts[1].trim();
-
-                    // Check for empty values
-                    if value.is_empty() {
-                        diagnostics.push(Diagnostic {
-                            range: Range {
-                                start: Position::new(line_num, 0),
-                                end: Position::new(line_num, line.len() as u32),
-                            },
-                            severity: Some(DiagnosticSeverity::WARNING),
-                            message: format!("Key '{}' has an empty value", key),
-                            source: Some("systemd-lsp".into()),
-                            ..Default::default()
-                        });
-                    }
+    // An open document, or the file on disk if it isn't open
+    fn load_document(&self, path: &Path) -> Option<(Url, Arc<Document>)> {
+        let uri = Url::from_file_path(path).ok()?;
+        if let Some(document) = self.documents.get(&uri) {
+            return Some((uri, document));
+        }
+        let text = std::fs::read_to_string(path).ok()?;
+        Some((uri, Arc::new(Document::new(&text, 0))))
+    }
 
-                    // Check for common configuration errors
-                    match key {
-                        "ExecStart" => {
-                            if !value.starts_with('/') && !value.starts_with('-') {
-                                diagnostics.push(Diagnostic {
-                                    range: Range {
-                                        start: Position::new(line_num, 0),
-                                        end: Position::new(line_num, line.len() as u32),
-                                    },
-                                    severity: Some(DiagnosticSeverity::WARNING),
-                                    message: "ExecStart should use absolute paths".to_string(),
-                                    source: Some("systemd-lsp".into()),
-                                    ..Default::default()
-                                });
-                            }
-                        }
-                        "Type" => {
-                            let valid_types =
-                                ["simple", "forking", "oneshot", "dbus", "notify", "idle"];
-                            if !valid_types.contains(&value) {
-                                diagnostics.push(Diagnostic {
-                                    range: Range {
-                                        start: Position::new(line_num, 0),
-                                        end: Position::new(line_num, line.len() as u32),
-                                    },
-                                    severity: Some(DiagnosticSeverity::ERROR),
-                                    message: format!(
-                                        "Invalid service type: '{}'. Valid types: {:?}",
-                                        value, valid_types
-                                    ),
-                                    source: Some("systemd-lsp".into()),
-                                    ..Default::default()
-                                });
-                            }
-                        }
-                        _ => {}
-                    }
-                }
+    // The file defining a unit, looked up next to `near` first
+    fn find_unit_file(&self, name: &str, near: &Path) -> Option<PathBuf> {
+        let sibling = near
+            .ancestors()
+            .skip(1)
+            .take(2)
+            .map(|dir| dir.join(name))
+            .find(|path| path.is_file());
+        if sibling.is_some() {
+            return sibling;
+        }
+
+        workspace::find_unit(name, &self.index, &self.config().unit_search_paths)
+    }
+
+    // The unit file of a document and its drop-ins, in applying order
+    fn get_effective_unit(&self, document_uri: &Url) -> Option<EffectiveUnit> {
+        let path = document_uri.to_file_path().ok()?;
+        let (unit, base) = match dropins::extended_unit(&path) {
+            Some(unit) => {
+                let base = self.find_unit_file(&unit, &path);
+                (unit, base)
+            }
+            None => {
+                let unit = path.file_name()?.to_str()?.to_string();
+                UnitType::from_name(&unit)?;
+                (unit, Some(path.clone()))
+            }
+        };
+
+        // Drop-ins next to the unit file take precedence over those elsewhere
+        // in the workspace, which take precedence over the search paths
+        let mut dirs: Vec<PathBuf> = base
+            .iter()
+            .filter_map(|base| base.parent().map(Path::to_path_buf))
+            .collect();
+        let workspace_dirs = dropins::workspace_dirs(&self.index, &unit);
+        for dir in workspace_dirs
+            .into_iter()
+            .chain(self.config().unit_search_paths)
+        {
+            if !dirs.contains(&dir) {
+                dirs.push(dir);
             }
         }
+
+        let files: Vec<(Url, Arc<Document>)> = base
+            .into_iter()
+            .chain(dropins::find_dropins(&unit, &dirs))
+            .filter_map(|path| self.load_document(&path))
+            .collect();
+        let files: Vec<(Url, &Document)> = files
+            .iter()
+            .map(|(uri, document)| (uri.clone(), document.as_ref()))
+            .collect();
+
+        Some(EffectiveUnit {
+            unit,
+            files: files.iter().map(|(uri, _)| uri.clone()).collect(),
+            text: dropins::cat(&files),
+            sections: dropins::merge(&files),
+        })
+    }
+
+    // Handle the `systemd/effectiveUnit` request: the unit merged with its
+    // drop-ins, as `systemctl cat` shows it
+    pub async fn effective_unit(
+        &self,
+        params: EffectiveUnitParams,
+    ) -> Result<Option<EffectiveUnit>> {
+        Ok(self.get_effective_unit(&params.text_document.uri))
+    }
+
+    // Handle the `systemd/dependencyGraph` request: a unit and its
+    // transitive dependencies in the workspace, as DOT or JSON
+    pub async fn dependency_graph(&self, params: DependencyGraphParams) -> Result<GraphOutput> {
+        graph::check_unit(&params.unit).map_err(Error::invalid_params)?;
+        Ok(self.graph().export(&params.unit).render(params.format))
     }
 
     // Get completion items
@@ -138,8 +262,8 @@ impl Backend {
         let mut items = Vec::new();
 
         // Get current document content
-        if let Some(content) = self.documents.get(document_uri) {
-            let lines: Vec<&str> = content.lines().collect();
+        if let Some(document) = self.documents.get(document_uri) {
+            let lines: Vec<&str> = document.text().lines().collect();
 
             // Get current line
             if let Some(line) = lines.get(position.line as usize) {
@@ -148,169 +272,59 @@ impl Backend {
                 // Check if currently in a section name
                 if line.trim().starts_with('[') && !line.contains(']') {
                     // Provide section name completions
-                    items.extend(vec![
-                        CompletionItem::new_simple(
-                            "Unit]".into(),
-                            "Unit configuration section".into(),
-                        ),
-                        CompletionItem::new_simple(
-                            "Service]".into(),
-                            "Service configuration section".into(),
-                        ),
-                        CompletionItem::new_simple(
-                            "Install]".into(),
-                            "Install configuration section".into(),
-                        ),
-                        CompletionItem::new_simple(
-                            "Socket]".into(),
-                            "Socket configuration section".into(),
-                        ),
-                        CompletionItem::new_simple(
-                            "Mount]".into(),
-                            "Mount configuration section".into(),
-                        ),
+                    items.extend(sections_for(document_uri).into_iter().map(|section| {
                         CompletionItem::new_simple(
-                            "Timer]".into(),
-                            "Timer configuration section".into(),
-                        ),
-                    ]);
+                            format!("{}]", section.name),
+                            section.doc.to_string(),
+                        )
+                    }));
+                } else if let Some((key, value)) = directive_prefix(&document, position)
+                    && ends_with_specifier_start(value)
+                    && self
+                        .get_current_section(&document, position)
+                        .as_deref()
+                        .and_then(|section| schema::lookup(section, key))
+                        .is_some_and(|directive| directive.value.expands_specifiers())
+                {
+                    // Provide specifiers after a `%` in values resolving them
+                    items.extend(specifier_completions(document_uri, position));
+                } else if let Some((key, value)) = directive_prefix(&document, position) {
+                    // Provide value completions for the directive on this line
+                    let current_section = self.get_current_section(&document, position);
+                    if let Some(directive) = current_section
+                        .as_deref()
+                        .and_then(|section| schema::lookup(section, key))
+                    {
+                        items.extend(self.get_value_completions(directive, value, position));
+                    }
                 } else {
                     // Provide key completions based on current section
-                    let current_section = self.get_current_section(lines, position.line as usize);
+                    let current_section = self.get_current_section(&document, position);
 
-                    match current_section.as_deref() {
-                        Some("Unit") => {
-                            items.extend(vec![
-                                CompletionItem::new_simple(
-                                    "Description=".into(),
-                                    "Unit description".into(),
-                                ),
-                                CompletionItem::new_simple(
-                                    "Documentation=".into(),
-                                    "Documentation URL".into(),
-                                ),
-                                CompletionItem::new_simple(
-                                    "Requires=".into(),
-                                    "Strong dependencies".into(),
-                                ),
-                                CompletionItem::new_simple(
-                                    "Wants=".into(),
-                                    "Weak dependencies".into(),
-                                ),
-                                CompletionItem::new_simple(
-                                    "After=".into(),
-                                    "Start order dependency".into(),
-                                ),
-                                CompletionItem::new_simple(
-                                    "Before=".into(),
-                                    "Start order dependency".into(),
-                                ),
-                                CompletionItem::new_simple(
-                                    "Conflicts=".into(),
-                                    "Conflicting units".into(),
-                                ),
-                            ]);
-                        }
-                        Some("Service") => {
-                            items.extend(vec![
-                                CompletionItem::new_simple("Type=".into(), "Service type".into()),
-                                CompletionItem::new_simple(
-                                    "ExecStart=".into(),
-                                    "Start command".into(),
-                                ),
-                                CompletionItem::new_simple(
-                                    "ExecStop=".into(),
-                                    "Stop command".into(),
-                                ),
-                                CompletionItem::new_simple(
-                                    "Restart=".into(),
-                                    "Restart policy".into(),
-                                ),
-                                CompletionItem::new_simple(
-                                    "RestartSec=".into(),
-                                    "Restart interval".into(),
-                                ),
-                                CompletionItem::new_simple("User=".into(), "Run as user".into()),
-                                CompletionItem::new_simple("Group=".into(), "Run as group".into()),
-                                CompletionItem::new_simple(
-                                    "WorkingDirectory=".into(),
-                                    "Working directory".into(),
-                                ),
-                            ]);
-                        }
-                        Some("Install") => {
-                            items.extend(vec![
-                                CompletionItem::new_simple(
-                                    "WantedBy=".into(),
-                                    "Wanted by targets".into(),
-                                ),
-                                CompletionItem::new_simple(
-                                    "RequiredBy=".into(),
-                                    "Required by targets".into(),
-                                ),
-                                CompletionItem::new_simple("Alias=".into(), "Unit alias".into()),
-                            ]);
-                        }
-                        Some("Socket") => {
-                            items.extend(vec![
-                                CompletionItem::new_simple(
-                                    "ListenStream=".into(),
-                                    "Listen on TCP port".into(),
-                                ),
-                                CompletionItem::new_simple(
-                                    "ListenDatagram=".into(),
-                                    "Listen on UDP port".into(),
-                                ),
-                                CompletionItem::new_simple(
-                                    "Accept=".into(),
-                                    "Accept connections".into(),
-                                ),
-                            ]);
+                    match current_section.as_deref().and_then(schema::section) {
+                        Some(section) => {
+                            // Hide directives the target systemd release does not know
+                            let config = self.config();
+                            let directives = section
+                                .directives()
+                                .filter(|directive| config.supports(directive.since));
+                            items.extend(directives.map(|directive| CompletionItem {
+                                label: format!("{}=", directive.name),
+                                kind: Some(CompletionItemKind::PROPERTY),
+                                documentation: Some(Documentation::String(
+                                    directive.doc.to_string(),
+                                )),
+                                ..Default::default()
+                            }));
                         }
-                        Some("Timer") => {
-                            items.extend(vec![
+                        None => {
+                            // Default to providing the section names
+                            items.extend(sections_for(document_uri).into_iter().map(|section| {
                                 CompletionItem::new_simple(
-                                    "OnBootSec=".into(),
-                                    "Delay after boot".into(),
-                                ),
-                                CompletionItem::new_simple(
-                                    "OnUnitActiveSec=".into(),
-                                    "Delay after unit activation".into(),
-                                ),
-                                CompletionItem::new_simple(
-                                    "OnCalendar=".into(),
-                                    "Calendar-based trigger".into(),
-                                ),
-                            ]);
-                        }
-                        _ => {
-                            // Default to providing all section names
-                            items.extend(vec![
-                                CompletionItem::new_simple(
-                                    "[Unit]".into(),
-                                    "Unit configuration section".into(),
-                                ),
-                                CompletionItem::new_simple(
-                                    "[Service]".into(),
-                                    "Service configuration section".into(),
-                                ),
-                                CompletionItem::new_simple(
-                                    "[Install]".into(),
-                                    "Install configuration section".into(),
-                                ),
-                                CompletionItem::new_simple(
-                                    "[Socket]".into(),
-                                    "Socket configuration section".into(),
-                                ),
-                                CompletionItem::new_simple(
-                                    "[Mount]".into(),
-                                    "Mount configuration section".into(),
-                                ),
-                                CompletionItem::new_simple(
-                                    "[Timer]".into(),
-                                    "Timer configuration section".into(),
-                                ),
-                            ]);
+                                    format!("[{}]", section.name),
+                                    section.doc.to_string(),
+                                )
+                            }));
                         }
                     }
                 }
@@ -320,139 +334,498 @@ impl Backend {
         items
     }
 
-    // Get current section
-    fn get_current_section(&self, lines: Vec<&str>, current_line: usize) -> Option<String> {
-        let mut current_section = None;
+    // Completions for the value of a directive. `value` is the text typed
+    // before the cursor.
+    fn get_value_completions(
+        &self,
+        directive: &schema::Directive,
+        value: &str,
+        position: &Position,
+    ) -> Vec<CompletionItem> {
+        // Unit lists complete the name under the cursor, other values the
+        // whole value
+        let word = match directive.value {
+            ValueType::Units => value.rsplit(char::is_whitespace).next().unwrap_or_default(),
+            _ => value.trim_start(),
+        };
+        let start = position.character - word.encode_utf16().count() as u32;
+        let range = Range::new(Position::new(position.line, start), *position);
+        let item = |label: &str, kind, documentation: &str| CompletionItem {
+            label: label.to_string(),
+            kind: Some(kind),
+            documentation: (!documentation.is_empty())
+                .then(|| Documentation::String(documentation.to_string())),
+            text_edit: Some(CompletionTextEdit::Edit(TextEdit::new(
+                range,
+                label.to_string(),
+            ))),
+            ..Default::default()
+        };
 
-        for (i, line) in lines.iter().enumerate() {
-            if i > current_line {
-                break;
+        match directive.value {
+            ValueType::Enum(values) => {
+                let config = self.config();
+                values
+                    .iter()
+                    .filter(|value| config.supports(value.since))
+                    .map(|value| item(value.name, CompletionItemKind::ENUM_MEMBER, value.doc))
+                    .collect()
             }
+            ValueType::Boolean => ["yes", "no"]
+                .into_iter()
+                .map(|name| item(name, CompletionItemKind::KEYWORD, ""))
+                .collect(),
+            ValueType::Units => {
+                // Special units, then the workspace, then the search paths
+                let listed: Vec<&str> = value.split_whitespace().collect();
+                let mut seen = HashSet::new();
+                let special = units::SPECIAL_UNITS
+                    .iter()
+                    .map(|&(name, doc)| (name.to_string(), doc));
+                let workspace = self.index.unit_names().into_iter().map(|name| (name, ""));
+                let search_paths = workspace::search_path_units(&self.config().unit_search_paths)
+                    .into_iter()
+                    .map(|name| (name, ""));
 
-            let line = line.trim();
-            if line.starts_with('[') && line.ends_with(']') {
-                let section = line[1..line.len() - 1].to_string();
-                current_section = Some(section);
+                special
+                    .chain(workspace)
+                    .chain(search_paths)
+                    .filter(|(name, _)| !listed.contains(&name.as_str()))
+                    .filter(|(name, _)| seen.insert(name.clone()))
+                    .map(|(name, doc)| item(&name, CompletionItemKind::MODULE, doc))
+                    .collect()
             }
+            _ => Vec::new(),
         }
+    }
 
-        current_section
+    // Get current section
+    fn get_current_section(&self, document: &Document, position: &Position) -> Option<String> {
+        let offset = document.index.offset(*position);
+
+        document
+            .unit
+            .section_at(offset)
+            .map(|section| section.name.clone())
     }
 
     // Get hover information
     fn get_hover_info(&self, position: &Position, document_uri: &Url) -> Option<Hover> {
-        if let Some(content) = self.documents.get(document_uri) {
-            let lines: Vec<&str> = content.lines().collect();
+        let document = self.documents.get(document_uri)?;
+        let unit = &document.unit;
+        let index = &document.index;
+        let offset = index.offset(*position);
 
-            if let Some(line) = lines.get(position.line as usize) {
-                let line = *line;
+        // Check if hovering over a section name
+        if let Some(section) = unit
+            .sections
+            .iter()
+            .find(|section| section.header_span.contains(offset))
+        {
+            let hover_text = hover::section_markdown(schema::section(&section.name)?);
 
-                // Check if hovering over a section name
-                if line.trim().starts_with('[') && line.trim().ends_with(']') {
-                    let section = line.trim()[1..line.trim().len() - 1].to_string();
+            return Some(Hover {
+                contents: HoverContents::Markup(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: hover_text,
+                }),
+                range: Some(index.range(section.header_span)),
+            });
+        }
 
-                    let hover_text = match section.as_str() {
-                        "Unit" => {
-                            "The Unit section contains basic information about the unit, such as description and dependencies."
-                        }
-                        "Service" => {
-                            "The Service section contains service configuration, such as start commands and restart policies."
-                        }
-                        "Install" => {
-                            "The Install section contains installation information, such as which targets want this unit."
-                        }
-                        "Socket" => {
-                            "The Socket section contains socket configuration, such as listening addresses and ports."
-                        }
-                        "Mount" => "The Mount section contains mount point configuration.",
-                        "Timer" => {
-                            "The Timer section contains timer configuration, used for scheduled service activation."
-                        }
-                        _ => return None,
-                    };
-
-                    return Some(Hover {
-                        contents: HoverContents::Markup(MarkupContent {
-                            kind: MarkupKind::Markdown,
-                            value: hover_text.to_string(),
-                        }),
-                        range: Some(Range {
-                            start: Position::new(position.line, 0),
-                            end: Position::new(position.line, line.len() as u32),
-                        }),
-                    });
-                }
+        // Check if hovering over a key-value pair
+        let (section, directive) = unit.directive_at(offset)?;
 
-                // Check if hovering over a key-value pair
-                if line.contains('=') && !line.trim().starts_with('#') {
-                    let parts: Vec<&str> = line.splitn(2, '=').collect();
-                    if parts.len() == 2 {
-                        let key = parts[0].trim();
-
-                        // Provide hover information based on key
-                        let hover_text = match key {
-                            "Description" => "Describes the unit's function and purpose.",
-                            "After" => {
-                                "Defines start order, this unit will start after the specified units."
-                            }
-                            "Before" => {
-                                "Defines start order, this unit will start before the specified units."
-                            }
-                            "Requires" => {
-                                "Strong dependency relationship, if the dependency fails, this unit will also fail."
-                            }
-                            "Wants" => {
-                                "Weak dependency relationship, dependency failure won't affect this unit."
-                            }
-                            "ExecStart" => {
-                                "Defines the command to execute when the service starts. Should use absolute paths."
-                            }
-                            "ExecStop" => "Defines the command to execute when the service stops.",
-                            "Type" => {
-                                "Defines the service type, can be simple, forking, oneshot, dbus, notify, or idle."
-                            }
-                            "Restart" => "Defines the restart policy when the service exits.",
-                            "WantedBy" => {
-                                "Specifies which targets want this unit, used for enabling the unit."
-                            }
-                            _ => return None,
-                        };
-
-                        return Some(Hover {
-                            contents: HoverContents::Markup(MarkupContent {
-                                kind: MarkupKind::Markdown,
-                                value: hover_text.to_string(),
-                            }),
-                            range: Some(Range {
-                                start: Position::new(position.line, 0),
-                                end: Position::new(position.line, key.len() as u32),
-                            }),
-                        });
-                    }
-                }
+        // Show what the specifier under the cursor expands to
+        if let Some(found) = specifiers::scan(&directive.value)
+            .into_iter()
+            .find(|found| {
+                directive
+                    .source_span(found.span.start, found.span.end)
+                    .contains(offset)
+            })
+            && let Some(specifier) = found.letter.and_then(specifiers::find)
+        {
+            let unit = unit_name_for(document_uri);
+            let path = document_uri.to_file_path().ok();
+            let expansion = unit.as_deref().and_then(|unit| {
+                Some((
+                    unit,
+                    specifiers::expand(specifier.letter, unit, path.as_deref())?,
+                ))
+            });
+            return Some(Hover {
+                contents: HoverContents::Markup(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: hover::specifier_markdown(specifier, expansion),
+                }),
+                range: Some(index.range(directive.source_span(found.span.start, found.span.end))),
+            });
+        }
+
+        // Provide hover information based on the schema
+        let section_schema = schema::section(&section.name)?;
+        let schema = section_schema.directive(&directive.key)?;
+
+        // Preview calendar events when hovering their value
+        if schema.value == ValueType::Calendar
+            && directive.value_span.contains(offset)
+            && let Ok(spec) = calendar::parse(&directive.value)
+        {
+            return Some(Hover {
+                contents: HoverContents::Markup(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: calendar_preview(&spec),
+                }),
+                range: Some(index.range(directive.value_span)),
+            });
+        }
+
+        // Explain the enum value under the cursor
+        if directive.value_span.contains(offset)
+            && let Some(value) = schema.find_enum_value(&directive.value)
+        {
+            return Some(Hover {
+                contents: HoverContents::Markup(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: hover::value_markdown(schema, value),
+                }),
+                range: Some(index.range(directive.value_span)),
+            });
+        }
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value: hover::directive_markdown(section_schema, schema),
+            }),
+            range: Some(index.range(directive.key_span)),
+        })
+    }
+
+    // Find the unit file a unit name under the cursor refers to
+    fn get_definition(&self, position: &Position, document_uri: &Url) -> Option<Location> {
+        let document = self.documents.get(document_uri)?;
+        let offset = document.index.offset(*position);
+        let reference = units::reference_at(&document.unit, offset)?;
+
+        let path = workspace::find_unit(
+            reference.name,
+            &self.index,
+            &self.config().unit_search_paths,
+        )?;
+
+        Some(Location::new(
+            Url::from_file_path(path).ok()?,
+            Range::default(),
+        ))
+    }
+
+    // The unit name under the cursor, or the unit the document defines
+    fn unit_name_at(
+        &self,
+        document: &Document,
+        offset: usize,
+        document_uri: &Url,
+    ) -> Option<String> {
+        match units::reference_at(&document.unit, offset) {
+            Some(reference) => Some(reference.name.to_string()),
+            None => index::unit_name(document_uri).map(str::to_string),
+        }
+    }
+
+    // Find every reference to the unit under the cursor, or to the unit the
+    // document defines when the cursor is not on a unit name
+    fn get_references(
+        &self,
+        position: &Position,
+        document_uri: &Url,
+        include_declaration: bool,
+    ) -> Option<Vec<Location>> {
+        let document = self.documents.get(document_uri)?;
+        let offset = document.index.offset(*position);
+
+        let name = self.unit_name_at(&document, offset, document_uri)?;
+
+        let mut locations = Vec::new();
+        if include_declaration {
+            locations.extend(
+                self.index
+                    .definitions(&name)
+                    .into_iter()
+                    .map(|uri| Location::new(uri, Range::default())),
+            );
+        }
+        locations.extend(self.index.references(&name));
+        Some(locations)
+    }
+
+    // Range of the unit name under the cursor. Elsewhere the unit the
+    // document defines is renamed, which has no range of its own, so the
+    // cursor position is given with the name as placeholder.
+    fn get_prepare_rename(
+        &self,
+        position: &Position,
+        document_uri: &Url,
+    ) -> Option<PrepareRenameResponse> {
+        let document = self.documents.get(document_uri)?;
+        let offset = document.index.offset(*position);
+        if let Some(reference) = units::reference_at(&document.unit, offset) {
+            return Some(PrepareRenameResponse::Range(
+                document.index.range(reference.span),
+            ));
+        }
+
+        let name = self.unit_name_at(&document, offset, document_uri)?;
+        Some(PrepareRenameResponse::RangeWithPlaceholder {
+            range: Range::new(*position, *position),
+            placeholder: name,
+        })
+    }
+
+    // Rename the unit under the cursor, or the unit the document defines
+    fn get_rename(
+        &self,
+        position: &Position,
+        document_uri: &Url,
+        new_name: &str,
+    ) -> Result<Option<WorkspaceEdit>> {
+        let Some(document) = self.documents.get(document_uri) else {
+            return Ok(None);
+        };
+        let offset = document.index.offset(*position);
+        let Some(name) = self.unit_name_at(&document, offset, document_uri) else {
+            return Ok(None);
+        };
+        rename::check_new_name(&name, new_name).map_err(Error::invalid_params)?;
+
+        let edit = rename::rename_unit(&self.index, &name, new_name, |uri| {
+            self.documents.get(uri).map(|document| document.version)
+        });
+        Ok(Some(edit))
+    }
+
+    // A code lens on a template unit listing the instances the workspace
+    // refers to
+    fn get_code_lenses(&self, document_uri: &Url) -> Vec<CodeLens> {
+        let Some(name) = index::unit_name(document_uri).filter(|name| units::is_template(name))
+        else {
+            return Vec::new();
+        };
+        let Some(document) = self.documents.get(document_uri) else {
+            return Vec::new();
+        };
+
+        let instances = self.index.instances(name);
+        let title = match instances.len() {
+            0 => "No instances referenced in the workspace".to_string(),
+            1 => format!("1 instance: {}", instances[0]),
+            count => format!("{} instances: {}", count, instances.join(", ")),
+        };
+        let range = document
+            .unit
+            .sections
+            .first()
+            .map(|section| document.index.range(section.header_span))
+            .unwrap_or_default();
+
+        vec![CodeLens {
+            range,
+            command: Some(Command::new(title, String::new(), None)),
+            data: None,
+        }]
+    }
+
+    // Get inlay hints showing the normalized form of calendar events
+    fn get_inlay_hints(&self, document_uri: &Url, range: Range) -> Vec<InlayHint> {
+        let Some(document) = self.documents.get(document_uri) else {
+            return Vec::new();
+        };
+        let index = &document.index;
+        let range = index.span(range);
+        let now = Utc::now();
+
+        let mut hints = Vec::new();
+        for (section, directive) in document.unit.directives() {
+            if directive.span.end < range.start || directive.span.start > range.end {
+                continue;
             }
+            let is_calendar = schema::lookup(&section.name, &directive.key)
+                .is_some_and(|schema| schema.value == ValueType::Calendar);
+            let Some(spec) = is_calendar
+                .then(|| calendar::parse(&directive.value).ok())
+                .flatten()
+            else {
+                continue;
+            };
+
+            let next = match spec.next_elapses(now, 1).first() {
+                Some(time) => format_elapse(time),
+                None => "never".to_string(),
+            };
+            hints.push(InlayHint {
+                position: index.position(directive.value_span.end),
+                label: InlayHintLabel::String(format!("{} (next: {})", spec, next)),
+                kind: None,
+                text_edits: None,
+                tooltip: Some(InlayHintTooltip::MarkupContent(MarkupContent {
+                    kind: MarkupKind::Markdown,
+                    value: calendar_preview(&spec),
+                })),
+                padding_left: Some(true),
+                padding_right: None,
+                data: None,
+            });
         }
 
-        None
+        hints
+    }
+}
+
+// Sections allowed in a file, from its unit type. Files of unknown type
+// may contain any section.
+fn sections_for(uri: &Url) -> Vec<&'static schema::SectionSchema> {
+    match UnitType::from_uri(uri) {
+        Some(unit_type) => unit_type.sections().collect(),
+        None => schema::SECTIONS.iter().collect(),
     }
 }
 
+// Name of the unit a file defines, or extends for drop-ins
+fn unit_name_for(uri: &Url) -> Option<String> {
+    match index::unit_name(uri) {
+        Some(name) => UnitType::from_name(name).map(|_| name.to_string()),
+        None => dropins::extended_unit(&uri.to_file_path().ok()?),
+    }
+}
+
+// Whether a value typed so far ends with a `%` starting a specifier, as
+// opposed to the second `%` of `%%`
+fn ends_with_specifier_start(value: &str) -> bool {
+    let percents = value.len() - value.trim_end_matches('%').len();
+    percents % 2 == 1
+}
+
+// Every specifier, replacing the `%` before the cursor, with its expansion
+// for this file where known
+fn specifier_completions(uri: &Url, position: &Position) -> Vec<CompletionItem> {
+    let unit = unit_name_for(uri);
+    let path = uri.to_file_path().ok();
+    let range = Range::new(
+        Position::new(position.line, position.character.saturating_sub(1)),
+        *position,
+    );
+
+    specifiers::SPECIFIERS
+        .iter()
+        .map(|specifier| {
+            let label = format!("%{}", specifier.letter);
+            let detail = unit
+                .as_deref()
+                .and_then(|unit| specifiers::expand(specifier.letter, unit, path.as_deref()))
+                .map(|expansion| format!("→ {}", expansion));
+            CompletionItem {
+                label: label.clone(),
+                kind: Some(CompletionItemKind::CONSTANT),
+                detail,
+                documentation: Some(Documentation::String(specifier.doc.to_string())),
+                text_edit: Some(CompletionTextEdit::Edit(TextEdit::new(range, label))),
+                ..Default::default()
+            }
+        })
+        .collect()
+}
+
+// The key and the value typed so far when the cursor is after the `=` of
+// an assignment
+fn directive_prefix<'a>(document: &'a Document, position: &Position) -> Option<(&'a str, &'a str)> {
+    let index = &document.index;
+    let line_start = index.offset(Position::new(position.line, 0));
+    let prefix = &document.text()[line_start..index.offset(*position)];
+    if prefix.trim_start().starts_with(['#', ';', '[']) {
+        return None;
+    }
+    let (key, value) = prefix.split_once('=')?;
+    Some((key.trim(), value))
+}
+
+// Number of upcoming elapses shown for calendar events
+const CALENDAR_PREVIEW_ELAPSES: usize = 5;
+
+fn format_elapse(time: &DateTime<FixedOffset>) -> String {
+    time.format("%a %Y-%m-%d %H:%M:%S %:z").to_string()
+}
+
+// Markdown preview of a calendar event, like `systemd-analyze calendar`
+fn calendar_preview(spec: &calendar::CalendarSpec) -> String {
+    let mut text = format!("**Normalized form:** `{}`\n\n**Next elapses:**\n", spec);
+    let elapses = spec.next_elapses(Utc::now(), CALENDAR_PREVIEW_ELAPSES);
+    if elapses.is_empty() {
+        text.push_str("\n_never_\n");
+    }
+    for time in elapses {
+        text.push_str(&format!("- {}\n", format_elapse(&time)));
+    }
+    text
+}
+
 #[tower_lsp::async_trait]
 impl LanguageServer for Backend {
-    async fn initialize(&self, _: InitializeParams) -> Result<InitializeResult> {
+    async fn initialize(&self, params: InitializeParams) -> Result<InitializeResult> {
         info!("Systemd Language Server initialized");
 
+        // Load .systemd-ls.json from the workspace root
+        let folders = workspace_folders(&params);
+        if let Some(root) = folders.first() {
+            *self.config.write().unwrap() = Config::load(root);
+        }
+        *self.workspace_folders.write().unwrap() = folders;
+
         Ok(InitializeResult {
             capabilities: ServerCapabilities {
                 text_document_sync: Some(TextDocumentSyncCapability::Kind(
-                    TextDocumentSyncKind::FULL,
+                    TextDocumentSyncKind::INCREMENTAL,
                 )),
                 completion_provider: Some(CompletionOptions {
                     resolve_provider: Some(false),
-                    trigger_characters: Some(vec!["[".to_string(), "=".to_string()]),
+                    trigger_characters: Some(vec![
+                        "[".to_string(),
+                        "=".to_string(),
+                        "%".to_string(),
+                    ]),
                     ..Default::default()
                 }),
                 hover_provider: Some(HoverProviderCapability::Simple(true)),
+                definition_provider: Some(OneOf::Left(true)),
+                document_symbol_provider: Some(OneOf::Left(true)),
+                document_formatting_provider: Some(OneOf::Left(true)),
+                document_range_formatting_provider: Some(OneOf::Left(true)),
+                workspace_symbol_provider: Some(OneOf::Left(true)),
+                semantic_tokens_provider: Some(
+                    SemanticTokensServerCapabilities::SemanticTokensOptions(
+                        SemanticTokensOptions {
+                            legend: semantic_tokens::legend(),
+                            range: Some(true),
+                            full: Some(SemanticTokensFullOptions::Bool(true)),
+                            ..Default::default()
+                        },
+                    ),
+                ),
+                references_provider: Some(OneOf::Left(true)),
+                rename_provider: Some(OneOf::Right(RenameOptions {
+                    prepare_provider: Some(true),
+                    work_done_progress_options: Default::default(),
+                })),
+                inlay_hint_provider: Some(OneOf::Left(true)),
+                code_lens_provider: Some(CodeLensOptions {
+                    resolve_provider: Some(false),
+                }),
+                code_action_provider: Some(CodeActionProviderCapability::Options(
+                    CodeActionOptions {
+                        code_action_kinds: Some(vec![CodeActionKind::QUICKFIX]),
+                        ..Default::default()
+                    },
+                )),
                 ..Default::default()
             },
             server_info: Some(ServerInfo {
@@ -465,6 +838,19 @@ impl LanguageServer for Backend {
     async fn initialized(&self, _: InitializedParams) {
         info!("Systemd Language Server is ready");
 
+        // Index the unit files of every workspace folder off the async
+        // runtime, so requests keep being answered meanwhile
+        let folders = self.workspace_folders.read().unwrap().clone();
+        let index = Arc::clone(&self.index);
+        let indexing = tokio::task::spawn_blocking(move || {
+            for folder in &folders {
+                index.index_folder(folder);
+            }
+        });
+        if let Err(err) = indexing.await {
+            error!("Failed to index the workspace: {}", err);
+        }
+
         self.client
             .log_message(MessageType::INFO, "Systemd Language Server has started")
             .await;
@@ -479,36 +865,51 @@ impl LanguageServer for Backend {
         info!("File opened: {:?}", params.text_document.uri);
 
         // Store document content
-        let mut documents = self.documents.clone();
-        documents.insert(
+        let document = self.documents.insert(
             params.text_document.uri.clone(),
-            params.text_document.text.clone(),
+            &params.text_document.text,
+            params.text_document.version,
         );
 
+        // Index the buffer in place of the file on disk
+        let uri = params.text_document.uri;
+        let before = self.update_index(&uri, Some(&document));
+
         // Generate diagnostics
-        let diagnostics = self.generate_diagnostics(&params.text_document.text);
+        let diagnostics = self.diagnose(&uri, &document);
 
         // Publish diagnostics
         self.client
-            .publish_diagnostics(params.text_document.uri, diagnostics, None)
+            .publish_diagnostics(uri.clone(), diagnostics, Some(document.version))
             .await;
+        if let Some(before) = before {
+            self.republish_cycles(&uri, &before, &self.graph()).await;
+        }
     }
 
     async fn did_change(&self, params: DidChangeTextDocumentParams) {
         info!("File changed: {:?}", params.text_document.uri);
 
-        if let Some(change) = params.content_changes.first() {
-            // Update document content
-            let mut documents = self.documents.clone();
-            documents.insert(params.text_document.uri.clone(), change.text.clone());
+        // Apply every change in order on top of the stored document
+        let uri = params.text_document.uri;
+        let version = params.text_document.version;
+        let Some(document) = self
+            .documents
+            .update(&uri, &params.content_changes, version)
+        else {
+            return;
+        };
+        let before = self.update_index(&uri, Some(&document));
 
-            // Generate diagnostics
-            let diagnostics = self.generate_diagnostics(&change.text);
+        // Generate diagnostics
+        let diagnostics = self.diagnose(&uri, &document);
 
-            // Publish diagnostics
-            self.client
-                .publish_diagnostics(params.text_document.uri.clone(), diagnostics, None)
-                .await;
+        // Publish diagnostics for this version so clients can drop stale results
+        self.client
+            .publish_diagnostics(uri.clone(), diagnostics, Some(version))
+            .await;
+        if let Some(before) = before {
+            self.republish_cycles(&uri, &before, &self.graph()).await;
         }
     }
 
@@ -516,13 +917,24 @@ impl LanguageServer for Backend {
         info!("File closed: {:?}", params.text_document.uri);
 
         // Remove document content
-        let mut documents = self.documents.clone();
-        documents.remove(&params.text_document.uri);
+        let uri = &params.text_document.uri;
+        self.documents.remove(uri);
+
+        // Unsaved changes are discarded, so index the file on disk again
+        let text = uri
+            .to_file_path()
+            .ok()
+            .and_then(|path| std::fs::read_to_string(path).ok());
+        let document = text.map(|text| Document::new(&text, 0));
+        let before = self.update_index(uri, document.as_ref());
 
         // Clear diagnostics
         self.client
-            .publish_diagnostics(params.text_document.uri, vec![], None)
+            .publish_diagnostics(uri.clone(), vec![], None)
             .await;
+        if let Some(before) = before {
+            self.republish_cycles(uri, &before, &self.graph()).await;
+        }
     }
 
     async fn completion(&self, params: CompletionParams) -> Result<Option<CompletionResponse>> {
@@ -540,13 +952,158 @@ impl LanguageServer for Backend {
 
         Ok(self.get_hover_info(&position, &document_uri))
     }
+
+    async fn goto_definition(
+        &self,
+        params: GotoDefinitionParams,
+    ) -> Result<Option<GotoDefinitionResponse>> {
+        let position = params.text_document_position_params.position;
+        let document_uri = params.text_document_position_params.text_document.uri;
+
+        Ok(self
+            .get_definition(&position, &document_uri)
+            .map(GotoDefinitionResponse::Scalar))
+    }
+
+    async fn document_symbol(
+        &self,
+        params: DocumentSymbolParams,
+    ) -> Result<Option<DocumentSymbolResponse>> {
+        let Some(document) = self.documents.get(&params.text_document.uri) else {
+            return Ok(None);
+        };
+
+        Ok(Some(DocumentSymbolResponse::Nested(
+            symbols::document_symbols(&document),
+        )))
+    }
+
+    async fn formatting(&self, params: DocumentFormattingParams) -> Result<Option<Vec<TextEdit>>> {
+        let Some(document) = self.documents.get(&params.text_document.uri) else {
+            return Ok(None);
+        };
+
+        Ok(Some(formatting::edits(
+            &document,
+            &self.config().formatting,
+            None,
+        )))
+    }
+
+    async fn range_formatting(
+        &self,
+        params: DocumentRangeFormattingParams,
+    ) -> Result<Option<Vec<TextEdit>>> {
+        let Some(document) = self.documents.get(&params.text_document.uri) else {
+            return Ok(None);
+        };
+
+        Ok(Some(formatting::edits(
+            &document,
+            &self.config().formatting,
+            Some(params.range),
+        )))
+    }
+
+    async fn semantic_tokens_full(
+        &self,
+        params: SemanticTokensParams,
+    ) -> Result<Option<SemanticTokensResult>> {
+        let Some(document) = self.documents.get(&params.text_document.uri) else {
+            return Ok(None);
+        };
+
+        Ok(Some(SemanticTokensResult::Tokens(SemanticTokens {
+            result_id: None,
+            data: semantic_tokens::semantic_tokens(&document, None),
+        })))
+    }
+
+    async fn semantic_tokens_range(
+        &self,
+        params: SemanticTokensRangeParams,
+    ) -> Result<Option<SemanticTokensRangeResult>> {
+        let Some(document) = self.documents.get(&params.text_document.uri) else {
+            return Ok(None);
+        };
+        let range = document.index.span(params.range);
+
+        Ok(Some(SemanticTokensRangeResult::Tokens(SemanticTokens {
+            result_id: None,
+            data: semantic_tokens::semantic_tokens(&document, Some(range)),
+        })))
+    }
+
+    async fn symbol(
+        &self,
+        params: WorkspaceSymbolParams,
+    ) -> Result<Option<Vec<SymbolInformation>>> {
+        Ok(Some(self.index.symbols(&params.query)))
+    }
+
+    async fn references(&self, params: ReferenceParams) -> Result<Option<Vec<Location>>> {
+        let position = params.text_document_position.position;
+        let document_uri = params.text_document_position.text_document.uri;
+
+        Ok(self.get_references(&position, &document_uri, params.context.include_declaration))
+    }
+
+    async fn prepare_rename(
+        &self,
+        params: TextDocumentPositionParams,
+    ) -> Result<Option<PrepareRenameResponse>> {
+        Ok(self.get_prepare_rename(&params.position, &params.text_document.uri))
+    }
+
+    async fn rename(&self, params: RenameParams) -> Result<Option<WorkspaceEdit>> {
+        let position = params.text_document_position.position;
+        let document_uri = params.text_document_position.text_document.uri;
+
+        self.get_rename(&position, &document_uri, &params.new_name)
+    }
+
+    async fn inlay_hint(&self, params: InlayHintParams) -> Result<Option<Vec<InlayHint>>> {
+        Ok(Some(
+            self.get_inlay_hints(&params.text_document.uri, params.range),
+        ))
+    }
+
+    async fn code_lens(&self, params: CodeLensParams) -> Result<Option<Vec<CodeLens>>> {
+        Ok(Some(self.get_code_lenses(&params.text_document.uri)))
+    }
+
+    async fn code_action(&self, params: CodeActionParams) -> Result<Option<CodeActionResponse>> {
+        let actions =
+            code_actions::quick_fixes(&params.text_document.uri, &params.context.diagnostics);
+
+        Ok(Some(actions))
+    }
+}
+
+// Root directories of the workspace the client opened, falling back to
+// the deprecated root URI for clients without workspace folders
+fn workspace_folders(params: &InitializeParams) -> Vec<PathBuf> {
+    #[allow(deprecated)]
+    let uris: Vec<&Url> = match &params.workspace_folders {
+        Some(folders) if !folders.is_empty() => folders.iter().map(|f| &f.uri).collect(),
+        _ => params.root_uri.iter().collect(),
+    };
+    uris.into_iter()
+        .filter_map(|uri| uri.to_file_path().ok())
+        .collect()
 }
 
 // Export public function for testing
-pub fn parse_unit_file(content: &str) -> anyhow::Result<Ini> {
-    let mut ini = Ini::new();
-    if let Err(e) = ini.read(content.to_string()) {
-        return Err(anyhow::anyhow!(e));
+pub fn parse_unit_file(content: &str) -> anyhow::Result<UnitFile> {
+    let unit = parser::parse(content);
+    if let Some(error) = unit.errors.first() {
+        let position = LineIndex::new(content).position(error.span.start);
+        return Err(anyhow::anyhow!(
+            "line {}, column {}: {}",
+            position.line + 1,
+            position.character + 1,
+            error.message
+        ));
     }
-    Ok(ini)
+    Ok(unit)
 }
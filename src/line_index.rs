@@ -0,0 +1,94 @@
+use tower_lsp::lsp_types::{Position, Range};
+
+use crate::parser::Span;
+
+// Maps byte offsets in a document to LSP positions and back.
+//
+// LSP positions count characters in UTF-16 code units, while the parser
+// works with byte offsets into the UTF-8 source, so every conversion goes
+// through the line table built here.
+#[derive(Debug, Clone)]
+pub struct LineIndex {
+    text: String,
+    line_starts: Vec<usize>,
+}
+
+impl LineIndex {
+    pub fn new(text: &str) -> Self {
+        let mut line_starts = vec![0];
+        for (i, b) in text.bytes().enumerate() {
+            if b == b'\n' {
+                line_starts.push(i + 1);
+            }
+        }
+        Self {
+            text: text.to_string(),
+            line_starts,
+        }
+    }
+
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    pub fn line_count(&self) -> usize {
+        self.line_starts.len()
+    }
+
+    // Byte range of a line, excluding its line terminator
+    pub fn line_span(&self, line: usize) -> Option<Span> {
+        let start = *self.line_starts.get(line)?;
+        let mut end = self
+            .line_starts
+            .get(line + 1)
+            .map(|next| next - 1)
+            .unwrap_or(self.text.len());
+        if end > start && self.text.as_bytes()[end - 1] == b'\r' {
+            end -= 1;
+        }
+        Some(Span::new(start, end))
+    }
+
+    // Convert a byte offset into an LSP position
+    pub fn position(&self, offset: usize) -> Position {
+        let offset = self.clamp(offset);
+        let line = match self.line_starts.binary_search(&offset) {
+            Ok(line) => line,
+            Err(next) => next - 1,
+        };
+        let start = self.line_starts[line];
+        let character = self.text[start..offset].encode_utf16().count();
+        Position::new(line as u32, character as u32)
+    }
+
+    // Convert an LSP position into a byte offset, clamping out-of-range values
+    pub fn offset(&self, position: Position) -> usize {
+        let Some(span) = self.line_span(position.line as usize) else {
+            return self.text.len();
+        };
+        let mut units = 0;
+        for (i, c) in self.text[span.start..span.end].char_indices() {
+            if units >= position.character as usize {
+                return span.start + i;
+            }
+            units += c.len_utf16();
+        }
+        span.end
+    }
+
+    pub fn range(&self, span: Span) -> Range {
+        Range::new(self.position(span.start), self.position(span.end))
+    }
+
+    pub fn span(&self, range: Range) -> Span {
+        Span::new(self.offset(range.start), self.offset(range.end))
+    }
+
+    fn clamp(&self, offset: usize) -> usize {
+        let mut offset = offset.min(self.text.len());
+        while !self.text.is_char_boundary(offset) {
+            offset -= 1;
+        }
+        offset
+    }
+}
@@ -0,0 +1,394 @@
+// Lossless parser for systemd unit files.
+//
+// Unlike a generic INI parser this keeps every section, directive, comment
+// and blank line together with its byte range in the source, preserves the
+// case and order of repeated keys, and follows the rules of systemd's own
+// config parser: `#` and `;` start comment lines, there are no inline
+// comments, and a trailing backslash continues a value on the next line.
+
+// Half-open byte range into the parsed source
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Hash)]
+pub struct Span {
+    pub start: usize,
+    pub end: usize,
+}
+
+impl Span {
+    pub fn new(start: usize, end: usize) -> Self {
+        Self { start, end }
+    }
+
+    pub fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    pub fn contains(&self, offset: usize) -> bool {
+        self.start <= offset && offset <= self.end
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitFile {
+    // Comments and blank lines before the first section header
+    pub preamble: Vec<Entry>,
+    pub sections: Vec<Section>,
+    pub errors: Vec<SyntaxError>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Section {
+    pub name: String,
+    pub name_span: Span,
+    pub header_span: Span,
+    // From the header to the end of the last entry
+    pub span: Span,
+    pub entries: Vec<Entry>,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Entry {
+    Directive(Directive),
+    Comment(Comment),
+    Blank(Span),
+    // A line systemd would reject; the reason is recorded in `UnitFile::errors`
+    Invalid(Span),
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Directive {
+    pub key: String,
+    pub key_span: Span,
+    // Logical value, with continuation lines joined by a single space
+    pub value: String,
+    // Source range of the value, across all continuation lines
+    pub value_span: Span,
+    // Whole directive, including continuation lines
+    pub span: Span,
+    pieces: Vec<ValuePiece>,
+}
+
+// Where a run of the logical value came from in the source
+#[derive(Debug, Clone, Copy, PartialEq)]
+struct ValuePiece {
+    logical_start: usize,
+    span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Comment {
+    pub text: String,
+    pub span: Span,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct SyntaxError {
+    pub span: Span,
+    pub message: String,
+}
+
+impl Entry {
+    pub fn span(&self) -> Span {
+        match self {
+            Entry::Directive(directive) => directive.span,
+            Entry::Comment(comment) => comment.span,
+            Entry::Blank(span) | Entry::Invalid(span) => *span,
+        }
+    }
+}
+
+impl Directive {
+    // Map a byte range of the logical value back to the source
+    pub fn source_span(&self, start: usize, end: usize) -> Span {
+        Span::new(self.source_offset(start), self.source_offset(end))
+    }
+
+    pub fn source_offset(&self, logical: usize) -> usize {
+        let piece = self
+            .pieces
+            .iter()
+            .rev()
+            .find(|piece| piece.logical_start <= logical)
+            .copied()
+            .unwrap_or(ValuePiece {
+                logical_start: 0,
+                span: self.value_span,
+            });
+        (piece.span.start + logical - piece.logical_start).min(piece.span.end)
+    }
+
+    // Whether the value spans more than one line
+    pub fn is_continued(&self) -> bool {
+        self.pieces.len() > 1
+    }
+}
+
+impl Section {
+    pub fn directives(&self) -> impl Iterator<Item = &Directive> {
+        self.entries.iter().filter_map(|entry| match entry {
+            Entry::Directive(directive) => Some(directive),
+            _ => None,
+        })
+    }
+}
+
+impl UnitFile {
+    // Last value assigned to `key` in `section`, like systemd's own lookup
+    pub fn get(&self, section: &str, key: &str) -> Option<&str> {
+        self.get_all(section, key).last().copied()
+    }
+
+    // Every value assigned to `key` in `section`, in file order
+    pub fn get_all(&self, section: &str, key: &str) -> Vec<&str> {
+        self.sections
+            .iter()
+            .filter(|s| s.name == section)
+            .flat_map(|s| s.directives())
+            .filter(|d| d.key == key)
+            .map(|d| d.value.as_str())
+            .collect()
+    }
+
+    pub fn section(&self, name: &str) -> Option<&Section> {
+        self.sections.iter().find(|s| s.name == name)
+    }
+
+    // Every directive together with the section it belongs to
+    pub fn directives(&self) -> impl Iterator<Item = (&Section, &Directive)> {
+        self.sections
+            .iter()
+            .flat_map(|section| section.directives().map(move |d| (section, d)))
+    }
+
+    // Section whose header or body contains the byte offset
+    pub fn section_at(&self, offset: usize) -> Option<&Section> {
+        self.sections
+            .iter()
+            .rev()
+            .find(|section| section.header_span.start <= offset)
+    }
+
+    pub fn directive_at(&self, offset: usize) -> Option<(&Section, &Directive)> {
+        self.directives().find(|(_, d)| d.span.contains(offset))
+    }
+}
+
+// Parse a unit file. Parsing never fails: problems are collected in
+// `UnitFile::errors` and the offending lines kept as `Entry::Invalid`.
+pub fn parse(text: &str) -> UnitFile {
+    let mut unit = UnitFile {
+        preamble: Vec::new(),
+        sections: Vec::new(),
+        errors: Vec::new(),
+    };
+    let lines = split_lines(text);
+    let mut i = 0;
+
+    while i < lines.len() {
+        let line = lines[i];
+        let raw = &text[line.start..line.end];
+        let trimmed = raw.trim();
+        let indent = raw.len() - raw.trim_start().len();
+        let content = Span::new(line.start + indent, line.start + indent + trimmed.len());
+
+        if trimmed.is_empty() {
+            push_entry(&mut unit, Entry::Blank(line));
+            i += 1;
+        } else if trimmed.starts_with('#') || trimmed.starts_with(';') {
+            push_entry(
+                &mut unit,
+                Entry::Comment(Comment {
+                    text: trimmed.to_string(),
+                    span: line,
+                }),
+            );
+            i += 1;
+        } else if trimmed.starts_with('[') {
+            let closed = trimmed.len() > 1 && trimmed.ends_with(']');
+            let inner_end = if closed { content.end - 1 } else { content.end };
+            let inner = &text[content.start + 1..inner_end];
+            let name = inner.trim();
+            let name_start = content.start + 1 + (inner.len() - inner.trim_start().len());
+            let name_span = Span::new(name_start, name_start + name.len());
+
+            if !closed {
+                unit.errors.push(SyntaxError {
+                    span: content,
+                    message: format!("Invalid section header '{}': missing ']'", trimmed),
+                });
+            } else if name.is_empty() {
+                unit.errors.push(SyntaxError {
+                    span: content,
+                    message: "Empty section name".to_string(),
+                });
+            } else if name.contains(['[', ']']) {
+                unit.errors.push(SyntaxError {
+                    span: content,
+                    message: format!("Invalid section header '{}'", trimmed),
+                });
+            }
+
+            unit.sections.push(Section {
+                name: name.to_string(),
+                name_span,
+                header_span: line,
+                span: line,
+                entries: Vec::new(),
+            });
+            i += 1;
+        } else {
+            let consumed = parse_directive(text, &lines[i..], &mut unit);
+            i += consumed;
+        }
+    }
+
+    unit
+}
+
+// Parse an assignment starting at `lines[0]`, following continuation lines.
+// Returns the number of lines consumed.
+fn parse_directive(text: &str, lines: &[Span], unit: &mut UnitFile) -> usize {
+    let first = lines[0];
+    let raw = &text[first.start..first.end];
+    let indent = raw.len() - raw.trim_start().len();
+    let line_start = first.start + indent;
+
+    let Some(eq) = raw.find('=') else {
+        unit.errors.push(SyntaxError {
+            span: Span::new(line_start, first.start + raw.trim_end().len()),
+            message: format!("Missing '=' in line '{}'", raw.trim()),
+        });
+        push_entry(unit, Entry::Invalid(first));
+        return 1;
+    };
+
+    let key_raw = &raw[..eq];
+    let key = key_raw.trim();
+    let key_span = Span::new(line_start, line_start + key.len());
+
+    // Collect the value, joining backslash-continued lines with a space.
+    // Comment lines in the middle of a continuation are skipped, as systemd does.
+    let mut value = String::new();
+    let mut pieces = Vec::new();
+    let mut consumed = 0;
+    let mut rest_start = first.start + eq + 1;
+    let mut end;
+
+    loop {
+        let line = lines[consumed];
+        let segment = &text[rest_start..line.end];
+        let trimmed_end = segment.trim_end();
+        let continued = trimmed_end.ends_with('\\') && consumed + 1 < lines.len();
+        let body = if continued {
+            &trimmed_end[..trimmed_end.len() - 1]
+        } else {
+            trimmed_end
+        };
+        let body_trimmed = body.trim();
+        let start = rest_start + (body.len() - body.trim_start().len());
+
+        if !pieces.is_empty() && !body_trimmed.is_empty() {
+            value.push(' ');
+        }
+        if !body_trimmed.is_empty() || pieces.is_empty() {
+            pieces.push(ValuePiece {
+                logical_start: value.len(),
+                span: Span::new(start, start + body_trimmed.len()),
+            });
+            value.push_str(body_trimmed);
+        }
+        end = line.end;
+        consumed += 1;
+
+        if !continued {
+            break;
+        }
+
+        // Skip comment lines inside the continuation
+        while consumed < lines.len() {
+            let next = &text[lines[consumed].start..lines[consumed].end];
+            let next = next.trim_start();
+            if next.starts_with('#') || next.starts_with(';') {
+                end = lines[consumed].end;
+                consumed += 1;
+            } else {
+                break;
+            }
+        }
+        if consumed >= lines.len() {
+            break;
+        }
+        rest_start = lines[consumed].start;
+    }
+
+    let value_span = Span::new(
+        pieces.first().map(|p| p.span.start).unwrap_or(end),
+        pieces.last().map(|p| p.span.end).unwrap_or(end),
+    );
+    let span = Span::new(first.start, end);
+
+    if key.is_empty() {
+        unit.errors.push(SyntaxError {
+            span: Span::new(line_start, first.start + eq + 1),
+            message: "Missing key name before '='".to_string(),
+        });
+        push_entry(unit, Entry::Invalid(span));
+        return consumed;
+    }
+
+    if unit.sections.is_empty() {
+        unit.errors.push(SyntaxError {
+            span: key_span,
+            message: format!("Assignment outside of section: '{}'", key),
+        });
+        push_entry(unit, Entry::Invalid(span));
+        return consumed;
+    }
+
+    push_entry(
+        unit,
+        Entry::Directive(Directive {
+            key: key.to_string(),
+            key_span,
+            value,
+            value_span,
+            span,
+            pieces,
+        }),
+    );
+    consumed
+}
+
+fn push_entry(unit: &mut UnitFile, entry: Entry) {
+    match unit.sections.last_mut() {
+        Some(section) => {
+            section.span.end = entry.span().end;
+            section.entries.push(entry);
+        }
+        None => unit.preamble.push(entry),
+    }
+}
+
+// Split into line spans, excluding `\n` and `\r\n` terminators
+fn split_lines(text: &str) -> Vec<Span> {
+    let mut lines = Vec::new();
+    let mut start = 0;
+    for (i, b) in text.bytes().enumerate() {
+        if b == b'\n' {
+            let end = if i > start && text.as_bytes()[i - 1] == b'\r' {
+                i - 1
+            } else {
+                i
+            };
+            lines.push(Span::new(start, end));
+            start = i + 1;
+        }
+    }
+    if start < text.len() {
+        lines.push(Span::new(start, text.len()));
+    }
+    lines
+}
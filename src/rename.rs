@@ -0,0 +1,147 @@
+// Renaming a unit across the workspace: every reference to it, the unit
+// file itself, links naming it and the directories named after it
+
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+
+use tower_lsp::lsp_types::*;
+
+use crate::index::{self, UnitIndex};
+use crate::schema::UnitType;
+use crate::units;
+
+// Check a new unit name is valid to rename `old` to
+pub fn check_new_name(old: &str, new: &str) -> Result<(), String> {
+    if new.is_empty() || new.contains(['/', ' ', '\t']) {
+        return Err(format!("'{}' is not a valid unit name", new));
+    }
+    if UnitType::from_name(new) != UnitType::from_name(old) {
+        return Err(format!("'{}' must keep the type suffix of '{}'", new, old));
+    }
+    if old.contains("@.") != new.contains("@.") {
+        return Err(format!(
+            "'{}' and '{}' must both be templates or both be plain units",
+            old, new
+        ));
+    }
+    Ok(())
+}
+
+// The new text for a reference when `old` is renamed to `new`. Instances
+// of a renamed template keep their instance name.
+pub fn renamed_reference(reference: &str, old: &str, new: &str) -> Option<String> {
+    if reference == old {
+        return Some(new.to_string());
+    }
+    if units::template_of(reference)? != old {
+        return None;
+    }
+    let (_, rest) = reference.split_once('@')?;
+    let (instance, _) = rest.rsplit_once('.')?;
+    let (prefix, suffix) = new.split_once("@.")?;
+    Some(format!("{}@{}.{}", prefix, instance, suffix))
+}
+
+// Replace the last path segment of a file or directory URI
+fn with_file_name(uri: &Url, name: &str) -> Option<Url> {
+    let path = uri.to_file_path().ok()?;
+    Url::from_file_path(path.with_file_name(name)).ok()
+}
+
+// The directory holding a file, and its name
+fn parent_dir(uri: &Url) -> Option<(PathBuf, String)> {
+    let path = uri.to_file_path().ok()?;
+    let dir = path.parent()?.to_path_buf();
+    let name = dir.file_name()?.to_str()?.to_string();
+    Some((dir, name))
+}
+
+// Workspace edit renaming the unit `old` to `new`. `version` gives the
+// version of documents open in the editor.
+pub fn rename_unit(
+    index: &UnitIndex,
+    old: &str,
+    new: &str,
+    version: impl Fn(&Url) -> Option<i32>,
+) -> WorkspaceEdit {
+    let mut operations = Vec::new();
+
+    // Update every reference, Alias= entries included
+    let mut edits: BTreeMap<Url, Vec<TextEdit>> = BTreeMap::new();
+    for uri in index.uris() {
+        let Some(file) = index.get(&uri) else {
+            continue;
+        };
+        for reference in &file.references {
+            if let Some(text) = renamed_reference(&reference.name, old, new) {
+                edits
+                    .entry(uri.clone())
+                    .or_default()
+                    .push(TextEdit::new(reference.range, text));
+            }
+        }
+    }
+    for (uri, edits) in edits {
+        let version = version(&uri);
+        operations.push(DocumentChangeOperation::Edit(TextDocumentEdit {
+            text_document: OptionalVersionedTextDocumentIdentifier { uri, version },
+            edits: edits.into_iter().map(OneOf::Left).collect(),
+        }));
+    }
+
+    // Rename the unit files and the links in `.wants/` and similar
+    // directories naming them, then the directories named after them
+    let rename = |old_uri: Url, new_uri: Url| {
+        DocumentChangeOperation::Op(ResourceOp::Rename(RenameFile {
+            old_uri,
+            new_uri,
+            options: None,
+            annotation_id: None,
+        }))
+    };
+    for uri in index.definitions(old) {
+        if let Some(new_uri) = with_file_name(&uri, new) {
+            operations.push(rename(uri, new_uri));
+        }
+    }
+
+    // Directories to rename and their new names
+    let mut dirs: BTreeMap<PathBuf, String> = BTreeMap::new();
+    for (uri, link) in index.links() {
+        if let Some(name) = renamed_reference(&link.name, old, new)
+            && let Some(new_uri) = with_file_name(&uri, &name)
+        {
+            operations.push(rename(uri.clone(), new_uri));
+        }
+        if let Some(unit) = renamed_reference(&link.unit, old, new)
+            && let Some((dir, name)) = parent_dir(&uri)
+            && let Some(suffix) = name.strip_prefix(&link.unit)
+        {
+            dirs.insert(dir, format!("{}{}", unit, suffix));
+        }
+    }
+
+    // Drop-in directories, those of the instances of a template included
+    for uri in index.uris() {
+        if index::unit_name(&uri).is_none()
+            && let Some((dir, name)) = parent_dir(&uri)
+            && let Some(unit) = name.strip_suffix(".d")
+            && let Some(new_unit) = renamed_reference(unit, old, new)
+        {
+            dirs.insert(dir, format!("{}.d", new_unit));
+        }
+    }
+
+    for (dir, name) in dirs {
+        let new_dir = dir.with_file_name(name);
+        if let (Ok(old_uri), Ok(new_uri)) = (Url::from_file_path(dir), Url::from_file_path(new_dir))
+        {
+            operations.push(rename(old_uri, new_uri));
+        }
+    }
+
+    WorkspaceEdit {
+        document_changes: Some(DocumentChanges::Operations(operations)),
+        ..Default::default()
+    }
+}
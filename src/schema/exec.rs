@@ -0,0 +1,341 @@
+// Execution environment and kill settings shared by [Service], [Socket],
+// [Mount] and [Swap], systemd.exec(5) and systemd.kill(5)
+
+use super::{Directive as D, DirectiveTable, EnumValue as V, ValueType as T};
+
+static PROTECT_SYSTEM: &[V] = &[
+    V::new("yes").doc("Mount /usr and the boot loader directories read-only."),
+    V::new("no").doc("Do not protect the OS directories."),
+    V::new("full").doc("Also mount /etc read-only."),
+    V::new("strict")
+        .doc("Mount the whole file system hierarchy read-only, except /dev, /proc and /sys."),
+];
+
+static PROTECT_HOME: &[V] = &[
+    V::new("yes").doc("Make /home, /root and /run/user inaccessible."),
+    V::new("no").doc("Do not protect home directories."),
+    V::new("read-only").doc("Mount /home, /root and /run/user read-only."),
+    V::new("tmpfs")
+        .doc("Mount empty read-only tmpfs file systems over /home, /root and /run/user."),
+];
+
+static PROTECT_PROC: &[V] = &[
+    V::new("noaccess"),
+    V::new("invisible"),
+    V::new("ptraceable"),
+    V::new("default"),
+];
+
+static PRIVATE_TMP: &[V] = &[
+    V::new("yes"),
+    V::new("no"),
+    V::new("disconnected").since(257),
+];
+
+static PRIVATE_USERS: &[V] = &[
+    V::new("yes"),
+    V::new("no"),
+    V::new("self").since(257),
+    V::new("identity").since(257),
+];
+
+static PROTECT_HOSTNAME: &[V] = &[V::new("yes"), V::new("no"), V::new("private").since(258)];
+
+static PROTECT_CONTROL_GROUPS: &[V] = &[
+    V::new("yes"),
+    V::new("no"),
+    V::new("private").since(257),
+    V::new("strict").since(257),
+];
+
+static PROC_SUBSET: &[V] = &[V::new("all"), V::new("pid")];
+
+static KEYRING_MODES: &[V] = &[V::new("inherit"), V::new("private"), V::new("shared")];
+
+static PERSONALITIES: &[V] = &[
+    V::new("x86"),
+    V::new("x86-64"),
+    V::new("ppc"),
+    V::new("ppc-le"),
+    V::new("ppc64"),
+    V::new("ppc64-le"),
+    V::new("s390"),
+    V::new("s390x"),
+    V::new("arm"),
+    V::new("arm64"),
+];
+
+static SCHEDULING_POLICIES: &[V] = &[
+    V::new("other"),
+    V::new("batch"),
+    V::new("idle"),
+    V::new("fifo"),
+    V::new("rr"),
+];
+
+static NUMA_POLICIES: &[V] = &[
+    V::new("default"),
+    V::new("preferred"),
+    V::new("bind"),
+    V::new("interleave"),
+    V::new("local"),
+];
+
+static IO_SCHEDULING_CLASSES: &[V] = &[
+    V::new("realtime"),
+    V::new("best-effort"),
+    V::new("idle"),
+    V::new("none").since(248),
+    V::new("0"),
+    V::new("1"),
+    V::new("2"),
+    V::new("3"),
+];
+
+static DIRECTORY_PRESERVE: &[V] = &[V::new("no"), V::new("yes"), V::new("restart")];
+
+static MOUNT_FLAGS: &[V] = &[V::new("shared"), V::new("slave"), V::new("private")];
+
+static STANDARD_INPUT: &[V] = &[
+    V::new("null").doc("Connect standard input to /dev/null."),
+    V::new("tty").doc("Connect standard input to the terminal in TTYPath=."),
+    V::new("tty-force").doc("Like tty, taking the terminal over even if another process owns it."),
+    V::new("tty-fail").doc("Like tty, failing if the terminal is owned by another process."),
+    V::new("data").since(236).doc("Read standard input from StandardInputText= and StandardInputData=."),
+    V::new("file:").since(236).doc("Read standard input from the given file."),
+    V::new("socket").doc("Connect standard input to the socket the service was activated from."),
+    V::new("fd:").doc("Connect standard input to a file descriptor passed by the socket unit with the given FileDescriptorName=."),
+];
+
+static STANDARD_OUTPUT: &[V] = &[
+    V::new("inherit").doc("Use the same file descriptor as standard input."),
+    V::new("null").doc("Discard the output."),
+    V::new("tty").doc("Write to the terminal in TTYPath=."),
+    V::new("journal").doc("Send the output to the journal."),
+    V::new("kmsg").doc("Send the output to the kernel log buffer."),
+    V::new("journal+console").doc("Send the output to the journal and the system console."),
+    V::new("kmsg+console").doc("Send the output to the kernel log buffer and the system console."),
+    V::new("file:")
+        .since(236)
+        .doc("Write to the given file, overwriting from the start without truncating it."),
+    V::new("append:")
+        .since(240)
+        .doc("Append to the given file."),
+    V::new("truncate:")
+        .since(248)
+        .doc("Truncate the given file and write to it."),
+    V::new("socket").doc("Write to the socket the service was activated from."),
+    V::new("fd:").doc(
+        "Write to a file descriptor passed by the socket unit with the given FileDescriptorName=.",
+    ),
+];
+
+static LOG_LEVELS: &[V] = &[
+    V::new("emerg"),
+    V::new("alert"),
+    V::new("crit"),
+    V::new("err"),
+    V::new("warning"),
+    V::new("notice"),
+    V::new("info"),
+    V::new("debug"),
+];
+
+static SYSLOG_FACILITIES: &[V] = &[
+    V::new("kern"),
+    V::new("user"),
+    V::new("mail"),
+    V::new("daemon"),
+    V::new("auth"),
+    V::new("syslog"),
+    V::new("lpr"),
+    V::new("news"),
+    V::new("uucp"),
+    V::new("cron"),
+    V::new("authpriv"),
+    V::new("ftp"),
+    V::new("local0"),
+    V::new("local1"),
+    V::new("local2"),
+    V::new("local3"),
+    V::new("local4"),
+    V::new("local5"),
+    V::new("local6"),
+    V::new("local7"),
+];
+
+static UTMP_MODES: &[V] = &[V::new("init"), V::new("login"), V::new("user")];
+
+static KILL_MODES: &[V] = &[
+    V::new("control-group").doc("Kill every remaining process in the unit's control group."),
+    V::new("mixed")
+        .doc("Send SIGTERM to the main process and SIGKILL to the rest of the control group."),
+    V::new("process").doc("Kill only the main process."),
+    V::new("none").doc("Kill no process; only ExecStop= is run."),
+];
+
+#[rustfmt::skip]
+pub static EXEC: DirectiveTable = DirectiveTable {
+    man: "systemd.exec(5)",
+    directives: &[
+        D::new("ExecSearchPath", T::Path, "Colon-separated list of directories used to resolve relative executable names in Exec*= lines.").since(250),
+        D::new("WorkingDirectory", T::Path, "The working directory of executed processes, `~` for the user's home directory, prefixed with `-` to ignore a missing directory."),
+        D::new("RootDirectory", T::Path, "Runs executed processes with the given directory as the root directory, using chroot(2)."),
+        D::new("RootImage", T::Path, "Mounts the given disk image or block device and uses it as the root directory."),
+        D::new("RootImageOptions", T::String, "Mount options for the partitions of RootImage=.").list().since(247),
+        D::new("RootEphemeral", T::Boolean, "If true, runs the service on an ephemeral copy of the root directory or image.").since(254),
+        D::new("RootHash", T::String, "The dm-verity root hash used to verify RootImage=.").since(246),
+        D::new("RootHashSignature", T::String, "The PKCS7 signature of the RootHash= value.").since(246),
+        D::new("RootVerity", T::Path, "The path to the dm-verity data of RootImage=.").since(246),
+        D::new("RootImagePolicy", T::String, "The image policy applied to RootImage=.").since(254),
+        D::new("MountImagePolicy", T::String, "The image policy applied to MountImages=.").since(254),
+        D::new("ExtensionImagePolicy", T::String, "The image policy applied to ExtensionImages=.").since(254),
+        D::new("MountAPIVFS", T::Boolean, "If true, mounts /proc, /sys, /dev and /run in the private root file system."),
+        D::new("ProtectProc", T::Enum(PROTECT_PROC), "Controls the hidepid= mount option of the procfs instance for the unit.").since(247),
+        D::new("ProcSubset", T::Enum(PROC_SUBSET), "If pid, hides all files and directories of /proc not related to process management.").since(247),
+        D::new("BindPaths", T::Path, "Bind mounts host paths into the unit's file system namespace, read-write.").list(),
+        D::new("BindReadOnlyPaths", T::Path, "Bind mounts host paths into the unit's file system namespace, read-only.").list(),
+        D::new("MountImages", T::Path, "Mounts disk images at the given paths in the unit's file system namespace.").list().since(247),
+        D::new("ExtensionImages", T::Path, "Disk images overlaid on /usr and /opt as system extensions.").list().since(248),
+        D::new("ExtensionDirectories", T::Path, "Directories overlaid on /usr and /opt as system extensions.").list().since(251),
+        D::new("User", T::String, "The UNIX user or UID the processes are executed as."),
+        D::new("Group", T::String, "The UNIX group or GID the processes are executed as."),
+        D::new("DynamicUser", T::Boolean, "If true, a UNIX user and group pair is allocated dynamically when the unit is started.").since(235),
+        D::new("SupplementaryGroups", T::String, "Supplementary UNIX groups the processes are executed as.").list(),
+        D::new("SetLoginEnvironment", T::Boolean, "If true, sets $HOME, $LOGNAME and $SHELL for the processes even when no login session is registered.").since(255),
+        D::new("PAMName", T::String, "Registers a PAM session with the given service name."),
+        D::new("CapabilityBoundingSet", T::String, "Controls which capabilities to include in the capability bounding set of the executed process.").list(),
+        D::new("AmbientCapabilities", T::String, "Controls which capabilities to include in the ambient capability set of the executed process.").list(),
+        D::new("NoNewPrivileges", T::Boolean, "If true, ensures the process and its children can never gain new privileges through execve().").default_value("no"),
+        D::new("SecureBits", T::String, "Controls the secure bits set for the executed process.").list(),
+        D::new("SELinuxContext", T::String, "Sets the SELinux security context of the executed process."),
+        D::new("AppArmorProfile", T::String, "Switches to the given AppArmor profile when executing the process."),
+        D::new("SmackProcessLabel", T::String, "Sets the SMACK64 security label of the executed process."),
+        D::new("LimitCPU", T::Limit, "Limit on CPU time, as with ulimit -t."),
+        D::new("LimitFSIZE", T::Limit, "Limit on file size, as with ulimit -f."),
+        D::new("LimitDATA", T::Limit, "Limit on the data segment size, as with ulimit -d."),
+        D::new("LimitSTACK", T::Limit, "Limit on the stack size, as with ulimit -s."),
+        D::new("LimitCORE", T::Limit, "Limit on the core file size, as with ulimit -c."),
+        D::new("LimitRSS", T::Limit, "Limit on the resident set size, as with ulimit -m."),
+        D::new("LimitNOFILE", T::Limit, "Limit on the number of open file descriptors, as with ulimit -n."),
+        D::new("LimitAS", T::Limit, "Limit on the address space size, as with ulimit -v."),
+        D::new("LimitNPROC", T::Limit, "Limit on the number of processes, as with ulimit -u."),
+        D::new("LimitMEMLOCK", T::Limit, "Limit on locked memory, as with ulimit -l."),
+        D::new("LimitLOCKS", T::Limit, "Limit on the number of file locks, as with ulimit -x."),
+        D::new("LimitSIGPENDING", T::Limit, "Limit on the number of pending signals, as with ulimit -i."),
+        D::new("LimitMSGQUEUE", T::Limit, "Limit on POSIX message queue memory, as with ulimit -q."),
+        D::new("LimitNICE", T::Limit, "Limit on the nice level, as with ulimit -e."),
+        D::new("LimitRTPRIO", T::Limit, "Limit on the realtime priority, as with ulimit -r."),
+        D::new("LimitRTTIME", T::Limit, "Limit on realtime CPU time without a blocking system call, in microseconds."),
+        D::new("UMask", T::Mode, "The file mode creation mask of executed processes, in octal notation.").default_value("0022"),
+        D::new("CoredumpFilter", T::String, "Controls which types of memory mappings are written to core dumps.").since(246),
+        D::new("KeyringMode", T::Enum(KEYRING_MODES), "Controls how the kernel session keyring is set up for the service.").since(235),
+        D::new("OOMScoreAdjust", T::Integer, "The adjustment value for the OOM killer, between -1000 and 1000."),
+        D::new("TimerSlackNSec", T::Timespan, "The timer slack for executed processes, in nanoseconds by default."),
+        D::new("Personality", T::Enum(PERSONALITIES), "Controls the process execution domain, as with personality(2)."),
+        D::new("IgnoreSIGPIPE", T::Boolean, "If true, causes SIGPIPE to be ignored in the executed process."),
+        D::new("Nice", T::Integer, "The default nice level of executed processes, between -20 and 19.").default_value("0"),
+        D::new("CPUSchedulingPolicy", T::Enum(SCHEDULING_POLICIES), "The CPU scheduling policy of executed processes.").default_value("other"),
+        D::new("CPUSchedulingPriority", T::Integer, "The CPU scheduling priority of executed processes, depending on the scheduling policy."),
+        D::new("CPUSchedulingResetOnFork", T::Boolean, "If true, elevated CPU scheduling priorities and policies are reset when executed processes fork."),
+        D::new("CPUAffinity", T::String, "Controls the CPU affinity of executed processes, as a list of CPU indices or ranges.").list(),
+        D::new("NUMAPolicy", T::Enum(NUMA_POLICIES), "The NUMA memory policy of executed processes.").since(243),
+        D::new("NUMAMask", T::String, "The NUMA node mask used together with NUMAPolicy=.").since(243),
+        D::new("IOSchedulingClass", T::Enum(IO_SCHEDULING_CLASSES), "The I/O scheduling class of executed processes."),
+        D::new("IOSchedulingPriority", T::Integer, "The I/O scheduling priority of executed processes, between 0 (highest) and 7 (lowest)."),
+        D::new("ProtectSystem", T::Enum(PROTECT_SYSTEM), "Mounts /usr and the boot loader directories read-only; full also covers /etc, strict the whole file system hierarchy.").default_value("no"),
+        D::new("ProtectHome", T::Enum(PROTECT_HOME), "Makes /home, /root and /run/user inaccessible, read-only or empty for the unit's processes.").default_value("no"),
+        D::new("RuntimeDirectory", T::Path, "Directories created below /run when the unit is started and removed when it is stopped.").list(),
+        D::new("StateDirectory", T::Path, "Directories created below /var/lib when the unit is started.").list(),
+        D::new("CacheDirectory", T::Path, "Directories created below /var/cache when the unit is started.").list(),
+        D::new("LogsDirectory", T::Path, "Directories created below /var/log when the unit is started.").list(),
+        D::new("ConfigurationDirectory", T::Path, "Directories created below /etc when the unit is started.").list(),
+        D::new("RuntimeDirectoryMode", T::Mode, "The access mode of the directories listed in RuntimeDirectory=."),
+        D::new("StateDirectoryMode", T::Mode, "The access mode of the directories listed in StateDirectory=."),
+        D::new("CacheDirectoryMode", T::Mode, "The access mode of the directories listed in CacheDirectory=."),
+        D::new("LogsDirectoryMode", T::Mode, "The access mode of the directories listed in LogsDirectory=."),
+        D::new("ConfigurationDirectoryMode", T::Mode, "The access mode of the directories listed in ConfigurationDirectory=."),
+        D::new("RuntimeDirectoryPreserve", T::Enum(DIRECTORY_PRESERVE), "Whether the RuntimeDirectory= directories are kept when the unit is stopped."),
+        D::new("TimeoutCleanSec", T::Timespan, "Time limit for `systemctl clean` operations on this unit.").since(244),
+        D::new("ReadWritePaths", T::Path, "Paths made accessible read-write to the unit's processes.").list(),
+        D::new("ReadOnlyPaths", T::Path, "Paths made accessible read-only to the unit's processes.").list(),
+        D::new("InaccessiblePaths", T::Path, "Paths made inaccessible to the unit's processes.").list(),
+        D::new("ExecPaths", T::Path, "Paths where programs may be executed from, when combined with NoExecPaths=.").list().since(247),
+        D::new("NoExecPaths", T::Path, "Paths where programs may not be executed from.").list().since(247),
+        D::new("TemporaryFileSystem", T::Path, "Mounts a tmpfs on the given paths in the unit's file system namespace.").list().since(238),
+        D::new("PrivateTmp", T::Enum(PRIVATE_TMP), "If true, sets up a private /tmp and /var/tmp for the executed processes.").default_value("no"),
+        D::new("PrivateDevices", T::Boolean, "If true, sets up a private /dev with only pseudo devices for the executed processes."),
+        D::new("PrivateNetwork", T::Boolean, "If true, sets up a private network namespace with only a loopback device."),
+        D::new("NetworkNamespacePath", T::Path, "Joins the network namespace referenced by the given file.").since(242),
+        D::new("PrivateIPC", T::Boolean, "If true, sets up a private IPC namespace for the executed processes.").since(248),
+        D::new("IPCNamespacePath", T::Path, "Joins the IPC namespace referenced by the given file.").since(248),
+        D::new("MemoryKSM", T::Boolean, "If true, enables kernel samepage merging for the processes of the unit.").since(254),
+        D::new("PrivateUsers", T::Enum(PRIVATE_USERS), "If true, sets up a new user namespace mapping only root and the unit's user."),
+        D::new("ProtectHostname", T::Enum(PROTECT_HOSTNAME), "If true, sets up a new UTS namespace so the hostname cannot be changed.").since(242),
+        D::new("ProtectClock", T::Boolean, "If true, denies writes to the system and hardware clock.").since(245),
+        D::new("ProtectKernelTunables", T::Boolean, "If true, makes kernel variables in /proc/sys, /sys and similar read-only."),
+        D::new("ProtectKernelModules", T::Boolean, "If true, denies explicit kernel module loading."),
+        D::new("ProtectKernelLogs", T::Boolean, "If true, denies access to the kernel log ring buffer.").since(244),
+        D::new("ProtectControlGroups", T::Enum(PROTECT_CONTROL_GROUPS), "If true, makes the cgroup hierarchy read-only."),
+        D::new("RestrictAddressFamilies", T::String, "Restricts the socket address families the processes may use.").list(),
+        D::new("RestrictFileSystems", T::String, "Restricts the file system types the processes may access.").list().since(250),
+        D::new("RestrictNamespaces", T::String, "Restricts the namespace types the processes may create, or a boolean.").list(),
+        D::new("LockPersonality", T::Boolean, "If true, locks down the personality(2) system call."),
+        D::new("MemoryDenyWriteExecute", T::Boolean, "If true, prohibits creating memory mappings that are both writable and executable."),
+        D::new("RestrictRealtime", T::Boolean, "If true, refuses attempts to enable realtime scheduling."),
+        D::new("RestrictSUIDSGID", T::Boolean, "If true, refuses attempts to set the set-user-ID or set-group-ID bits on files.").since(242),
+        D::new("RemoveIPC", T::Boolean, "If true, removes all System V and POSIX IPC objects owned by the unit's user when it stops."),
+        D::new("PrivateMounts", T::Boolean, "If true, runs the processes in their own mount namespace.").since(239),
+        D::new("MountFlags", T::Enum(MOUNT_FLAGS), "The mount propagation flag of the unit's mount namespace.").default_value("shared"),
+        D::new("SystemCallFilter", T::String, "Allow-list or, prefixed with `~`, deny-list of system calls the processes may use.").list(),
+        D::new("SystemCallErrorNumber", T::String, "The errno returned for system calls denied by SystemCallFilter=, or kill or log."),
+        D::new("SystemCallArchitectures", T::String, "The system call architectures the processes may use.").list(),
+        D::new("SystemCallLog", T::String, "System calls to log when invoked by the processes.").list().since(247),
+        D::new("Environment", T::String, "Sets environment variables as space-separated VAR=VALUE assignments.").list(),
+        D::new("EnvironmentFile", T::Path, "Reads environment variables from a file, prefixed with `-` to ignore a missing file.").list(),
+        D::new("PassEnvironment", T::String, "Passes the listed variables from the service manager's environment.").list(),
+        D::new("UnsetEnvironment", T::String, "Removes the listed variables or assignments from the environment.").list(),
+        D::new("StandardInput", T::Enum(STANDARD_INPUT), "Controls where file descriptor 0 of executed processes is connected to.").default_value("null"),
+        D::new("StandardOutput", T::Enum(STANDARD_OUTPUT), "Controls where file descriptor 1 of executed processes is connected to.").default_value("journal"),
+        D::new("StandardError", T::Enum(STANDARD_OUTPUT), "Controls where file descriptor 2 of executed processes is connected to.").default_value("inherit"),
+        D::new("StandardInputText", T::String, "Text passed to standard input when StandardInput=data.").list().since(236),
+        D::new("StandardInputData", T::String, "Base64 data passed to standard input when StandardInput=data.").list().since(236),
+        D::new("LogLevelMax", T::Enum(LOG_LEVELS), "The maximum log level of messages from this unit that are processed."),
+        D::new("LogExtraFields", T::String, "Additional journal fields attached to log messages of this unit.").list(),
+        D::new("LogRateLimitIntervalSec", T::Timespan, "The interval used for rate limiting log messages of this unit.").since(240),
+        D::new("LogRateLimitBurst", T::Integer, "How many log messages are accepted within LogRateLimitIntervalSec=.").since(240),
+        D::new("LogFilterPatterns", T::String, "Regular expressions filtering log messages of this unit.").list().since(253),
+        D::new("LogNamespace", T::String, "The journal namespace log messages of this unit are written to.").since(245),
+        D::new("SyslogIdentifier", T::String, "The process name prefixed to log lines sent to the journal or syslog."),
+        D::new("SyslogFacility", T::Enum(SYSLOG_FACILITIES), "The syslog facility used when logging."),
+        D::new("SyslogLevel", T::Enum(LOG_LEVELS), "The default syslog level used when logging.").default_value("info"),
+        D::new("SyslogLevelPrefix", T::Boolean, "If true, interprets kernel-style log level prefixes on log lines."),
+        D::new("TTYPath", T::Path, "The terminal device node standard input, output or error are connected to."),
+        D::new("TTYReset", T::Boolean, "If true, resets the terminal device specified with TTYPath= before and after execution."),
+        D::new("TTYVHangup", T::Boolean, "If true, disconnects all clients of the terminal device before and after execution."),
+        D::new("TTYRows", T::Integer, "The number of rows of the terminal device.").since(250),
+        D::new("TTYColumns", T::Integer, "The number of columns of the terminal device.").since(250),
+        D::new("TTYVTDisallocate", T::Boolean, "If true, deallocates the virtual console before and after execution."),
+        D::new("LoadCredential", T::String, "Passes a credential read from a file or socket to the unit, as ID:PATH.").list().since(247),
+        D::new("LoadCredentialEncrypted", T::String, "Like LoadCredential=, but the credential is decrypted first.").list().since(250),
+        D::new("ImportCredential", T::String, "Imports credentials passed to the service manager matching a glob.").list().since(254),
+        D::new("SetCredential", T::String, "Passes a credential given literally to the unit, as ID:VALUE.").list().since(247),
+        D::new("SetCredentialEncrypted", T::String, "Like SetCredential=, but the value is encrypted.").list().since(250),
+        D::new("UtmpIdentifier", T::String, "The utmp/wtmp ID used for the executed process."),
+        D::new("UtmpMode", T::Enum(UTMP_MODES), "The type of utmp/wtmp record written when UtmpIdentifier= is set."),
+    ],
+};
+
+#[rustfmt::skip]
+pub static KILL: DirectiveTable = DirectiveTable {
+    man: "systemd.kill(5)",
+    directives: &[
+        D::new("KillMode", T::Enum(KILL_MODES), "Specifies how processes of this unit are killed.").default_value("control-group"),
+        D::new("KillSignal", T::Signal, "The signal used when stopping the unit, SIGTERM by default.").default_value("SIGTERM"),
+        D::new("RestartKillSignal", T::Signal, "The signal used when restarting the unit.").since(244),
+        D::new("SendSIGHUP", T::Boolean, "If true, sends SIGHUP to remaining processes immediately after the stop signal.").default_value("no"),
+        D::new("SendSIGKILL", T::Boolean, "If true, sends SIGKILL to remaining processes after the stop timeout.").default_value("yes"),
+        D::new("FinalKillSignal", T::Signal, "The signal sent to remaining processes after SIGKILL failed to terminate them.").since(239).default_value("SIGKILL"),
+        D::new("WatchdogSignal", T::Signal, "The signal sent when the watchdog timeout expires.").since(240),
+    ],
+};
@@ -0,0 +1,433 @@
+// Directive schema for systemd unit files.
+//
+// The tables in the submodules are written by hand from the man pages, one
+// table per page, and are not generated from systemd's sources. They are
+// combined per section the way systemd's `load-fragment-gperf.gperf.in`
+// shares its EXEC/KILL/CGROUP context settings between [Service],
+// [Socket], [Mount] and [Swap]. Completion, hover and diagnostics all read
+// from here.
+//
+// The gperf table only carries names and parser callbacks, not the value
+// grammar, enum values or docs, so a generator would still need the man
+// pages on top. The tables are instead kept in step with the release
+// pinned in `SYSTEMD_VERSION`; bump it together with the tables.
+
+use std::path::Path;
+
+use tower_lsp::lsp_types::Url;
+
+mod exec;
+mod mount;
+mod resource_control;
+mod service;
+mod socket;
+mod timer;
+mod unit;
+
+// systemd release whose man pages the tables are written against
+pub const SYSTEMD_VERSION: u32 = 258;
+
+// Grammar of a directive value
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ValueType {
+    String,
+    Boolean,
+    Integer,
+    // Time span such as `5min 30s` or `infinity`
+    Timespan,
+    // Byte size with optional K/M/G/T/P/E suffix, or `infinity`
+    Size,
+    // Resource limit, `soft[:hard]` or `infinity`
+    Limit,
+    // Percentage or permille
+    Percent,
+    // Integer weight between 1 and 10000
+    Weight,
+    // Task count, percentage or `infinity`
+    Tasks,
+    // Octal access mode
+    Mode,
+    Signal,
+    Path,
+    // Command line, as in ExecStart=
+    Command,
+    // Space-separated unit names
+    Units,
+    // Calendar event expression, as in OnCalendar=
+    Calendar,
+    Enum(&'static [EnumValue]),
+}
+
+impl ValueType {
+    // What the value is, for documentation
+    pub fn describe(&self) -> &'static str {
+        match self {
+            ValueType::String => "a string",
+            ValueType::Boolean => "a boolean (`yes`/`no`, `true`/`false`, `on`/`off`, `1`/`0`)",
+            ValueType::Integer => "an integer",
+            ValueType::Timespan => "a time span such as `90s`, `5min 30s` or `infinity`",
+            ValueType::Size => {
+                "a size in bytes with an optional `K`, `M`, `G`, `T`, `P` or `E` suffix, a percentage, or `infinity`"
+            }
+            ValueType::Limit => "a resource limit, `soft[:hard]` or `infinity`",
+            ValueType::Percent => "a percentage or permille",
+            ValueType::Weight => "a weight between 1 and 10000",
+            ValueType::Tasks => "a task count, a percentage or `infinity`",
+            ValueType::Mode => "an octal access mode such as `0644`",
+            ValueType::Signal => "a signal name such as `SIGTERM`, or number",
+            ValueType::Path => "an absolute path",
+            ValueType::Command => {
+                "a command line, optionally prefixed with `@`, `-`, `:`, `+`, `!` or `!!`; a lone `;` separates commands"
+            }
+            ValueType::Units => "a space-separated list of unit names",
+            ValueType::Calendar => "a calendar event such as `daily` or `Mon..Fri *-*-* 09:00`",
+            ValueType::Enum(_) => "one of the values below",
+        }
+    }
+
+    // Whether `%` specifiers are resolved in the value. In numbers and
+    // other typed values `%` means percent instead.
+    pub fn expands_specifiers(&self) -> bool {
+        matches!(
+            self,
+            ValueType::String | ValueType::Path | ValueType::Command | ValueType::Units
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EnumValue {
+    pub name: &'static str,
+    // First systemd release accepting the value, 0 if always accepted
+    pub since: u32,
+    pub doc: &'static str,
+}
+
+impl EnumValue {
+    pub const fn new(name: &'static str) -> Self {
+        Self {
+            name,
+            since: 0,
+            doc: "",
+        }
+    }
+
+    pub const fn since(mut self, version: u32) -> Self {
+        self.since = version;
+        self
+    }
+
+    pub const fn doc(mut self, doc: &'static str) -> Self {
+        self.doc = doc;
+        self
+    }
+
+    // Values ending in ':' take an argument, as in `file:/path`
+    pub fn matches(&self, value: &str) -> bool {
+        match self.name.strip_suffix(':') {
+            Some(prefix) => value
+                .strip_prefix(prefix)
+                .is_some_and(|rest| rest.starts_with(':')),
+            None => self.name == value,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Directive {
+    pub name: &'static str,
+    pub value: ValueType,
+    // Whether repeated assignments accumulate instead of overriding
+    pub repeatable: bool,
+    // Whether an empty assignment resets the accumulated list
+    pub empty_resets: bool,
+    // First systemd release accepting the directive, 0 if always accepted
+    pub since: u32,
+    // Still accepted, but superseded by another directive
+    pub deprecated: bool,
+    // Value used when the directive is not set, empty if not documented
+    pub default: &'static str,
+    pub doc: &'static str,
+}
+
+impl Directive {
+    pub const fn new(name: &'static str, value: ValueType, doc: &'static str) -> Self {
+        Self {
+            name,
+            value,
+            repeatable: false,
+            empty_resets: false,
+            since: 0,
+            deprecated: false,
+            default: "",
+            doc,
+        }
+    }
+
+    // Repeated assignments accumulate and an empty assignment resets the list
+    pub const fn list(mut self) -> Self {
+        self.repeatable = true;
+        self.empty_resets = true;
+        self
+    }
+
+    pub const fn since(mut self, version: u32) -> Self {
+        self.since = version;
+        self
+    }
+
+    pub const fn deprecated(mut self) -> Self {
+        self.deprecated = true;
+        self
+    }
+
+    pub const fn default_value(mut self, value: &'static str) -> Self {
+        self.default = value;
+        self
+    }
+
+    pub fn enum_values(&self) -> &'static [EnumValue] {
+        match self.value {
+            ValueType::Enum(values) => values,
+            _ => &[],
+        }
+    }
+
+    // The enum value a literal value selects. Enums offering `yes` and `no`
+    // accept every boolean spelling for them, as systemd does.
+    pub fn find_enum_value(&self, value: &str) -> Option<&'static EnumValue> {
+        let values = self.enum_values();
+        values.iter().find(|v| v.matches(value)).or_else(|| {
+            let canonical = boolean_alias(value)?;
+            values.iter().find(|v| v.name == canonical)
+        })
+    }
+}
+
+// Canonical `yes`/`no` for systemd's boolean spellings
+pub fn boolean_alias(value: &str) -> Option<&'static str> {
+    match value.to_ascii_lowercase().as_str() {
+        "1" | "yes" | "y" | "true" | "t" | "on" => Some("yes"),
+        "0" | "no" | "n" | "false" | "f" | "off" => Some("no"),
+        _ => None,
+    }
+}
+
+// Directives documented in one man page
+#[derive(Debug)]
+pub struct DirectiveTable {
+    pub man: &'static str,
+    pub directives: &'static [Directive],
+}
+
+#[derive(Debug)]
+pub struct SectionSchema {
+    pub name: &'static str,
+    pub doc: &'static str,
+    pub tables: &'static [&'static DirectiveTable],
+}
+
+impl SectionSchema {
+    pub fn directives(&self) -> impl Iterator<Item = &'static Directive> + '_ {
+        self.tables.iter().flat_map(|table| table.directives.iter())
+    }
+
+    pub fn directive(&self, key: &str) -> Option<&'static Directive> {
+        self.directives().find(|directive| directive.name == key)
+    }
+
+    // The table, and so the man page, documenting a directive
+    pub fn table(&self, key: &str) -> Option<&'static DirectiveTable> {
+        self.tables.iter().copied().find(|table| {
+            table
+                .directives
+                .iter()
+                .any(|directive| directive.name == key)
+        })
+    }
+}
+
+pub static SECTIONS: &[SectionSchema] = &[
+    SectionSchema {
+        name: "Unit",
+        doc: "Generic information about the unit that is not dependent on the type of unit, such as its description, dependencies and conditions.",
+        tables: &[&unit::UNIT],
+    },
+    SectionSchema {
+        name: "Install",
+        doc: "Installation information used by `systemctl enable` and `disable`, such as which targets want this unit.",
+        tables: &[&unit::INSTALL],
+    },
+    SectionSchema {
+        name: "Service",
+        doc: "Configuration of a process controlled and supervised by systemd.",
+        tables: &[
+            &service::SERVICE,
+            &exec::EXEC,
+            &exec::KILL,
+            &resource_control::CGROUP,
+        ],
+    },
+    SectionSchema {
+        name: "Socket",
+        doc: "Configuration of an IPC or network socket or FIFO used for socket-based activation.",
+        tables: &[
+            &socket::SOCKET,
+            &exec::EXEC,
+            &exec::KILL,
+            &resource_control::CGROUP,
+        ],
+    },
+    SectionSchema {
+        name: "Mount",
+        doc: "Configuration of a file system mount point controlled by systemd.",
+        tables: &[
+            &mount::MOUNT,
+            &exec::EXEC,
+            &exec::KILL,
+            &resource_control::CGROUP,
+        ],
+    },
+    SectionSchema {
+        name: "Automount",
+        doc: "Configuration of a mount point that is mounted on demand when accessed.",
+        tables: &[&mount::AUTOMOUNT],
+    },
+    SectionSchema {
+        name: "Swap",
+        doc: "Configuration of a swap device or file controlled by systemd.",
+        tables: &[
+            &mount::SWAP,
+            &exec::EXEC,
+            &exec::KILL,
+            &resource_control::CGROUP,
+        ],
+    },
+    SectionSchema {
+        name: "Timer",
+        doc: "Configuration of a timer used for timer-based activation of another unit.",
+        tables: &[&timer::TIMER],
+    },
+    SectionSchema {
+        name: "Path",
+        doc: "Configuration of a path monitored by systemd for path-based activation of another unit.",
+        tables: &[&timer::PATH],
+    },
+    SectionSchema {
+        name: "Slice",
+        doc: "Resource control settings for a group of units arranged in the cgroup hierarchy.",
+        tables: &[&resource_control::CGROUP],
+    },
+    SectionSchema {
+        name: "Scope",
+        doc: "Configuration of externally created processes grouped and managed by systemd.",
+        tables: &[&service::SCOPE, &exec::KILL, &resource_control::CGROUP],
+    },
+];
+
+pub fn section(name: &str) -> Option<&'static SectionSchema> {
+    SECTIONS.iter().find(|section| section.name == name)
+}
+
+pub fn lookup(section_name: &str, key: &str) -> Option<&'static Directive> {
+    section(section_name)?.directive(key)
+}
+
+// Unit type, as determined by the unit file name suffix
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum UnitType {
+    Service,
+    Socket,
+    Device,
+    Mount,
+    Automount,
+    Swap,
+    Target,
+    Path,
+    Timer,
+    Slice,
+    Scope,
+}
+
+impl UnitType {
+    pub const ALL: [UnitType; 11] = [
+        UnitType::Service,
+        UnitType::Socket,
+        UnitType::Device,
+        UnitType::Mount,
+        UnitType::Automount,
+        UnitType::Swap,
+        UnitType::Target,
+        UnitType::Path,
+        UnitType::Timer,
+        UnitType::Slice,
+        UnitType::Scope,
+    ];
+
+    pub fn suffix(&self) -> &'static str {
+        match self {
+            UnitType::Service => "service",
+            UnitType::Socket => "socket",
+            UnitType::Device => "device",
+            UnitType::Mount => "mount",
+            UnitType::Automount => "automount",
+            UnitType::Swap => "swap",
+            UnitType::Target => "target",
+            UnitType::Path => "path",
+            UnitType::Timer => "timer",
+            UnitType::Slice => "slice",
+            UnitType::Scope => "scope",
+        }
+    }
+
+    pub fn from_suffix(suffix: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|t| t.suffix() == suffix)
+    }
+
+    // Type of a unit name such as `foo.service` or `foo@.service`
+    pub fn from_name(name: &str) -> Option<Self> {
+        let (_, suffix) = name.rsplit_once('.')?;
+        Self::from_suffix(suffix)
+    }
+
+    // Type of a unit file path. Drop-in `.conf` files take the type of
+    // the unit their `.d` directory extends, or of the whole type for
+    // type-wide directories such as `service.d`.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let name = path.file_name()?.to_str()?;
+        if name.ends_with(".conf") {
+            let dir = path.parent()?.file_name()?.to_str()?;
+            let unit = dir.strip_suffix(".d")?;
+            return Self::from_name(unit).or_else(|| Self::from_suffix(unit));
+        }
+        Self::from_name(name)
+    }
+
+    pub fn from_uri(uri: &Url) -> Option<Self> {
+        Self::from_path(Path::new(uri.path()))
+    }
+
+    // Sections a unit of this type may contain
+    pub fn sections(&self) -> impl Iterator<Item = &'static SectionSchema> {
+        let own = self.section();
+        SECTIONS.iter().filter(move |section| {
+            matches!(section.name, "Unit" | "Install") || Some(section.name) == own
+        })
+    }
+
+    // Name of the type-specific section, if the type has one
+    pub fn section(&self) -> Option<&'static str> {
+        match self {
+            UnitType::Service => Some("Service"),
+            UnitType::Socket => Some("Socket"),
+            UnitType::Mount => Some("Mount"),
+            UnitType::Automount => Some("Automount"),
+            UnitType::Swap => Some("Swap"),
+            UnitType::Path => Some("Path"),
+            UnitType::Timer => Some("Timer"),
+            UnitType::Slice => Some("Slice"),
+            UnitType::Scope => Some("Scope"),
+            UnitType::Device | UnitType::Target => None,
+        }
+    }
+}
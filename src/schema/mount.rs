@@ -0,0 +1,43 @@
+// [Mount], [Automount] and [Swap] directives, systemd.mount(5),
+// systemd.automount(5) and systemd.swap(5)
+
+use super::{Directive as D, DirectiveTable, ValueType as T};
+
+#[rustfmt::skip]
+pub static MOUNT: DirectiveTable = DirectiveTable {
+    man: "systemd.mount(5)",
+    directives: &[
+        D::new("What", T::String, "The absolute path of a device node, file or other resource to mount."),
+        D::new("Where", T::Path, "The absolute path of the mount point; must match the unit name."),
+        D::new("Type", T::String, "The file system type, as passed to mount(8) with -t."),
+        D::new("Options", T::String, "Comma-separated mount options, as passed to mount(8) with -o."),
+        D::new("SloppyOptions", T::Boolean, "If true, unknown mount options are tolerated."),
+        D::new("LazyUnmount", T::Boolean, "If true, the file system is detached lazily on unmount.").default_value("no"),
+        D::new("ReadWriteOnly", T::Boolean, "If true, mounting fails instead of falling back to read-only when read-write is not possible.").since(246),
+        D::new("ForceUnmount", T::Boolean, "If true, unmounting is forced, which is useful for unreachable NFS file systems.").default_value("no"),
+        D::new("DirectoryMode", T::Mode, "The access mode of automatically created mount point directories, 0755 by default.").default_value("0755"),
+        D::new("TimeoutSec", T::Timespan, "The time to wait for the mount command to finish before it is killed.").default_value("90s"),
+    ],
+};
+
+#[rustfmt::skip]
+pub static AUTOMOUNT: DirectiveTable = DirectiveTable {
+    man: "systemd.automount(5)",
+    directives: &[
+        D::new("Where", T::Path, "The absolute path of the automount point; must match the unit name."),
+        D::new("ExtraOptions", T::String, "Extra mount options for the autofs mount point.").since(250),
+        D::new("DirectoryMode", T::Mode, "The access mode of automatically created automount point directories, 0755 by default.").default_value("0755"),
+        D::new("TimeoutIdleSec", T::Timespan, "The idle time after which systemd attempts to unmount the file system.").default_value("0"),
+    ],
+};
+
+#[rustfmt::skip]
+pub static SWAP: DirectiveTable = DirectiveTable {
+    man: "systemd.swap(5)",
+    directives: &[
+        D::new("What", T::Path, "The path of the device node or file to use for paging."),
+        D::new("Priority", T::Integer, "The swap priority, as passed to swapon(8) with -p."),
+        D::new("Options", T::String, "Comma-separated swap options, as passed to swapon(8) with -o."),
+        D::new("TimeoutSec", T::Timespan, "The time to wait for the swapon command to finish before it is killed.").default_value("90s"),
+    ],
+};
@@ -0,0 +1,85 @@
+// Resource control settings shared by [Service], [Socket], [Mount], [Swap],
+// [Slice] and [Scope], systemd.resource-control(5)
+
+use super::{Directive as D, DirectiveTable, EnumValue as V, ValueType as T};
+
+static DEVICE_POLICIES: &[V] = &[V::new("auto"), V::new("closed"), V::new("strict")];
+
+static MANAGED_OOM_MODES: &[V] = &[V::new("auto"), V::new("kill")];
+
+static MANAGED_OOM_PREFERENCES: &[V] = &[V::new("none"), V::new("avoid"), V::new("omit")];
+
+static MEMORY_PRESSURE_WATCH: &[V] = &[V::new("auto"), V::new("on"), V::new("off"), V::new("skip")];
+
+#[rustfmt::skip]
+pub static CGROUP: DirectiveTable = DirectiveTable {
+    man: "systemd.resource-control(5)",
+    directives: &[
+        D::new("CPUAccounting", T::Boolean, "If true, turns on CPU usage accounting for this unit."),
+        D::new("CPUWeight", T::Weight, "The CPU time weight of the processes in this unit, between 1 and 10000, or idle.").default_value("100"),
+        D::new("StartupCPUWeight", T::Weight, "Like CPUWeight=, but applies during system startup and shutdown only."),
+        D::new("CPUQuota", T::Percent, "The maximum CPU time the processes may receive, as a percentage of one CPU; may exceed 100%."),
+        D::new("CPUQuotaPeriodSec", T::Timespan, "The period over which CPUQuota= is measured.").since(242),
+        D::new("AllowedCPUs", T::String, "Restricts the processes to the given CPU indices or ranges.").since(244),
+        D::new("StartupAllowedCPUs", T::String, "Like AllowedCPUs=, but applies during system startup and shutdown only.").since(244),
+        D::new("MemoryAccounting", T::Boolean, "If true, turns on memory usage accounting for this unit.").default_value("yes"),
+        D::new("MemoryMin", T::Size, "Memory usage protection: memory below this amount is never reclaimed.").since(240),
+        D::new("MemoryLow", T::Size, "Best-effort memory usage protection: memory below this amount is reclaimed only if no unprotected memory is available."),
+        D::new("StartupMemoryLow", T::Size, "Like MemoryLow=, but applies during system startup and shutdown only.").since(252),
+        D::new("DefaultStartupMemoryLow", T::Size, "The default StartupMemoryLow= of the unit's children.").since(254),
+        D::new("MemoryHigh", T::Size, "Throttling limit on memory usage: processes are slowed down and memory reclaimed aggressively above it."),
+        D::new("StartupMemoryHigh", T::Size, "Like MemoryHigh=, but applies during system startup and shutdown only.").since(256),
+        D::new("MemoryMax", T::Size, "Absolute limit on memory usage: the OOM killer is invoked above it."),
+        D::new("StartupMemoryMax", T::Size, "Like MemoryMax=, but applies during system startup and shutdown only.").since(256),
+        D::new("MemorySwapMax", T::Size, "Absolute limit on swap usage.").since(232),
+        D::new("StartupMemorySwapMax", T::Size, "Like MemorySwapMax=, but applies during system startup and shutdown only.").since(256),
+        D::new("MemoryZSwapMax", T::Size, "Absolute limit on zswap usage.").since(253),
+        D::new("StartupMemoryZSwapMax", T::Size, "Like MemoryZSwapMax=, but applies during system startup and shutdown only.").since(256),
+        D::new("MemoryZSwapWriteback", T::Boolean, "If false, disables writing pages from zswap back to the swap device.").since(256),
+        D::new("AllowedMemoryNodes", T::String, "Restricts the processes to the given NUMA memory nodes.").since(244),
+        D::new("StartupAllowedMemoryNodes", T::String, "Like AllowedMemoryNodes=, but applies during system startup and shutdown only.").since(244),
+        D::new("TasksAccounting", T::Boolean, "If true, turns on task accounting for this unit."),
+        D::new("TasksMax", T::Tasks, "The maximum number of tasks created in the unit, as an absolute number, a percentage of the system limit, or infinity.").default_value("15%"),
+        D::new("IOAccounting", T::Boolean, "If true, turns on block I/O accounting for this unit."),
+        D::new("IOWeight", T::Weight, "The default block I/O weight of the processes, between 1 and 10000.").default_value("100"),
+        D::new("StartupIOWeight", T::Weight, "Like IOWeight=, but applies during system startup and shutdown only."),
+        D::new("IODeviceWeight", T::String, "The block I/O weight for a specific device, as DEVICE WEIGHT.").list(),
+        D::new("IOReadBandwidthMax", T::String, "Per-device read bandwidth limit, as DEVICE BYTES.").list(),
+        D::new("IOWriteBandwidthMax", T::String, "Per-device write bandwidth limit, as DEVICE BYTES.").list(),
+        D::new("IOReadIOPSMax", T::String, "Per-device read I/O operations per second limit, as DEVICE IOPS.").list(),
+        D::new("IOWriteIOPSMax", T::String, "Per-device write I/O operations per second limit, as DEVICE IOPS.").list(),
+        D::new("IODeviceLatencyTargetSec", T::String, "Per-device I/O latency target, as DEVICE TIMESPAN.").list().since(240),
+        D::new("IPAccounting", T::Boolean, "If true, turns on IPv4 and IPv6 network traffic accounting for this unit.").since(235),
+        D::new("IPAddressAllow", T::String, "IP address prefixes the unit's sockets may communicate with.").list().since(235),
+        D::new("IPAddressDeny", T::String, "IP address prefixes the unit's sockets may not communicate with.").list().since(235),
+        D::new("SocketBindAllow", T::String, "Address families and ports the unit's sockets may bind to.").list().since(249),
+        D::new("SocketBindDeny", T::String, "Address families and ports the unit's sockets may not bind to.").list().since(249),
+        D::new("RestrictNetworkInterfaces", T::String, "Network interfaces the unit's sockets may use.").list().since(250),
+        D::new("NFTSet", T::String, "Adds the unit's cgroup or user to the given nftables sets.").list().since(255),
+        D::new("IPIngressFilterPath", T::Path, "BPF programs applied to ingress network traffic of the unit.").list().since(243),
+        D::new("IPEgressFilterPath", T::Path, "BPF programs applied to egress network traffic of the unit.").list().since(243),
+        D::new("BPFProgram", T::String, "BPF programs attached to the unit's cgroup, as TYPE:PATH.").list().since(249),
+        D::new("DeviceAllow", T::String, "Devices the processes may access, with r, w and m access flags.").list(),
+        D::new("DevicePolicy", T::Enum(DEVICE_POLICIES), "Controls the policy for allowing device access."),
+        D::new("Slice", T::Units, "The slice unit this unit is placed in."),
+        D::new("Delegate", T::String, "If true, delegates further cgroup control to the unit's processes; or a list of controllers to delegate."),
+        D::new("DelegateSubgroup", T::String, "The subgroup the unit's processes are placed in when Delegate= is enabled.").since(254),
+        D::new("DisableControllers", T::String, "Controllers disabled for the unit's children.").list().since(240),
+        D::new("ManagedOOMSwap", T::Enum(MANAGED_OOM_MODES), "How systemd-oomd acts on this unit when swap usage is high.").since(247),
+        D::new("ManagedOOMMemoryPressure", T::Enum(MANAGED_OOM_MODES), "How systemd-oomd acts on this unit when memory pressure is high.").since(247),
+        D::new("ManagedOOMMemoryPressureLimit", T::Percent, "The memory pressure limit above which systemd-oomd acts on this unit.").since(247),
+        D::new("ManagedOOMPreference", T::Enum(MANAGED_OOM_PREFERENCES), "Whether systemd-oomd should avoid or omit this unit when picking a candidate to kill.").since(248),
+        D::new("MemoryPressureWatch", T::Enum(MEMORY_PRESSURE_WATCH), "Controls memory pressure monitoring for the unit's processes.").since(254),
+        D::new("MemoryPressureThresholdSec", T::Timespan, "The memory pressure stall threshold used by MemoryPressureWatch=.").since(254),
+        D::new("CoredumpReceive", T::Boolean, "If true, core dumps of processes in this unit are forwarded to the unit's container.").since(255),
+        D::new("CPUShares", T::Integer, "Deprecated: use CPUWeight= instead.").deprecated(),
+        D::new("StartupCPUShares", T::Integer, "Deprecated: use StartupCPUWeight= instead.").deprecated(),
+        D::new("MemoryLimit", T::Size, "Deprecated: use MemoryMax= instead.").deprecated(),
+        D::new("BlockIOAccounting", T::Boolean, "Deprecated: use IOAccounting= instead.").deprecated(),
+        D::new("BlockIOWeight", T::Integer, "Deprecated: use IOWeight= instead.").deprecated(),
+        D::new("StartupBlockIOWeight", T::Integer, "Deprecated: use StartupIOWeight= instead.").deprecated(),
+        D::new("BlockIODeviceWeight", T::String, "Deprecated: use IODeviceWeight= instead.").list().deprecated(),
+        D::new("BlockIOReadBandwidth", T::String, "Deprecated: use IOReadBandwidthMax= instead.").list().deprecated(),
+        D::new("BlockIOWriteBandwidth", T::String, "Deprecated: use IOWriteBandwidthMax= instead.").list().deprecated(),
+    ],
+};
@@ -0,0 +1,109 @@
+// [Service] and [Scope] directives, systemd.service(5) and systemd.scope(5)
+
+use super::unit::EMERGENCY_ACTIONS;
+use super::{Directive as D, DirectiveTable, EnumValue as V, ValueType as T};
+
+static SERVICE_TYPES: &[V] = &[
+    V::new("simple").doc("The service is considered started as soon as the main process is forked off."),
+    V::new("exec").since(240).doc("Like simple, but the service is only considered started once the main binary has been executed."),
+    V::new("forking").doc("The started process forks and the parent exits; the child becomes the main process."),
+    V::new("oneshot").doc("The service is considered started once the main process exits. Allows several ExecStart= lines."),
+    V::new("dbus").doc("The service is considered started once it acquires the name given in BusName=."),
+    V::new("notify").doc("The service sends a READY=1 notification through sd_notify() once it has started."),
+    V::new("notify-reload").since(253).doc("Like notify, and the service also reloads on SIGHUP, reporting RELOADING=1 and READY=1."),
+    V::new("idle").doc("Like simple, but the program is only run once all active jobs are dispatched."),
+];
+
+static EXIT_TYPES: &[V] = &[V::new("main"), V::new("cgroup")];
+
+static RESTART_POLICIES: &[V] = &[
+    V::new("no").doc("Never restart the service."),
+    V::new("on-success").doc("Restart only when the service exits cleanly."),
+    V::new("on-failure").doc("Restart when the service exits uncleanly, is killed by a signal, times out or trips the watchdog."),
+    V::new("on-abnormal").doc("Restart when the service is killed by a signal, times out or trips the watchdog."),
+    V::new("on-watchdog").doc("Restart only when the watchdog timeout expires."),
+    V::new("on-abort").doc("Restart only when the service is killed by an uncaught signal."),
+    V::new("always").doc("Always restart the service, however it exited."),
+];
+
+static RESTART_MODES: &[V] = &[V::new("normal"), V::new("direct")];
+
+static TIMEOUT_FAILURE_MODES: &[V] = &[V::new("terminate"), V::new("abort"), V::new("kill")];
+
+static NOTIFY_ACCESS: &[V] = &[
+    V::new("none").doc("Ignore all status notifications."),
+    V::new("main").doc("Accept notifications from the main process only."),
+    V::new("exec").since(238).doc(
+        "Accept notifications from the main process and the processes of the Exec*= commands.",
+    ),
+    V::new("all").doc("Accept notifications from every process of the service's control group."),
+];
+
+static FD_STORE_PRESERVE: &[V] = &[V::new("no"), V::new("yes"), V::new("restart")];
+
+static OOM_POLICIES: &[V] = &[V::new("continue"), V::new("stop"), V::new("kill")];
+
+#[rustfmt::skip]
+pub static SERVICE: DirectiveTable = DirectiveTable {
+    man: "systemd.service(5)",
+    directives: &[
+        D::new("Type", T::Enum(SERVICE_TYPES), "Configures the mechanism via which the service notifies the manager that it finished starting up.").default_value("simple"),
+        D::new("ExitType", T::Enum(EXIT_TYPES), "Whether the service is considered finished when the main process exits, or when the last process of its cgroup exits.").since(250).default_value("main"),
+        D::new("RemainAfterExit", T::Boolean, "If true, the service is considered active even when all its processes exited.").default_value("no"),
+        D::new("GuessMainPID", T::Boolean, "If true, systemd guesses the main PID of a forking service when it cannot be determined reliably.").default_value("yes"),
+        D::new("PIDFile", T::Path, "The path to the PID file of a forking service."),
+        D::new("BusName", T::String, "The D-Bus bus name this service is reachable as; required for Type=dbus."),
+        D::new("ExecStart", T::Command, "The command executed when this service is started.").list(),
+        D::new("ExecStartPre", T::Command, "Commands executed before ExecStart=.").list(),
+        D::new("ExecStartPost", T::Command, "Commands executed after ExecStart= has finished starting up.").list(),
+        D::new("ExecCondition", T::Command, "Commands executed before ExecStartPre=; an exit code between 1 and 254 skips the service without marking it failed.").list().since(243),
+        D::new("ExecReload", T::Command, "Commands executed to trigger a configuration reload in the service.").list(),
+        D::new("ExecStop", T::Command, "Commands executed to stop the service.").list(),
+        D::new("ExecStopPost", T::Command, "Commands executed after the service is stopped, including when it failed.").list(),
+        D::new("RestartSec", T::Timespan, "The time to sleep before restarting the service, 100ms by default.").default_value("100ms"),
+        D::new("RestartSteps", T::Integer, "The number of steps taken to increase the restart interval from RestartSec= to RestartMaxDelaySec=.").since(254),
+        D::new("RestartMaxDelaySec", T::Timespan, "The longest time to sleep before restarting the service when RestartSteps= is set.").since(254),
+        D::new("TimeoutStartSec", T::Timespan, "The time to wait for start-up before the service is considered failed.").default_value("90s"),
+        D::new("TimeoutStopSec", T::Timespan, "The time to wait for each ExecStop= command and for the service to stop before it is killed.").default_value("90s"),
+        D::new("TimeoutAbortSec", T::Timespan, "The time to wait for the service to stop after a watchdog abort.").since(243),
+        D::new("TimeoutSec", T::Timespan, "Shorthand for configuring both TimeoutStartSec= and TimeoutStopSec=."),
+        D::new("TimeoutStartFailureMode", T::Enum(TIMEOUT_FAILURE_MODES), "The action taken when the start timeout is hit.").since(246).default_value("terminate"),
+        D::new("TimeoutStopFailureMode", T::Enum(TIMEOUT_FAILURE_MODES), "The action taken when the stop timeout is hit.").since(246).default_value("terminate"),
+        D::new("RuntimeMaxSec", T::Timespan, "The maximum time the service may run before it is terminated.").since(229),
+        D::new("RuntimeRandomizedExtraSec", T::Timespan, "Extends RuntimeMaxSec= by a random amount up to this value.").since(250),
+        D::new("WatchdogSec", T::Timespan, "The watchdog timeout; the service must send WATCHDOG=1 keep-alive pings within it."),
+        D::new("Restart", T::Enum(RESTART_POLICIES), "Whether the service is restarted when its process exits, is killed, or a timeout is reached.").default_value("no"),
+        D::new("RestartMode", T::Enum(RESTART_MODES), "Whether restarting goes through the failed or inactive state, or directly to activating.").since(254).default_value("normal"),
+        D::new("SuccessExitStatus", T::String, "Additional exit statuses and signals considered a successful termination.").list(),
+        D::new("RestartPreventExitStatus", T::String, "Exit statuses and signals that prevent an automatic restart.").list(),
+        D::new("RestartForceExitStatus", T::String, "Exit statuses and signals that force an automatic restart regardless of Restart=.").list(),
+        D::new("RootDirectoryStartOnly", T::Boolean, "If true, RootDirectory= applies only to ExecStart= and not to the other Exec*= commands.").default_value("no"),
+        D::new("NonBlocking", T::Boolean, "If true, sets O_NONBLOCK on all file descriptors passed via socket activation.").default_value("no"),
+        D::new("NotifyAccess", T::Enum(NOTIFY_ACCESS), "Controls which processes may send status notifications via sd_notify(3).").default_value("none"),
+        D::new("Sockets", T::Units, "Socket units this service inherits socket file descriptors from.").list(),
+        D::new("FileDescriptorStoreMax", T::Integer, "The number of file descriptors that may be stored in the service manager for this service.").default_value("0"),
+        D::new("FileDescriptorStorePreserve", T::Enum(FD_STORE_PRESERVE), "Whether the file descriptor store is kept when the service is stopped.").since(254).default_value("no"),
+        D::new("USBFunctionDescriptors", T::Path, "The USB FunctionFS descriptors file, used with ListenUSBFunction=."),
+        D::new("USBFunctionStrings", T::Path, "The USB FunctionFS strings file, used with ListenUSBFunction=."),
+        D::new("OOMPolicy", T::Enum(OOM_POLICIES), "The action taken when the kernel OOM killer terminates a process of the service.").since(243).default_value("stop"),
+        D::new("OpenFile", T::String, "Files or sockets opened by the service manager and passed to the service, as PATH[:FD-NAME[:OPTIONS]].").list().since(253),
+        D::new("ReloadSignal", T::Signal, "The signal sent to the main process on reload when Type=notify-reload, SIGHUP by default.").since(253).default_value("SIGHUP"),
+        D::new("PermissionsStartOnly", T::Boolean, "Deprecated: use the `+`, `!` or `!!` ExecStart= prefixes instead.").deprecated(),
+        D::new("StartLimitInterval", T::Timespan, "Deprecated: use StartLimitIntervalSec= in [Unit] instead.").deprecated(),
+        D::new("StartLimitBurst", T::Integer, "Deprecated here: use StartLimitBurst= in [Unit] instead.").deprecated(),
+        D::new("StartLimitAction", T::Enum(EMERGENCY_ACTIONS), "Deprecated here: use StartLimitAction= in [Unit] instead.").deprecated(),
+        D::new("FailureAction", T::Enum(EMERGENCY_ACTIONS), "Deprecated here: use FailureAction= in [Unit] instead.").deprecated(),
+        D::new("RebootArgument", T::String, "Deprecated here: use RebootArgument= in [Unit] instead.").deprecated(),
+    ],
+};
+
+#[rustfmt::skip]
+pub static SCOPE: DirectiveTable = DirectiveTable {
+    man: "systemd.scope(5)",
+    directives: &[
+        D::new("OOMPolicy", T::Enum(OOM_POLICIES), "The action taken when the kernel OOM killer terminates a process of the scope.").since(243).default_value("stop"),
+        D::new("RuntimeMaxSec", T::Timespan, "The maximum time the scope may run before it is terminated."),
+        D::new("RuntimeRandomizedExtraSec", T::Timespan, "Extends RuntimeMaxSec= by a random amount up to this value.").since(250),
+        D::new("TimeoutStopSec", T::Timespan, "The time to wait for the scope to stop before it is killed.").default_value("90s"),
+    ],
+};
@@ -0,0 +1,83 @@
+// [Socket] directives, systemd.socket(5)
+
+use super::{Directive as D, DirectiveTable, EnumValue as V, ValueType as T};
+
+static SOCKET_PROTOCOLS: &[V] = &[
+    V::new("udplite"),
+    V::new("sctp"),
+    V::new("mptcp").since(253),
+];
+
+static BIND_IPV6_ONLY: &[V] = &[V::new("default"), V::new("both"), V::new("ipv6-only")];
+
+static TIMESTAMPING: &[V] = &[V::new("off"), V::new("us"), V::new("ns")];
+
+#[rustfmt::skip]
+pub static SOCKET: DirectiveTable = DirectiveTable {
+    man: "systemd.socket(5)",
+    directives: &[
+        D::new("ListenStream", T::String, "Listens on a SOCK_STREAM socket: a port, an IP address with port, or a UNIX socket path.").list(),
+        D::new("ListenDatagram", T::String, "Listens on a SOCK_DGRAM socket: a port, an IP address with port, or a UNIX socket path.").list(),
+        D::new("ListenSequentialPacket", T::String, "Listens on a SOCK_SEQPACKET UNIX socket.").list(),
+        D::new("ListenFIFO", T::Path, "Listens on a FIFO in the file system.").list(),
+        D::new("ListenSpecial", T::Path, "Listens on a special file such as a character device or a /proc file.").list(),
+        D::new("ListenNetlink", T::String, "Listens on a netlink family socket, as FAMILY [GROUP].").list(),
+        D::new("ListenMessageQueue", T::String, "Listens on a POSIX message queue.").list(),
+        D::new("ListenUSBFunction", T::Path, "Listens on a USB FunctionFS endpoint directory.").list(),
+        D::new("SocketProtocol", T::Enum(SOCKET_PROTOCOLS), "The IP protocol used instead of the default for the socket type."),
+        D::new("BindIPv6Only", T::Enum(BIND_IPV6_ONLY), "Controls the IPV6_V6ONLY socket option."),
+        D::new("Backlog", T::Integer, "The number of connections to queue that have not been accepted yet."),
+        D::new("BindToDevice", T::String, "The network interface the socket is bound to."),
+        D::new("SocketUser", T::String, "The UNIX user that owns the AF_UNIX socket file or FIFO."),
+        D::new("SocketGroup", T::String, "The UNIX group that owns the AF_UNIX socket file or FIFO."),
+        D::new("SocketMode", T::Mode, "The access mode of the socket file or FIFO, 0666 by default.").default_value("0666"),
+        D::new("DirectoryMode", T::Mode, "The access mode of parent directories created for the socket file or FIFO, 0755 by default.").default_value("0755"),
+        D::new("Accept", T::Boolean, "If true, a service instance is spawned for each incoming connection; otherwise the listening socket itself is passed.").default_value("no"),
+        D::new("Writable", T::Boolean, "If true, the special file or FIFO is opened read-write instead of read-only."),
+        D::new("FlushPending", T::Boolean, "If true, pending socket data is flushed when the triggered service exits.").since(247),
+        D::new("MaxConnections", T::Integer, "The maximum number of connections to run service instances for when Accept=yes.").default_value("64"),
+        D::new("MaxConnectionsPerSource", T::Integer, "The maximum number of connections per source IP address when Accept=yes.").since(232),
+        D::new("KeepAlive", T::Boolean, "If true, enables TCP keep-alive on the socket."),
+        D::new("KeepAliveTimeSec", T::Timespan, "The idle time before TCP starts sending keep-alive probes."),
+        D::new("KeepAliveIntervalSec", T::Timespan, "The interval between TCP keep-alive probes."),
+        D::new("KeepAliveProbes", T::Integer, "The number of unacknowledged keep-alive probes before the connection is dropped."),
+        D::new("NoDelay", T::Boolean, "If true, disables the Nagle algorithm on the socket."),
+        D::new("Priority", T::Integer, "The priority for all traffic sent from this socket."),
+        D::new("DeferAcceptSec", T::Timespan, "Defers accepting a connection until data arrives, up to this time."),
+        D::new("ReceiveBuffer", T::Size, "The receive buffer size of the socket."),
+        D::new("SendBuffer", T::Size, "The send buffer size of the socket."),
+        D::new("IPTOS", T::String, "The IP type-of-service field for packets sent from this socket."),
+        D::new("IPTTL", T::Integer, "The IP time-to-live field for packets sent from this socket."),
+        D::new("Mark", T::Integer, "The firewall mark of packets sent from this socket."),
+        D::new("ReusePort", T::Boolean, "If true, allows multiple sockets to bind to the same port."),
+        D::new("SmackLabel", T::String, "The SMACK64 extended attribute of the socket file or FIFO."),
+        D::new("SmackLabelIPIn", T::String, "The SMACK64IPIN extended attribute of the socket."),
+        D::new("SmackLabelIPOut", T::String, "The SMACK64IPOUT extended attribute of the socket."),
+        D::new("SELinuxContextFromNet", T::Boolean, "If true, the service is started with the SELinux label derived from the incoming connection."),
+        D::new("PipeSize", T::Size, "The pipe buffer size of FIFOs created by this unit."),
+        D::new("MessageQueueMaxMessages", T::Integer, "The mq_maxmsg attribute of POSIX message queues created by this unit."),
+        D::new("MessageQueueMessageSize", T::Integer, "The mq_msgsize attribute of POSIX message queues created by this unit."),
+        D::new("FreeBind", T::Boolean, "If true, allows binding to an IP address that is not configured on a local interface.").default_value("no"),
+        D::new("Transparent", T::Boolean, "If true, enables the IP_TRANSPARENT socket option.").default_value("no"),
+        D::new("Broadcast", T::Boolean, "If true, allows sending broadcast datagrams from this socket.").default_value("no"),
+        D::new("PassCredentials", T::Boolean, "If true, enables SCM_CREDENTIALS on AF_UNIX sockets."),
+        D::new("PassSecurity", T::Boolean, "If true, enables SCM_SECURITY on AF_UNIX sockets."),
+        D::new("PassPacketInfo", T::Boolean, "If true, enables IP_PKTINFO and similar options for datagram sockets.").since(247),
+        D::new("Timestamping", T::Enum(TIMESTAMPING), "Controls the SO_TIMESTAMP or SO_TIMESTAMPNS socket options.").since(247),
+        D::new("TCPCongestion", T::String, "The TCP congestion algorithm used by this socket."),
+        D::new("ExecStartPre", T::Command, "Commands executed before the listening sockets or FIFOs are created.").list(),
+        D::new("ExecStartPost", T::Command, "Commands executed after the listening sockets or FIFOs are created.").list(),
+        D::new("ExecStopPre", T::Command, "Commands executed before the listening sockets or FIFOs are closed.").list(),
+        D::new("ExecStopPost", T::Command, "Commands executed after the listening sockets or FIFOs are closed.").list(),
+        D::new("TimeoutSec", T::Timespan, "The time to wait for the Exec*= commands to finish before they are killed."),
+        D::new("Service", T::Units, "The service unit activated on incoming traffic, by default the one with the same name."),
+        D::new("RemoveOnStop", T::Boolean, "If true, socket files, FIFOs and symlinks created by this unit are removed when it stops."),
+        D::new("Symlinks", T::Path, "Symlinks created in the file system pointing to the AF_UNIX socket or FIFO.").list(),
+        D::new("FileDescriptorName", T::String, "The name of the file descriptors passed to the service, as returned by sd_listen_fds_with_names(3)."),
+        D::new("TriggerLimitIntervalSec", T::Timespan, "The interval used for rate limiting activation of this socket."),
+        D::new("TriggerLimitBurst", T::Integer, "How many activations are allowed within TriggerLimitIntervalSec=."),
+        D::new("PollLimitIntervalSec", T::Timespan, "The interval used for rate limiting polling of this socket.").since(255),
+        D::new("PollLimitBurst", T::Integer, "How many polling events are allowed within PollLimitIntervalSec=.").since(255),
+        D::new("PassFileDescriptorsToExec", T::Boolean, "If true, the listening file descriptors are also passed to the ExecStartPost= and similar commands.").since(256),
+    ],
+};
@@ -0,0 +1,42 @@
+// [Timer] and [Path] directives, systemd.timer(5) and systemd.path(5)
+
+use super::{Directive as D, DirectiveTable, ValueType as T};
+
+#[rustfmt::skip]
+pub static TIMER: DirectiveTable = DirectiveTable {
+    man: "systemd.timer(5)",
+    directives: &[
+        D::new("OnActiveSec", T::Timespan, "Defines a timer relative to the moment the timer unit itself is activated.").list(),
+        D::new("OnBootSec", T::Timespan, "Defines a timer relative to when the machine was booted up.").list(),
+        D::new("OnStartupSec", T::Timespan, "Defines a timer relative to when the service manager was first started.").list(),
+        D::new("OnUnitActiveSec", T::Timespan, "Defines a timer relative to when the unit the timer activates was last activated.").list(),
+        D::new("OnUnitInactiveSec", T::Timespan, "Defines a timer relative to when the unit the timer activates was last deactivated.").list(),
+        D::new("OnCalendar", T::Calendar, "Defines a real-time timer with a calendar event expression, as in `*-*-* 02:00:00`.").list(),
+        D::new("AccuracySec", T::Timespan, "The accuracy the timer elapses with, 1min by default.").default_value("1min"),
+        D::new("RandomizedDelaySec", T::Timespan, "Delays the timer by a randomly selected time between 0 and this value.").default_value("0"),
+        D::new("FixedRandomDelay", T::Boolean, "If true, the randomized delay stays the same across reboots for the same machine and unit.").since(247),
+        D::new("OnClockChange", T::Boolean, "If true, the service unit is triggered when the system clock jumps.").since(242).default_value("no"),
+        D::new("OnTimezoneChange", T::Boolean, "If true, the service unit is triggered when the local time zone changes.").since(242),
+        D::new("Unit", T::Units, "The unit to activate when the timer elapses, by default the service with the same name."),
+        D::new("Persistent", T::Boolean, "If true, the time the service was last triggered is stored on disk and a missed run is caught up on.").default_value("no"),
+        D::new("WakeSystem", T::Boolean, "If true, an elapsing timer resumes the system from suspend.").default_value("no"),
+        D::new("RemainAfterElapse", T::Boolean, "If true, the timer stays loaded after it elapsed and cannot be triggered again.").default_value("yes"),
+    ],
+};
+
+#[rustfmt::skip]
+pub static PATH: DirectiveTable = DirectiveTable {
+    man: "systemd.path(5)",
+    directives: &[
+        D::new("PathExists", T::Path, "Activates the unit when the file exists.").list(),
+        D::new("PathExistsGlob", T::Path, "Activates the unit when a file matching the glob exists.").list(),
+        D::new("PathChanged", T::Path, "Activates the unit when the file is closed after being written, or renamed.").list(),
+        D::new("PathModified", T::Path, "Like PathChanged=, but also activates on every write to the file.").list(),
+        D::new("DirectoryNotEmpty", T::Path, "Activates the unit when the directory contains at least one file.").list(),
+        D::new("Unit", T::Units, "The unit to activate when a watched path matches, by default the service with the same name."),
+        D::new("MakeDirectory", T::Boolean, "If true, the watched directories are created before watching.").default_value("no"),
+        D::new("DirectoryMode", T::Mode, "The access mode of directories created by MakeDirectory=, 0755 by default.").default_value("0755"),
+        D::new("TriggerLimitIntervalSec", T::Timespan, "The interval used for rate limiting activation by this path unit.").since(250),
+        D::new("TriggerLimitBurst", T::Integer, "How many activations are allowed within TriggerLimitIntervalSec=.").since(250),
+    ],
+};
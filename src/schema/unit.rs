@@ -0,0 +1,163 @@
+// [Unit] and [Install] directives, systemd.unit(5)
+
+use super::{Directive as D, DirectiveTable, EnumValue as V, ValueType as T};
+
+pub(super) static JOB_MODES: &[V] = &[
+    V::new("fail"),
+    V::new("replace"),
+    V::new("replace-irreversibly"),
+    V::new("isolate"),
+    V::new("flush"),
+    V::new("ignore-dependencies"),
+    V::new("ignore-requirements"),
+];
+
+pub(super) static EMERGENCY_ACTIONS: &[V] = &[
+    V::new("none"),
+    V::new("reboot"),
+    V::new("reboot-force"),
+    V::new("reboot-immediate"),
+    V::new("poweroff"),
+    V::new("poweroff-force"),
+    V::new("poweroff-immediate"),
+    V::new("exit").since(236),
+    V::new("exit-force").since(236),
+    V::new("soft-reboot").since(254),
+    V::new("soft-reboot-force").since(254),
+    V::new("kexec").since(250),
+    V::new("kexec-force").since(250),
+    V::new("halt").since(250),
+    V::new("halt-force").since(250),
+    V::new("halt-immediate").since(250),
+];
+
+static COLLECT_MODES: &[V] = &[V::new("inactive"), V::new("inactive-or-failed")];
+
+#[rustfmt::skip]
+pub static UNIT: DirectiveTable = DirectiveTable {
+    man: "systemd.unit(5)",
+    directives: &[
+        D::new("Description", T::String, "A short human readable title of the unit."),
+        D::new("Documentation", T::String, "A space-separated list of URIs referencing documentation for this unit or its configuration.").list(),
+        D::new("Wants", T::Units, "Weak requirement dependencies: the listed units are started along with this unit, but failing to start them does not affect this unit.").list(),
+        D::new("Requires", T::Units, "Strong requirement dependencies: if one of the listed units fails to activate and an ordering dependency is set, this unit is not started.").list(),
+        D::new("Requisite", T::Units, "Like Requires=, but the listed units are not started if they are not already active; this unit fails immediately instead.").list(),
+        D::new("BindsTo", T::Units, "Like Requires=, but additionally this unit is stopped when one of the listed units stops or disappears.").list(),
+        D::new("PartOf", T::Units, "Stop and restart dependencies: when one of the listed units is stopped or restarted, this unit is stopped or restarted too.").list(),
+        D::new("Upholds", T::Units, "Continuously restarts the listed units whenever they are not active while this unit is active.").list().since(249),
+        D::new("Conflicts", T::Units, "Negative requirement dependencies: starting this unit stops the listed units and vice versa.").list(),
+        D::new("Before", T::Units, "Ordering dependencies: this unit is started before the listed units and stopped after them.").list(),
+        D::new("After", T::Units, "Ordering dependencies: this unit is started after the listed units and stopped before them.").list(),
+        D::new("OnFailure", T::Units, "Units activated when this unit enters the failed state.").list(),
+        D::new("OnSuccess", T::Units, "Units activated when this unit enters the inactive state after succeeding.").list().since(249),
+        D::new("PropagatesReloadTo", T::Units, "Reload requests on this unit are propagated to the listed units.").list(),
+        D::new("ReloadPropagatedFrom", T::Units, "Reload requests on the listed units are propagated to this unit.").list(),
+        D::new("PropagatesStopTo", T::Units, "Stop requests on this unit are propagated to the listed units.").list().since(249),
+        D::new("StopPropagatedFrom", T::Units, "Stop requests on the listed units are propagated to this unit.").list().since(249),
+        D::new("JoinsNamespaceOf", T::Units, "Run the processes of this unit in the same temporary file system and network namespaces as the listed units.").list(),
+        D::new("RequiresMountsFor", T::Path, "Adds Requires= and After= dependencies on the mount units needed to access the listed absolute paths.").list(),
+        D::new("WantsMountsFor", T::Path, "Adds Wants= and After= dependencies on the mount units needed to access the listed absolute paths.").list().since(256),
+        D::new("OnSuccessJobMode", T::Enum(JOB_MODES), "The job mode used to enqueue the OnSuccess= units.").since(249),
+        D::new("OnFailureJobMode", T::Enum(JOB_MODES), "The job mode used to enqueue the OnFailure= units.").default_value("replace"),
+        D::new("IgnoreOnIsolate", T::Boolean, "If true, this unit is not stopped when isolating another unit.").default_value("no"),
+        D::new("StopWhenUnneeded", T::Boolean, "If true, this unit is stopped when it is no longer used by any other active unit.").default_value("no"),
+        D::new("RefuseManualStart", T::Boolean, "If true, this unit can only be activated indirectly, never explicitly by the user.").default_value("no"),
+        D::new("RefuseManualStop", T::Boolean, "If true, this unit can only be deactivated indirectly, never explicitly by the user."),
+        D::new("AllowIsolate", T::Boolean, "If true, this unit may be used with `systemctl isolate`."),
+        D::new("DefaultDependencies", T::Boolean, "If false, the implicit default dependencies for this unit type are not created.").default_value("yes"),
+        D::new("SurviveFinalKillSignal", T::Boolean, "If true, processes of this unit are not killed by the final kill signal during shutdown or soft-reboot.").since(255),
+        D::new("CollectMode", T::Enum(COLLECT_MODES), "Tweaks the garbage collection algorithm for this unit.").since(236).default_value("inactive"),
+        D::new("FailureAction", T::Enum(EMERGENCY_ACTIONS), "The action to take when this unit stops in the failed state.").since(236),
+        D::new("SuccessAction", T::Enum(EMERGENCY_ACTIONS), "The action to take when this unit stops in the inactive state after succeeding.").since(236),
+        D::new("FailureActionExitStatus", T::Integer, "The exit status passed to the service manager when FailureAction= is exit or exit-force.").since(240),
+        D::new("SuccessActionExitStatus", T::Integer, "The exit status passed to the service manager when SuccessAction= is exit or exit-force.").since(240),
+        D::new("JobTimeoutSec", T::Timespan, "Time limit for a job of this unit to complete before it is cancelled.").default_value("infinity"),
+        D::new("JobRunningTimeoutSec", T::Timespan, "Time limit for a running job of this unit to complete before it is cancelled.").since(233),
+        D::new("JobTimeoutAction", T::Enum(EMERGENCY_ACTIONS), "The action to take when the job timeout is hit."),
+        D::new("JobTimeoutRebootArgument", T::String, "Optional reboot string passed to reboot(2) when JobTimeoutAction= reboots."),
+        D::new("StartLimitIntervalSec", T::Timespan, "The interval used for start rate limiting together with StartLimitBurst=.").since(230).default_value("10s"),
+        D::new("StartLimitBurst", T::Integer, "How many starts are allowed within StartLimitIntervalSec= before further starts are refused.").since(230).default_value("5"),
+        D::new("StartLimitAction", T::Enum(EMERGENCY_ACTIONS), "The action to take when the start rate limit is hit."),
+        D::new("RebootArgument", T::String, "Optional reboot string passed to reboot(2) when an emergency action reboots."),
+        D::new("SourcePath", T::Path, "The path to the configuration file this unit was generated from."),
+        D::new("ConditionArchitecture", T::String, "Check whether the system is running on a specific architecture.").list(),
+        D::new("ConditionFirmware", T::String, "Check whether the system's firmware is of a certain type.").list().since(249),
+        D::new("ConditionVirtualization", T::String, "Check whether the system is executed in a virtualized environment.").list(),
+        D::new("ConditionHost", T::String, "Check whether the system's hostname or machine ID matches.").list(),
+        D::new("ConditionKernelCommandLine", T::String, "Check whether a kernel command line option is set.").list(),
+        D::new("ConditionKernelVersion", T::String, "Check whether the kernel version matches an expression.").list().since(236),
+        D::new("ConditionCredential", T::String, "Check whether a credential was passed to the service manager.").list().since(252),
+        D::new("ConditionEnvironment", T::String, "Check whether an environment variable is set in the service manager's environment.").list().since(246),
+        D::new("ConditionSecurity", T::String, "Check whether a security technology such as selinux or apparmor is enabled.").list(),
+        D::new("ConditionCapability", T::String, "Check whether a capability exists in the service manager's bounding set.").list(),
+        D::new("ConditionACPower", T::Boolean, "Check whether the system is on AC power.").list(),
+        D::new("ConditionNeedsUpdate", T::Path, "Check whether /etc or /var needs an update after a /usr update.").list(),
+        D::new("ConditionFirstBoot", T::Boolean, "Check whether the system is booting for the first time.").list(),
+        D::new("ConditionPathExists", T::Path, "Check whether a file exists.").list(),
+        D::new("ConditionPathExistsGlob", T::Path, "Check whether a file matching a glob pattern exists.").list(),
+        D::new("ConditionPathIsDirectory", T::Path, "Check whether a path exists and is a directory.").list(),
+        D::new("ConditionPathIsSymbolicLink", T::Path, "Check whether a path exists and is a symbolic link.").list(),
+        D::new("ConditionPathIsMountPoint", T::Path, "Check whether a path exists and is a mount point.").list(),
+        D::new("ConditionPathIsReadWrite", T::Path, "Check whether a path exists and is on a writable file system.").list(),
+        D::new("ConditionPathIsEncrypted", T::Path, "Check whether a path exists and is on an encrypted block device.").list().since(246),
+        D::new("ConditionDirectoryNotEmpty", T::Path, "Check whether a path exists and is a non-empty directory.").list(),
+        D::new("ConditionFileNotEmpty", T::Path, "Check whether a path exists and is a non-empty regular file.").list(),
+        D::new("ConditionFileIsExecutable", T::Path, "Check whether a path exists and is an executable regular file.").list(),
+        D::new("ConditionUser", T::String, "Check whether the service manager runs as the given user.").list().since(237),
+        D::new("ConditionGroup", T::String, "Check whether the service manager runs as or is a member of the given group.").list().since(237),
+        D::new("ConditionControlGroupController", T::String, "Check whether the given cgroup controllers are available.").list().since(238),
+        D::new("ConditionMemory", T::String, "Check whether the system's physical memory matches a size comparison.").list().since(244),
+        D::new("ConditionCPUs", T::String, "Check whether the number of usable CPUs matches a comparison.").list().since(244),
+        D::new("ConditionCPUFeature", T::String, "Check whether the CPU supports the given feature.").list().since(248),
+        D::new("ConditionOSRelease", T::String, "Check whether an os-release field matches an expression.").list().since(249),
+        D::new("ConditionMemoryPressure", T::String, "Check whether memory pressure is below a threshold.").list().since(250),
+        D::new("ConditionCPUPressure", T::String, "Check whether CPU pressure is below a threshold.").list().since(250),
+        D::new("ConditionIOPressure", T::String, "Check whether IO pressure is below a threshold.").list().since(250),
+        D::new("AssertArchitecture", T::String, "Like ConditionArchitecture=, but fails the unit if the check fails.").list(),
+        D::new("AssertFirmware", T::String, "Like ConditionFirmware=, but fails the unit if the check fails.").list().since(249),
+        D::new("AssertVirtualization", T::String, "Like ConditionVirtualization=, but fails the unit if the check fails.").list(),
+        D::new("AssertHost", T::String, "Like ConditionHost=, but fails the unit if the check fails.").list(),
+        D::new("AssertKernelCommandLine", T::String, "Like ConditionKernelCommandLine=, but fails the unit if the check fails.").list(),
+        D::new("AssertKernelVersion", T::String, "Like ConditionKernelVersion=, but fails the unit if the check fails.").list().since(236),
+        D::new("AssertCredential", T::String, "Like ConditionCredential=, but fails the unit if the check fails.").list().since(252),
+        D::new("AssertEnvironment", T::String, "Like ConditionEnvironment=, but fails the unit if the check fails.").list().since(246),
+        D::new("AssertSecurity", T::String, "Like ConditionSecurity=, but fails the unit if the check fails.").list(),
+        D::new("AssertCapability", T::String, "Like ConditionCapability=, but fails the unit if the check fails.").list(),
+        D::new("AssertACPower", T::Boolean, "Like ConditionACPower=, but fails the unit if the check fails.").list(),
+        D::new("AssertNeedsUpdate", T::Path, "Like ConditionNeedsUpdate=, but fails the unit if the check fails.").list(),
+        D::new("AssertFirstBoot", T::Boolean, "Like ConditionFirstBoot=, but fails the unit if the check fails.").list(),
+        D::new("AssertPathExists", T::Path, "Like ConditionPathExists=, but fails the unit if the check fails.").list(),
+        D::new("AssertPathExistsGlob", T::Path, "Like ConditionPathExistsGlob=, but fails the unit if the check fails.").list(),
+        D::new("AssertPathIsDirectory", T::Path, "Like ConditionPathIsDirectory=, but fails the unit if the check fails.").list(),
+        D::new("AssertPathIsSymbolicLink", T::Path, "Like ConditionPathIsSymbolicLink=, but fails the unit if the check fails.").list(),
+        D::new("AssertPathIsMountPoint", T::Path, "Like ConditionPathIsMountPoint=, but fails the unit if the check fails.").list(),
+        D::new("AssertPathIsReadWrite", T::Path, "Like ConditionPathIsReadWrite=, but fails the unit if the check fails.").list(),
+        D::new("AssertPathIsEncrypted", T::Path, "Like ConditionPathIsEncrypted=, but fails the unit if the check fails.").list().since(246),
+        D::new("AssertDirectoryNotEmpty", T::Path, "Like ConditionDirectoryNotEmpty=, but fails the unit if the check fails.").list(),
+        D::new("AssertFileNotEmpty", T::Path, "Like ConditionFileNotEmpty=, but fails the unit if the check fails.").list(),
+        D::new("AssertFileIsExecutable", T::Path, "Like ConditionFileIsExecutable=, but fails the unit if the check fails.").list(),
+        D::new("AssertUser", T::String, "Like ConditionUser=, but fails the unit if the check fails.").list().since(237),
+        D::new("AssertGroup", T::String, "Like ConditionGroup=, but fails the unit if the check fails.").list().since(237),
+        D::new("AssertControlGroupController", T::String, "Like ConditionControlGroupController=, but fails the unit if the check fails.").list().since(238),
+        D::new("AssertMemory", T::String, "Like ConditionMemory=, but fails the unit if the check fails.").list().since(244),
+        D::new("AssertCPUs", T::String, "Like ConditionCPUs=, but fails the unit if the check fails.").list().since(244),
+        D::new("AssertCPUFeature", T::String, "Like ConditionCPUFeature=, but fails the unit if the check fails.").list().since(248),
+        D::new("AssertOSRelease", T::String, "Like ConditionOSRelease=, but fails the unit if the check fails.").list().since(249),
+        D::new("AssertMemoryPressure", T::String, "Like ConditionMemoryPressure=, but fails the unit if the check fails.").list().since(250),
+        D::new("AssertCPUPressure", T::String, "Like ConditionCPUPressure=, but fails the unit if the check fails.").list().since(250),
+        D::new("AssertIOPressure", T::String, "Like ConditionIOPressure=, but fails the unit if the check fails.").list().since(250),
+    ],
+};
+
+#[rustfmt::skip]
+pub static INSTALL: DirectiveTable = DirectiveTable {
+    man: "systemd.unit(5)",
+    directives: &[
+        D::new("Alias", T::Units, "Additional names this unit is installed under; `systemctl enable` creates symlinks for them.").list(),
+        D::new("WantedBy", T::Units, "Units that get a Wants= dependency on this unit when it is enabled, via a `.wants/` symlink.").list(),
+        D::new("RequiredBy", T::Units, "Units that get a Requires= dependency on this unit when it is enabled, via a `.requires/` symlink.").list(),
+        D::new("UpheldBy", T::Units, "Units that get an Upholds= dependency on this unit when it is enabled, via an `.upholds/` symlink.").list().since(249),
+        D::new("Also", T::Units, "Additional units to enable or disable together with this unit.").list(),
+        D::new("DefaultInstance", T::String, "For template units, the instance enabled when no instance is specified."),
+    ],
+};
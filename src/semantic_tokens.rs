@@ -0,0 +1,318 @@
+// Semantic tokens, so editors without a systemd grammar still highlight
+// unit files
+
+use tower_lsp::lsp_types::{
+    SemanticToken, SemanticTokenModifier, SemanticTokenType, SemanticTokensLegend,
+};
+
+use crate::document::Document;
+use crate::exec;
+use crate::parser::{Directive, Entry, Span};
+use crate::schema::{self, ValueType};
+use crate::units;
+
+// Token types, indexed by `Kind`
+const TOKEN_TYPES: &[SemanticTokenType] = &[
+    SemanticTokenType::NAMESPACE,
+    SemanticTokenType::PROPERTY,
+    SemanticTokenType::VARIABLE,
+    SemanticTokenType::KEYWORD,
+    SemanticTokenType::NUMBER,
+    SemanticTokenType::ENUM_MEMBER,
+    SemanticTokenType::TYPE,
+    SemanticTokenType::STRING,
+    SemanticTokenType::MACRO,
+    SemanticTokenType::PARAMETER,
+    SemanticTokenType::OPERATOR,
+    SemanticTokenType::COMMENT,
+];
+
+const DEPRECATED: u32 = 1;
+
+// In the order of `TOKEN_TYPES`
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Kind {
+    // Section header
+    Section,
+    // Key of a known directive
+    Key,
+    // Key systemd doesn't know, including `X-` extensions
+    UnknownKey,
+    // Booleans and keywords such as `infinity` or time units
+    Keyword,
+    Number,
+    EnumValue,
+    UnitName,
+    Path,
+    // `%n` and other specifiers
+    Specifier,
+    // `$VAR` and `${VAR}` in command lines
+    Variable,
+    // `@-:+!` prefixes and `;` separators of command lines
+    ExecPrefix,
+    Comment,
+}
+
+pub fn legend() -> SemanticTokensLegend {
+    SemanticTokensLegend {
+        token_types: TOKEN_TYPES.to_vec(),
+        token_modifiers: vec![SemanticTokenModifier::DEPRECATED],
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token {
+    span: Span,
+    kind: Kind,
+    modifiers: u32,
+}
+
+// Collects the tokens of one directive value, in logical value offsets
+struct ValueTokens<'a> {
+    directive: &'a Directive,
+    tokens: &'a mut Vec<Token>,
+}
+
+impl ValueTokens<'_> {
+    fn push(&mut self, start: usize, end: usize, kind: Kind) {
+        if start < end {
+            self.tokens.push(Token {
+                span: self.directive.source_span(start, end),
+                kind,
+                modifiers: 0,
+            });
+        }
+    }
+
+    // A run of the value, with specifiers, and variables if `variables` is
+    // set, split out into their own tokens
+    fn push_expanded(&mut self, start: usize, end: usize, kind: Option<Kind>, variables: bool) {
+        let value = &self.directive.value;
+        let bytes = value.as_bytes();
+        let mut plain = start;
+        let mut i = start;
+
+        while i < end {
+            let special = match bytes[i] {
+                b'%' if i + 1 < end && bytes[i + 1] == b'%' => {
+                    i += 2;
+                    continue;
+                }
+                b'%' if i + 1 < end && bytes[i + 1].is_ascii_alphabetic() => {
+                    Some((i + 2, Kind::Specifier))
+                }
+                b'$' if variables => {
+                    variable_end(&value[i..end]).map(|len| (i + len, Kind::Variable))
+                }
+                _ => None,
+            };
+            match special {
+                Some((special_end, special_kind)) => {
+                    if let Some(kind) = kind {
+                        self.push(plain, i, kind);
+                    }
+                    self.push(i, special_end, special_kind);
+                    i = special_end;
+                    plain = i;
+                }
+                None => i += 1,
+            }
+        }
+        if let Some(kind) = kind {
+            self.push(plain, end, kind);
+        }
+    }
+}
+
+// Length of a `$VAR` or `${VAR}` reference at the start of `text`
+fn variable_end(text: &str) -> Option<usize> {
+    let is_name = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    if let Some(rest) = text.strip_prefix("${") {
+        let len = rest.find('}')?;
+        return (len > 0 && rest[..len].chars().all(is_name)).then_some(len + 3);
+    }
+    let len = text[1..]
+        .find(|c: char| !is_name(c))
+        .unwrap_or(text.len() - 1);
+    (len > 0).then_some(len + 1)
+}
+
+// Words of a value with their logical byte ranges
+fn words(value: &str) -> impl Iterator<Item = (usize, usize, &str)> {
+    units::split_names(value).map(|(span, word)| (span.start, span.end, word))
+}
+
+// A number with an optional unit suffix, such as `5min` or `512M`
+fn push_quantity(tokens: &mut ValueTokens, start: usize, end: usize, word: &str) {
+    if word == "infinity" {
+        tokens.push(start, end, Kind::Keyword);
+        return;
+    }
+    let digits = word
+        .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+        .unwrap_or(word.len());
+    if digits == 0 || word[digits..].starts_with('%') {
+        tokens.push(start, end, Kind::Number);
+        return;
+    }
+    tokens.push(start, start + digits, Kind::Number);
+    tokens.push(start + digits, end, Kind::Keyword);
+}
+
+fn value_tokens(section: &str, directive: &Directive, tokens: &mut Vec<Token>) {
+    let Some(schema) = schema::lookup(section, &directive.key) else {
+        // Unknown settings still expand specifiers
+        let len = directive.value.len();
+        ValueTokens { directive, tokens }.push_expanded(0, len, None, false);
+        return;
+    };
+    let value = directive.value.as_str();
+    let mut tokens = ValueTokens { directive, tokens };
+
+    match schema.value {
+        ValueType::Boolean if schema::boolean_alias(value).is_some() => {
+            tokens.push(0, value.len(), Kind::Keyword);
+        }
+        ValueType::Enum(_) if schema.find_enum_value(value).is_some() => {
+            tokens.push(0, value.len(), Kind::EnumValue);
+        }
+        ValueType::Integer
+        | ValueType::Weight
+        | ValueType::Mode
+        | ValueType::Percent
+        | ValueType::Tasks
+        | ValueType::Size
+        | ValueType::Timespan => {
+            for (start, end, word) in words(value) {
+                push_quantity(&mut tokens, start, end, word);
+            }
+        }
+        ValueType::Limit => {
+            for (start, end, word) in words(value) {
+                let (soft, _) = word.split_once(':').unwrap_or((word, ""));
+                push_quantity(&mut tokens, start, start + soft.len(), soft);
+                if soft.len() < word.len() {
+                    let hard = &word[soft.len() + 1..];
+                    push_quantity(&mut tokens, start + soft.len() + 1, end, hard);
+                }
+            }
+        }
+        ValueType::Units => {
+            for (start, end, _) in words(value) {
+                tokens.push_expanded(start, end, Some(Kind::UnitName), false);
+            }
+        }
+        ValueType::Path => {
+            for (start, end, _) in words(value) {
+                tokens.push_expanded(start, end, Some(Kind::Path), false);
+            }
+        }
+        ValueType::Command => {
+            let line = exec::parse(value);
+            let first = tokens.tokens.len();
+            for command in &line.commands {
+                let variables = command.expands_variables();
+                let prefix = command.prefix_span;
+                tokens.push(prefix.start, prefix.end, Kind::ExecPrefix);
+                if let Some(executable) = &command.executable {
+                    let span = executable.span;
+                    tokens.push_expanded(span.start, span.end, Some(Kind::Path), false);
+                }
+                for argument in &command.arguments {
+                    let kind = argument.text.starts_with('/').then_some(Kind::Path);
+                    let span = argument.span;
+                    tokens.push_expanded(span.start, span.end, kind, variables);
+                }
+            }
+            for separator in &line.separators {
+                tokens.push(separator.start, separator.end, Kind::ExecPrefix);
+            }
+            tokens.tokens[first..].sort_by_key(|token| token.span.start);
+        }
+        _ => tokens.push_expanded(0, value.len(), None, false),
+    }
+}
+
+fn entry_tokens(section: Option<&str>, entries: &[Entry], tokens: &mut Vec<Token>) {
+    for entry in entries {
+        match entry {
+            Entry::Comment(comment) => tokens.push(Token {
+                span: comment.span,
+                kind: Kind::Comment,
+                modifiers: 0,
+            }),
+            Entry::Directive(directive) => {
+                let schema = section.and_then(|section| schema::lookup(section, &directive.key));
+                let kind = match schema {
+                    Some(_) => Kind::Key,
+                    None => Kind::UnknownKey,
+                };
+                let deprecated = schema.is_some_and(|schema| schema.deprecated);
+                tokens.push(Token {
+                    span: directive.key_span,
+                    kind,
+                    modifiers: if deprecated { DEPRECATED } else { 0 },
+                });
+                if let Some(section) = section {
+                    value_tokens(section, directive, tokens);
+                }
+            }
+            Entry::Blank(_) | Entry::Invalid(_) => {}
+        }
+    }
+}
+
+// Every token of the document, in source order
+fn tokens(document: &Document) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    entry_tokens(None, &document.unit.preamble, &mut tokens);
+    for section in &document.unit.sections {
+        tokens.push(Token {
+            span: section.name_span,
+            kind: Kind::Section,
+            modifiers: 0,
+        });
+        entry_tokens(Some(&section.name), &section.entries, &mut tokens);
+    }
+    tokens
+}
+
+// Tokens for the whole document, or the part of it within `range`, in the
+// relative encoding LSP expects. Tokens are split at line breaks, as
+// clients don't support multiline tokens.
+pub fn semantic_tokens(document: &Document, range: Option<Span>) -> Vec<SemanticToken> {
+    let text = document.text();
+    let mut result = Vec::new();
+    let (mut last_line, mut last_start) = (0, 0);
+
+    for token in tokens(document) {
+        if range.is_some_and(|range| token.span.end < range.start || token.span.start > range.end) {
+            continue;
+        }
+
+        let mut start = token.span.start;
+        for line in text[token.span.start..token.span.end].split('\n') {
+            let end = start + line.trim_end_matches('\r').len();
+            if start < end {
+                let position = document.index.position(start);
+                let delta_line = position.line - last_line;
+                let delta_start = if delta_line == 0 {
+                    position.character - last_start
+                } else {
+                    position.character
+                };
+                result.push(SemanticToken {
+                    delta_line,
+                    delta_start,
+                    length: text[start..end].encode_utf16().count() as u32,
+                    token_type: token.kind as u32,
+                    token_modifiers_bitset: token.modifiers,
+                });
+                (last_line, last_start) = (position.line, position.character);
+            }
+            start += line.len() + 1;
+        }
+    }
+
+    result
+}
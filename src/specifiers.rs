@@ -0,0 +1,208 @@
+// `%` specifiers, which systemd replaces in most settings when loading a
+// unit, from systemd.unit(5)
+
+use std::path::Path;
+
+use crate::parser::Span;
+use crate::schema::UnitType;
+
+pub struct Specifier {
+    pub letter: char,
+    pub doc: &'static str,
+}
+
+const fn specifier(letter: char, doc: &'static str) -> Specifier {
+    Specifier { letter, doc }
+}
+
+pub const SPECIFIERS: &[Specifier] = &[
+    specifier('a', "Architecture, such as `x86-64` or `arm64`."),
+    specifier(
+        'A',
+        "Operating system image version, `IMAGE_VERSION=` of os-release(5).",
+    ),
+    specifier('b', "Boot ID of the running system."),
+    specifier(
+        'B',
+        "Operating system build ID, `BUILD_ID=` of os-release(5).",
+    ),
+    specifier(
+        'C',
+        "Cache directory root: `/var/cache`, or `$XDG_CACHE_HOME` for the user manager.",
+    ),
+    specifier('d', "Credentials directory, `$CREDENTIALS_DIRECTORY`."),
+    specifier(
+        'D',
+        "Shared data directory: `/usr/share`, or `$XDG_DATA_HOME` for the user manager.",
+    ),
+    specifier(
+        'E',
+        "Configuration directory root: `/etc`, or `$XDG_CONFIG_HOME` for the user manager.",
+    ),
+    specifier(
+        'f',
+        "The unescaped instance name with `/` prepended, or the unescaped prefix name if there is no instance.",
+    ),
+    specifier('g', "Name of the group the manager runs as."),
+    specifier('G', "GID of the group the manager runs as."),
+    specifier('h', "Home directory of the user the manager runs as."),
+    specifier('H', "Host name of the running system."),
+    specifier(
+        'i',
+        "Instance name: the part between `@` and the type suffix of an instantiated unit.",
+    ),
+    specifier('I', "Unescaped instance name."),
+    specifier(
+        'j',
+        "Final component of the prefix: the part after the last `-` of the prefix name.",
+    ),
+    specifier('J', "Unescaped final component of the prefix."),
+    specifier('l', "Short host name, up to the first dot."),
+    specifier(
+        'L',
+        "Log directory root: `/var/log`, or `$XDG_STATE_HOME/log` for the user manager.",
+    ),
+    specifier('m', "Machine ID of the running system."),
+    specifier(
+        'M',
+        "Operating system image identifier, `IMAGE_ID=` of os-release(5).",
+    ),
+    specifier('n', "Full unit name."),
+    specifier('N', "Full unit name without the type suffix."),
+    specifier('o', "Operating system ID, `ID=` of os-release(5)."),
+    specifier(
+        'p',
+        "Prefix name: the part before `@` of an instantiated unit, or the name without the type suffix.",
+    ),
+    specifier('P', "Unescaped prefix name."),
+    specifier(
+        'q',
+        "Pretty host name, `PRETTY_HOSTNAME=` of machine-info(5).",
+    ),
+    specifier('s', "Shell of the user the manager runs as."),
+    specifier(
+        'S',
+        "State directory root: `/var/lib`, or `$XDG_STATE_HOME` for the user manager.",
+    ),
+    specifier(
+        't',
+        "Runtime directory root: `/run`, or `$XDG_RUNTIME_DIR` for the user manager.",
+    ),
+    specifier('T', "Directory for temporary files, usually `/tmp`."),
+    specifier('u', "Name of the user the manager runs as."),
+    specifier('U', "UID of the user the manager runs as."),
+    specifier('v', "Kernel release, as `uname -r` prints it."),
+    specifier(
+        'V',
+        "Directory for larger and persistent temporary files, usually `/var/tmp`.",
+    ),
+    specifier(
+        'w',
+        "Operating system version ID, `VERSION_ID=` of os-release(5).",
+    ),
+    specifier(
+        'W',
+        "Operating system variant ID, `VARIANT_ID=` of os-release(5).",
+    ),
+    specifier('y', "Path of the unit file."),
+    specifier('Y', "Directory of the unit file."),
+    specifier('%', "A literal `%`."),
+];
+
+pub fn find(letter: char) -> Option<&'static Specifier> {
+    SPECIFIERS
+        .iter()
+        .find(|specifier| specifier.letter == letter)
+}
+
+// A `%` in a value with the character after it, if any
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct SpecifierUse {
+    pub span: Span,
+    pub letter: Option<char>,
+}
+
+// The specifiers of a value, `%%` included, with logical byte ranges
+pub fn scan(value: &str) -> Vec<SpecifierUse> {
+    let mut uses = Vec::new();
+    let mut chars = value.char_indices();
+    while let Some((i, c)) = chars.next() {
+        if c != '%' {
+            continue;
+        }
+        let letter = chars.next().map(|(_, letter)| letter);
+        let len = 1 + letter.map_or(0, char::len_utf8);
+        uses.push(SpecifierUse {
+            span: Span::new(i, i + len),
+            letter,
+        });
+    }
+    uses
+}
+
+// Undo systemd's unit name escaping: `-` stands for `/` and `\xNN` for a
+// byte
+pub fn unescape_name(name: &str) -> String {
+    let mut bytes = Vec::new();
+    let mut rest = name.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'\\'
+            && tail.first() == Some(&b'x')
+            && let Some(hex) = tail.get(1..3)
+            && let Ok(decoded) = u8::from_str_radix(std::str::from_utf8(hex).unwrap_or(""), 16)
+        {
+            bytes.push(decoded);
+            rest = &tail[3..];
+            continue;
+        }
+        bytes.push(if byte == b'-' { b'/' } else { byte });
+        rest = tail;
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+// What a specifier expands to for the unit file at `path`, for the
+// specifiers that only depend on the unit name and file. `unit_name` is
+// the name of the unit, which differs from the file name for drop-ins.
+pub fn expand(letter: char, unit_name: &str, path: Option<&Path>) -> Option<String> {
+    let unit_type = UnitType::from_name(unit_name)?;
+    let stem = &unit_name[..unit_name.len() - unit_type.suffix().len() - 1];
+    let (prefix, instance) = match stem.split_once('@') {
+        Some((prefix, instance)) => (prefix, Some(instance)),
+        None => (stem, None),
+    };
+    let last = prefix.rsplit('-').next().unwrap_or(prefix);
+    // Templates have no instance until one is created
+    let instance = match instance {
+        Some("") if matches!(letter, 'i' | 'I' | 'f') => return None,
+        instance => instance.unwrap_or_default(),
+    };
+
+    let expansion = match letter {
+        'n' => unit_name.to_string(),
+        'N' => stem.to_string(),
+        'p' => prefix.to_string(),
+        'P' => unescape_name(prefix),
+        'i' => instance.to_string(),
+        'I' => unescape_name(instance),
+        'j' => last.to_string(),
+        'J' => unescape_name(last),
+        'f' => {
+            let name = unescape_name(if instance.is_empty() {
+                prefix
+            } else {
+                instance
+            });
+            if name.starts_with('/') {
+                name
+            } else {
+                format!("/{}", name)
+            }
+        }
+        'y' => path?.display().to_string(),
+        'Y' => path?.parent()?.display().to_string(),
+        '%' => "%".to_string(),
+        _ => return None,
+    };
+    Some(expansion)
+}
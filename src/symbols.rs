@@ -0,0 +1,57 @@
+// Outline of a unit file: sections with the directives they contain
+
+use tower_lsp::lsp_types::{DocumentSymbol, SymbolKind};
+
+use crate::document::Document;
+use crate::parser::{Directive, Entry};
+
+fn directive_symbol(document: &Document, directive: &Directive) -> DocumentSymbol {
+    #[allow(deprecated)]
+    DocumentSymbol {
+        name: directive.key.clone(),
+        detail: (!directive.value.is_empty()).then(|| directive.value.clone()),
+        kind: SymbolKind::PROPERTY,
+        tags: None,
+        deprecated: None,
+        range: document.index.range(directive.span),
+        selection_range: document.index.range(directive.key_span),
+        children: None,
+    }
+}
+
+fn entry_symbols<'a>(
+    document: &'a Document,
+    entries: &'a [Entry],
+) -> impl Iterator<Item = DocumentSymbol> + 'a {
+    entries.iter().filter_map(move |entry| match entry {
+        Entry::Directive(directive) => Some(directive_symbol(document, directive)),
+        _ => None,
+    })
+}
+
+// Hierarchical symbols for a document: sections as namespaces and their
+// directives, repeated ones included, as properties in file order
+pub fn document_symbols(document: &Document) -> Vec<DocumentSymbol> {
+    let mut symbols = Vec::new();
+
+    for section in &document.unit.sections {
+        // Clients reject symbols without a name, so `[]` keeps its brackets
+        let name = match section.name.trim() {
+            "" => "[]".to_string(),
+            _ => section.name.clone(),
+        };
+        #[allow(deprecated)]
+        symbols.push(DocumentSymbol {
+            name,
+            detail: None,
+            kind: SymbolKind::NAMESPACE,
+            tags: None,
+            deprecated: None,
+            range: document.index.range(section.span),
+            selection_range: document.index.range(section.name_span),
+            children: Some(entry_symbols(document, &section.entries).collect()),
+        });
+    }
+
+    symbols
+}
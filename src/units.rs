@@ -0,0 +1,146 @@
+// Unit names, as written in dependency directives such as After=
+
+use crate::parser::{Directive, Section, Span, UnitFile};
+use crate::schema::{self, UnitType, ValueType};
+
+// A unit name in a directive value, with its span in the source
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnitReference<'a> {
+    pub name: &'a str,
+    pub span: Span,
+}
+
+// Names in a space-separated unit list, with byte ranges of the value
+pub fn split_names(value: &str) -> impl Iterator<Item = (Span, &str)> {
+    value.split_whitespace().map(move |name| {
+        let start = name.as_ptr() as usize - value.as_ptr() as usize;
+        (Span::new(start, start + name.len()), name)
+    })
+}
+
+// Unit names a directive refers to, if its value is a unit list. Names
+// using specifiers can't be resolved and are left out.
+pub fn references<'a>(section: &Section, directive: &'a Directive) -> Vec<UnitReference<'a>> {
+    let is_unit_list = schema::lookup(&section.name, &directive.key)
+        .is_some_and(|schema| schema.value == ValueType::Units);
+    if !is_unit_list {
+        return Vec::new();
+    }
+
+    split_names(&directive.value)
+        .filter(|(_, name)| !name.contains('%') && UnitType::from_name(name).is_some())
+        .map(|(span, name)| UnitReference {
+            name,
+            span: directive.source_span(span.start, span.end),
+        })
+        .collect()
+}
+
+// The unit name at a byte offset of the file
+pub fn reference_at(unit: &UnitFile, offset: usize) -> Option<UnitReference<'_>> {
+    let (section, directive) = unit.directive_at(offset)?;
+    references(section, directive)
+        .into_iter()
+        .find(|reference| reference.span.contains(offset))
+}
+
+// The template an instance is created from: `foo@bar.service` becomes
+// `foo@.service`. Templates and plain units have none.
+pub fn template_of(name: &str) -> Option<String> {
+    let (prefix, rest) = name.split_once('@')?;
+    let (instance, suffix) = rest.rsplit_once('.')?;
+    if instance.is_empty() {
+        return None;
+    }
+    Some(format!("{}@.{}", prefix, suffix))
+}
+
+// Whether a unit name is a template such as `foo@.service`
+pub fn is_template(name: &str) -> bool {
+    name.split_once('@')
+        .is_some_and(|(prefix, rest)| !prefix.is_empty() && rest.starts_with('.'))
+}
+
+// Whether `text` may be used as an instance name: ASCII letters and digits
+// and `:-_.\@`
+pub fn is_valid_instance(text: &str) -> bool {
+    !text.is_empty()
+        && text
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ":-_.\\@".contains(c))
+}
+
+// Well-known units with a special meaning to systemd, from systemd.special(7)
+pub const SPECIAL_UNITS: &[(&str, &str)] = &[
+    (
+        "basic.target",
+        "Pulled in by every service with default dependencies; basic system initialization is complete.",
+    ),
+    (
+        "default.target",
+        "The unit systemd starts at boot, usually an alias of multi-user.target or graphical.target.",
+    ),
+    (
+        "emergency.target",
+        "Starts an emergency shell on the main console.",
+    ),
+    (
+        "getty.target",
+        "Pulls in the statically configured local TTY getty instances.",
+    ),
+    ("graphical.target", "Sets up a graphical login screen."),
+    ("halt.target", "Shuts down and halts the system."),
+    ("local-fs.target", "Local file systems are mounted."),
+    (
+        "multi-user.target",
+        "Sets up a non-graphical multi-user system.",
+    ),
+    (
+        "network.target",
+        "The network management stack is up. Does not mean the network is configured.",
+    ),
+    (
+        "network-online.target",
+        "The network is configured and online. Pull it in with Wants= and order after it.",
+    ),
+    (
+        "network-pre.target",
+        "Ordered before any network interface is configured, for firewalls and similar.",
+    ),
+    ("nss-lookup.target", "Host name lookups are available."),
+    (
+        "paths.target",
+        "Sets up every path unit that is active after boot.",
+    ),
+    ("poweroff.target", "Shuts down and powers off the system."),
+    ("reboot.target", "Shuts down and reboots the system."),
+    ("remote-fs.target", "Remote file systems are mounted."),
+    (
+        "rescue.target",
+        "Brings up the base system and a rescue shell.",
+    ),
+    (
+        "shutdown.target",
+        "Conflicted by services that should be stopped on shutdown.",
+    ),
+    (
+        "sockets.target",
+        "Sets up every socket unit that is active after boot.",
+    ),
+    ("sysinit.target", "Early boot initialization is complete."),
+    (
+        "time-sync.target",
+        "The system clock has been synchronized, if a synchronization service pulls it in.",
+    ),
+    (
+        "timers.target",
+        "Sets up every timer unit that is active after boot.",
+    ),
+    ("dbus.service", "The D-Bus system message bus."),
+    ("system.slice", "Contains all system services by default."),
+    ("user.slice", "Contains all user processes and services."),
+    (
+        "machine.slice",
+        "Contains all virtual machines and containers.",
+    ),
+];
@@ -0,0 +1,351 @@
+// Validators for the value grammars systemd's config parsers accept
+// (parse_sec, parse_size, parse_permyriad, rlimit_parse, ...)
+
+use crate::calendar;
+use crate::parser::Span;
+use crate::schema::{self, ValueType};
+
+// A rejected value. The span is a byte range of the logical value.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ValueError {
+    pub span: Span,
+    pub message: String,
+}
+
+impl ValueError {
+    fn new(start: usize, end: usize, message: String) -> Self {
+        Self {
+            span: Span::new(start, end),
+            message,
+        }
+    }
+
+    fn whole(value: &str, message: String) -> Self {
+        Self::new(0, value.len(), message)
+    }
+}
+
+const TIME_UNITS: &[(&str, f64)] = &[
+    ("usec", 1.0),
+    ("us", 1.0),
+    ("μs", 1.0),
+    ("µs", 1.0),
+    ("nsec", 0.001),
+    ("ns", 0.001),
+    ("msec", 1e3),
+    ("ms", 1e3),
+    ("seconds", 1e6),
+    ("second", 1e6),
+    ("sec", 1e6),
+    ("s", 1e6),
+    ("minutes", 60e6),
+    ("minute", 60e6),
+    ("min", 60e6),
+    ("m", 60e6),
+    ("hours", 3600e6),
+    ("hour", 3600e6),
+    ("hr", 3600e6),
+    ("h", 3600e6),
+    ("days", 86400e6),
+    ("day", 86400e6),
+    ("d", 86400e6),
+    ("weeks", 604800e6),
+    ("week", 604800e6),
+    ("w", 604800e6),
+    ("months", 2629800e6),
+    ("month", 2629800e6),
+    ("M", 2629800e6),
+    ("years", 31557600e6),
+    ("year", 31557600e6),
+    ("y", 31557600e6),
+];
+
+const SIZE_UNITS: &[(&str, f64)] = &[
+    ("B", 1.0),
+    ("K", 1024.0),
+    ("M", 1048576.0),
+    ("G", 1073741824.0),
+    ("T", 1099511627776.0),
+    ("P", 1125899906842624.0),
+    ("E", 1152921504606846976.0),
+];
+
+const TIMESPAN_FORMAT: &str = "expected a time span like '5min 30s' or 'infinity'; units are us, ms, s, min, h, d, w, M and y";
+const SIZE_FORMAT: &str = "expected a size like '512M' with an optional B, K, M, G, T, P or E suffix (base 1024), a percentage, or 'infinity'";
+
+// Check a value against the grammar of its type. Types without a grammar
+// of their own (strings, paths, enums, ...) are accepted here.
+pub fn validate(value_type: ValueType, value: &str) -> Result<(), ValueError> {
+    match value_type {
+        ValueType::Boolean => validate_boolean(value),
+        ValueType::Integer => validate_integer(value),
+        ValueType::Timespan => parse_timespan(value).map(|_| ()),
+        ValueType::Size => validate_size(value),
+        ValueType::Limit => validate_limit(value),
+        ValueType::Percent => parse_percent(value).map(|_| ()),
+        ValueType::Weight => validate_weight(value),
+        ValueType::Tasks => validate_tasks(value),
+        ValueType::Mode => validate_mode(value),
+        ValueType::Calendar => calendar::parse(value).map(|_| ()),
+        _ => Ok(()),
+    }
+}
+
+fn validate_boolean(value: &str) -> Result<(), ValueError> {
+    match schema::boolean_alias(value) {
+        Some(_) => Ok(()),
+        None => Err(ValueError::whole(
+            value,
+            format!(
+                "Invalid boolean '{}': expected yes/no, true/false, on/off or 1/0",
+                value
+            ),
+        )),
+    }
+}
+
+fn validate_integer(value: &str) -> Result<(), ValueError> {
+    let digits = value.strip_prefix(['-', '+']).unwrap_or(value);
+    let valid = match digits.strip_prefix("0x") {
+        Some(hex) => u64::from_str_radix(hex, 16).is_ok(),
+        None => digits.parse::<u64>().is_ok(),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(ValueError::whole(
+            value,
+            format!("Invalid integer '{}'", value),
+        ))
+    }
+}
+
+// A number with an optional fraction at the start of `text`, as the length
+// of the number and its value
+fn leading_number(text: &str) -> Option<(usize, f64)> {
+    let integer = text
+        .find(|c: char| !c.is_ascii_digit())
+        .unwrap_or(text.len());
+    let mut end = integer;
+    if let Some(fraction) = text[integer..].strip_prefix('.') {
+        end += 1 + fraction
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(fraction.len());
+    }
+    if integer == 0 && end <= 1 {
+        return None;
+    }
+    text[..end].parse().ok().map(|number| (end, number))
+}
+
+// Parse a sum of number-unit pairs such as `1h 30min` or `1.5s` into the
+// total multiplied out with `units`; a bare number uses `default_unit`
+fn parse_sum(
+    value: &str,
+    units: &[(&str, f64)],
+    default_unit: f64,
+    format: &str,
+) -> Result<f64, ValueError> {
+    let mut total = 0.0;
+    let mut offset = value.len() - value.trim_start().len();
+
+    while offset < value.len() {
+        let rest = &value[offset..];
+        let word_end = offset + rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let Some((number_len, number)) = leading_number(rest) else {
+            return Err(ValueError::new(
+                offset,
+                word_end,
+                format!("Invalid value '{}': {}", &value[offset..word_end], format),
+            ));
+        };
+
+        // The unit may be separated from its number by whitespace
+        let after_number = &rest[number_len..];
+        let gap = after_number.len() - after_number.trim_start().len();
+        let unit_text = &after_number[gap..];
+        let unit_len = unit_text
+            .find(|c: char| c.is_ascii_digit() || c.is_whitespace() || c == '.')
+            .unwrap_or(unit_text.len());
+        let unit = &unit_text[..unit_len];
+
+        let (factor, consumed) = if unit.is_empty() {
+            (default_unit, number_len)
+        } else {
+            match units.iter().find(|(name, _)| *name == unit) {
+                Some((_, factor)) => (*factor, number_len + gap + unit_len),
+                None => {
+                    let end = offset + number_len + gap + unit_len;
+                    return Err(ValueError::new(
+                        offset,
+                        end,
+                        format!(
+                            "Unknown unit '{}' in '{}': {}",
+                            unit,
+                            &value[offset..end],
+                            format
+                        ),
+                    ));
+                }
+            }
+        };
+
+        total += number * factor;
+        offset += consumed;
+        offset += value[offset..].len() - value[offset..].trim_start().len();
+    }
+
+    Ok(total)
+}
+
+// Parse a time span into microseconds; `infinity` is `None`
+pub fn parse_timespan(value: &str) -> Result<Option<u64>, ValueError> {
+    if value == "infinity" {
+        return Ok(None);
+    }
+    let usec = parse_sum(value, TIME_UNITS, 1e6, TIMESPAN_FORMAT)?;
+    Ok(Some(usec as u64))
+}
+
+// Parse a byte size; `infinity` is `None`
+pub fn parse_size(value: &str) -> Result<Option<u64>, ValueError> {
+    if value == "infinity" {
+        return Ok(None);
+    }
+    let bytes = parse_sum(value, SIZE_UNITS, 1.0, SIZE_FORMAT)?;
+    Ok(Some(bytes as u64))
+}
+
+fn validate_size(value: &str) -> Result<(), ValueError> {
+    if value.ends_with(['%', '‰', '‱']) {
+        return parse_percent(value).map(|_| ());
+    }
+    parse_size(value).map(|_| ())
+}
+
+// Parse a percentage, permille or permyriad value into a fraction of one
+pub fn parse_percent(value: &str) -> Result<f64, ValueError> {
+    let invalid = || {
+        ValueError::whole(
+            value,
+            format!(
+                "Invalid percentage '{}': expected a value like '50%', '12.5%' or '500‰'",
+                value
+            ),
+        )
+    };
+
+    let (number, scale) = if let Some(number) = value.strip_suffix('%') {
+        (number, 100.0)
+    } else if let Some(number) = value.strip_suffix('‰') {
+        (number, 1000.0)
+    } else if let Some(number) = value.strip_suffix('‱') {
+        (number, 10000.0)
+    } else {
+        return Err(invalid());
+    };
+
+    match leading_number(number) {
+        Some((len, fraction)) if len == number.len() => Ok(fraction / scale),
+        _ => Err(invalid()),
+    }
+}
+
+// Resource limits are `soft[:hard]`, each a number, size or time span
+fn validate_limit(value: &str) -> Result<(), ValueError> {
+    let (soft, hard) = match value.split_once(':') {
+        Some((soft, hard)) => (soft, Some(hard)),
+        None => (value, None),
+    };
+
+    let soft_value = parse_limit_part(soft, 0)?;
+    if let Some(hard) = hard {
+        let hard_value = parse_limit_part(hard, soft.len() + 1)?;
+        let exceeds = match (soft_value, hard_value) {
+            (LimitPart::Number(soft), LimitPart::Number(hard)) => soft > hard,
+            (LimitPart::Timespan(soft), LimitPart::Timespan(hard)) => soft > hard,
+            // A plain number is in the resource's own unit, seconds for
+            // LimitCPU= but microseconds for LimitRTTIME=
+            _ => false,
+        };
+        if exceeds {
+            return Err(ValueError::whole(
+                value,
+                format!("Soft limit '{}' exceeds hard limit '{}'", soft, hard),
+            ));
+        }
+    }
+    Ok(())
+}
+
+// One side of a limit. Time spans are accepted for LimitCPU= and
+// LimitRTTIME=, and only compared with each other.
+#[derive(Debug, Clone, Copy)]
+enum LimitPart {
+    Infinity,
+    Number(f64),
+    // In microseconds
+    Timespan(u64),
+}
+
+fn parse_limit_part(part: &str, offset: usize) -> Result<LimitPart, ValueError> {
+    if part == "infinity" {
+        return Ok(LimitPart::Infinity);
+    }
+    if let Ok(size) = parse_sum(part, SIZE_UNITS, 1.0, SIZE_FORMAT) {
+        return Ok(LimitPart::Number(size));
+    }
+    if let Ok(Some(usec)) = parse_timespan(part) {
+        return Ok(LimitPart::Timespan(usec));
+    }
+    Err(ValueError::new(
+        offset,
+        offset + part.len(),
+        format!(
+            "Invalid limit '{}': expected a number, size or time span, 'infinity', or 'soft:hard' such as '65536:524288'",
+            part
+        ),
+    ))
+}
+
+fn validate_weight(value: &str) -> Result<(), ValueError> {
+    match value.parse::<u64>() {
+        Ok(1..=10000) => Ok(()),
+        _ if value == "idle" => Ok(()),
+        _ => Err(ValueError::whole(
+            value,
+            format!(
+                "Invalid weight '{}': expected an integer between 1 and 10000",
+                value
+            ),
+        )),
+    }
+}
+
+fn validate_tasks(value: &str) -> Result<(), ValueError> {
+    if value == "infinity" || value.parse::<u64>().is_ok() {
+        return Ok(());
+    }
+    parse_percent(value).map(|_| ()).map_err(|_| {
+        ValueError::whole(
+            value,
+            format!(
+                "Invalid task limit '{}': expected a number, a percentage or 'infinity'",
+                value
+            ),
+        )
+    })
+}
+
+fn validate_mode(value: &str) -> Result<(), ValueError> {
+    match u32::from_str_radix(value, 8) {
+        Ok(mode) if mode <= 0o7777 => Ok(()),
+        _ => Err(ValueError::whole(
+            value,
+            format!(
+                "Invalid mode '{}': expected an octal access mode such as 0755",
+                value
+            ),
+        )),
+    }
+}
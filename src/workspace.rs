@@ -0,0 +1,96 @@
+// Unit files on disk: the workspace folders and systemd's search paths
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::index::UnitIndex;
+use crate::schema::UnitType;
+use crate::units;
+
+// Directories never searched for unit files
+const SKIPPED_DIRS: &[&str] = &["target", "node_modules"];
+
+// Unit files and drop-ins below a workspace folder. Hidden directories
+// and build output are skipped.
+pub fn unit_files(root: &Path) -> Vec<PathBuf> {
+    let mut files = Vec::new();
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let name = entry.file_name();
+            let name = name.to_string_lossy();
+            let Ok(file_type) = entry.file_type() else {
+                continue;
+            };
+
+            if file_type.is_dir() {
+                if !name.starts_with('.') && !SKIPPED_DIRS.contains(&name.as_ref()) {
+                    pending.push(path);
+                }
+            } else if UnitType::from_path(&path).is_some() {
+                files.push(path);
+            }
+        }
+    }
+
+    files.sort();
+    files
+}
+
+// Dependency a link in a `.wants/`, `.requires/` or `.upholds/` directory
+// stands for, as the unit owning the directory and the directive it acts
+// like. `multi-user.target.wants/app.service` makes multi-user.target want
+// app.service.
+pub fn dependency_link(path: &Path) -> Option<(String, &'static str)> {
+    let dir = path.parent()?.file_name()?.to_str()?;
+    let (unit, key) = [
+        (".wants", "Wants"),
+        (".requires", "Requires"),
+        (".upholds", "Upholds"),
+    ]
+    .into_iter()
+    .find_map(|(suffix, key)| Some((dir.strip_suffix(suffix)?, key)))?;
+    UnitType::from_name(unit)?;
+    Some((unit.to_string(), key))
+}
+
+// Find the file defining a unit: first among the indexed workspace files,
+// then in the search paths in order of precedence. Links in `.wants/` and
+// similar directories don't define the unit they point to. Instances fall
+// back to their template, as systemd does.
+pub fn find_unit(name: &str, index: &UnitIndex, search_paths: &[PathBuf]) -> Option<PathBuf> {
+    let lookup = |name: &str| {
+        index
+            .definitions(name)
+            .into_iter()
+            .filter_map(|uri| uri.to_file_path().ok())
+            .find(|path| dependency_link(path).is_none())
+            .or_else(|| {
+                search_paths
+                    .iter()
+                    .map(|dir| dir.join(name))
+                    .find(|path| path.is_file())
+            })
+    };
+
+    lookup(name).or_else(|| lookup(&units::template_of(name)?))
+}
+
+// Names of the unit files directly in the search paths
+pub fn search_path_units(search_paths: &[PathBuf]) -> Vec<String> {
+    let mut names: Vec<String> = search_paths
+        .iter()
+        .filter_map(|dir| fs::read_dir(dir).ok())
+        .flat_map(|entries| entries.flatten())
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| UnitType::from_name(name).is_some())
+        .collect();
+    names.sort();
+    names.dedup();
+    names
+}
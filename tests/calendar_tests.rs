@@ -0,0 +1,152 @@
+use chrono::{DateTime, Utc};
+use systemd_language_server::calendar::parse;
+use systemd_language_server::parser::Span;
+
+fn normalize(value: &str) -> String {
+    parse(value).unwrap().to_string()
+}
+
+fn elapses(value: &str, after: &str, count: usize) -> Vec<String> {
+    let after: DateTime<Utc> = after.parse().unwrap();
+    parse(value)
+        .unwrap()
+        .next_elapses(after, count)
+        .iter()
+        .map(|time| time.format("%a %Y-%m-%d %H:%M:%S").to_string())
+        .collect()
+}
+
+#[test]
+fn test_normalized_form() {
+    assert_eq!(normalize("daily"), "*-*-* 00:00:00");
+    assert_eq!(normalize("weekly"), "Mon *-*-* 00:00:00");
+    assert_eq!(normalize("quarterly"), "*-01,04,07,10-01 00:00:00");
+    assert_eq!(normalize("*:0/15"), "*-*-* *:00/15:00");
+    assert_eq!(normalize("Mon..Fri 02:00"), "Mon..Fri *-*-* 02:00:00");
+    assert_eq!(normalize("sat,sun 10:30"), "Sat,Sun *-*-* 10:30:00");
+    assert_eq!(normalize("Mon,Tue,Wed,Fri"), "Mon..Wed,Fri *-*-* 00:00:00");
+    assert_eq!(normalize("*-02~03"), "*-02~03 00:00:00");
+    assert_eq!(
+        normalize("2025-1-1 8:00:01.5"),
+        "2025-01-01 08:00:01.500000"
+    );
+    assert_eq!(normalize("*-*-* 02:00:00 UTC"), "*-*-* 02:00:00 UTC");
+    assert_eq!(
+        normalize("hourly Europe/Berlin"),
+        "*-*-* *:00:00 Europe/Berlin"
+    );
+}
+
+// The normalization examples of systemd.time(7)
+#[test]
+fn test_man_page_examples() {
+    for (value, normalized) in [
+        (
+            "Sat,Thu,Mon..Wed,Sat..Sun",
+            "Mon..Thu,Sat,Sun *-*-* 00:00:00",
+        ),
+        ("Mon,Sun 12-*-* 2,1:23", "Mon,Sun 2012-*-* 01,02:23:00"),
+        ("Wed *-1", "Wed *-*-01 00:00:00"),
+        ("Wed..Wed,Wed *-1", "Wed *-*-01 00:00:00"),
+        ("Wed, 17:48", "Wed *-*-* 17:48:00"),
+        (
+            "Wed..Sat,Tue 12-10-15 1:2:3",
+            "Tue..Sat 2012-10-15 01:02:03",
+        ),
+        ("*-*-7 0:0:0", "*-*-07 00:00:00"),
+        ("10-15", "*-10-15 00:00:00"),
+        ("monday *-12-* 17:00", "Mon *-12-* 17:00:00"),
+        ("Mon,Fri *-*-3,1,2 *:30:45", "Mon,Fri *-*-01,02,03 *:30:45"),
+        ("12,14,13,12:20,10,30", "*-*-* 12,13,14:10,20,30:00"),
+        ("12..14:10,20,30", "*-*-* 12..14:10,20,30:00"),
+        ("mon,fri *-1/2-1,3 *:30:45", "Mon,Fri *-01/2-01,03 *:30:45"),
+        ("03-05 08:05:40", "*-03-05 08:05:40"),
+        ("08:05:40", "*-*-* 08:05:40"),
+        ("05:40", "*-*-* 05:40:00"),
+        ("Sat,Sun 12-05 08:05:40", "Sat,Sun *-12-05 08:05:40"),
+        ("Sat,Sun 08:05:40", "Sat,Sun *-*-* 08:05:40"),
+        ("2003-03-05 05:40", "2003-03-05 05:40:00"),
+        (
+            "05:40:23.4200004/3.1700005",
+            "*-*-* 05:40:23.420000/3.170001",
+        ),
+        ("2003-02..04-05", "2003-02..04-05 00:00:00"),
+        ("2003-03-05 05:40 UTC", "2003-03-05 05:40:00 UTC"),
+        ("2003-03-05", "2003-03-05 00:00:00"),
+        ("03-05", "*-03-05 00:00:00"),
+        ("hourly", "*-*-* *:00:00"),
+        ("daily", "*-*-* 00:00:00"),
+        ("daily UTC", "*-*-* 00:00:00 UTC"),
+        ("monthly", "*-*-01 00:00:00"),
+        ("weekly", "Mon *-*-* 00:00:00"),
+        (
+            "weekly Pacific/Auckland",
+            "Mon *-*-* 00:00:00 Pacific/Auckland",
+        ),
+        ("yearly", "*-01-01 00:00:00"),
+        ("annually", "*-01-01 00:00:00"),
+        ("*:2/3", "*-*-* *:02/3:00"),
+    ] {
+        match parse(value) {
+            Ok(spec) => assert_eq!(spec.to_string(), normalized, "{}", value),
+            Err(error) => panic!("{}: {}", value, error.message),
+        }
+    }
+}
+
+#[test]
+fn test_next_elapses() {
+    assert_eq!(
+        elapses("Mon..Fri *-*-* 02:00:00 UTC", "2024-03-08T12:00:00Z", 3),
+        [
+            "Mon 2024-03-11 02:00:00",
+            "Tue 2024-03-12 02:00:00",
+            "Wed 2024-03-13 02:00:00"
+        ]
+    );
+    assert_eq!(
+        elapses("*:0/20 UTC", "2024-03-08T23:50:00Z", 2),
+        ["Sat 2024-03-09 00:00:00", "Sat 2024-03-09 00:20:00"]
+    );
+    // The last Monday in May
+    assert_eq!(
+        elapses("Mon *-05~07/1 UTC", "2024-01-01T00:00:00Z", 2),
+        ["Mon 2024-05-27 00:00:00", "Mon 2025-05-26 00:00:00"]
+    );
+    // The last day of February, leap years included
+    assert_eq!(
+        elapses("*-02~01 UTC", "2024-01-01T00:00:00Z", 2),
+        ["Thu 2024-02-29 00:00:00", "Fri 2025-02-28 00:00:00"]
+    );
+    assert_eq!(
+        elapses("*-*-* 12:00 Asia/Tokyo", "2024-03-08T00:00:00Z", 1)[0],
+        "Fri 2024-03-08 12:00:00"
+    );
+    assert!(elapses("2001-01-01 UTC", "2024-01-01T00:00:00Z", 5).is_empty());
+    assert_eq!(
+        elapses("*-*-* *:*:* UTC", "2024-03-08T12:00:00.5Z", 2),
+        ["Fri 2024-03-08 12:00:01", "Fri 2024-03-08 12:00:02"]
+    );
+    // Repetitions too large to step past the start only match it
+    assert_eq!(
+        elapses("*-*-* 5/4294967295:00 UTC", "2024-03-08T12:00:00Z", 2),
+        ["Sat 2024-03-09 05:00:00", "Sun 2024-03-10 05:00:00"]
+    );
+}
+
+#[test]
+fn test_invalid_expressions_point_at_the_error() {
+    let error = parse("Mon..Fry 02:00").unwrap_err();
+    assert_eq!(error.span, Span::new(5, 8));
+
+    let error = parse("*-13-01").unwrap_err();
+    assert_eq!(error.span, Span::new(2, 4));
+    assert!(error.message.contains("between 1 and 12"));
+
+    let error = parse("*-*-* 25:00").unwrap_err();
+    assert_eq!(error.span, Span::new(6, 8));
+
+    assert_eq!(parse("*:0/0").unwrap_err().span, Span::new(4, 5));
+    assert_eq!(parse("daily extra").unwrap_err().span, Span::new(0, 5));
+    assert!(parse("").is_err());
+}
@@ -1,4 +1,12 @@
+use systemd_language_server::code_actions::Fix;
+use systemd_language_server::config::Config;
+use systemd_language_server::diagnostics::{
+    dropin_diagnostics, find_executable, generate_diagnostics, template_diagnostics,
+};
+use systemd_language_server::document::Document;
 use systemd_language_server::parse_unit_file;
+use systemd_language_server::schema::UnitType;
+use tower_lsp::lsp_types::{Diagnostic, DiagnosticSeverity, Position, Range};
 
 #[test]
 fn test_syntax_error_detection() {
@@ -58,3 +66,343 @@ fn test_comment_handling() {
         "Service with comments"
     );
 }
+
+fn diagnose(content: &str, config: &Config) -> Vec<Diagnostic> {
+    generate_diagnostics(&Document::new(content, 1), None, config)
+}
+
+#[test]
+fn test_syntax_error_range() {
+    let content = "[Unit]\nDescription=Test\nmissing equals\n";
+    let diagnostics = diagnose(content, &Config::default());
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].range.start, Position::new(2, 0));
+    assert_eq!(diagnostics[0].range.end, Position::new(2, 14));
+}
+
+#[test]
+fn test_invalid_enum_value() {
+    let content = "[Service]\nType=daemon\nRestart=on-failure\n";
+    let diagnostics = diagnose(content, &Config::default());
+
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0].message.contains("'daemon'"));
+    assert_eq!(diagnostics[0].range.start, Position::new(1, 5));
+
+    let diagnostics = diagnose("[Service]\nType=simpel\n", &Config::default());
+    let fix = Fix::from_data(diagnostics[0].data.as_ref().unwrap()).unwrap();
+    assert!(matches!(fix, Fix::Replace { text, .. } if text == "simple"));
+}
+
+#[test]
+fn test_target_version_flags_newer_directives_and_values() {
+    let content = "[Service]\nType=notify-reload\nMemoryZSwapMax=1G\nMemoryMax=2G\n";
+    let config = Config::from_json(r#"{"systemd": {"targetSystemdVersion": 252}}"#).unwrap();
+    let diagnostics = diagnose(content, &config);
+
+    assert_eq!(diagnostics.len(), 2);
+    assert!(diagnostics[0].message.contains("notify-reload"));
+    assert!(diagnostics[0].message.contains("253"));
+    assert_eq!(diagnostics[1].range.start, Position::new(2, 0));
+    assert!(diagnostics[1].message.contains("MemoryZSwapMax"));
+
+    // Without a target version everything is accepted
+    assert!(diagnose(content, &Config::default()).is_empty());
+}
+
+#[test]
+fn test_diagnostics_can_be_disabled() {
+    let content = "[Service]\nType=daemon\n";
+    let config = Config::from_json(r#"{"systemd": {"diagnostics": {"enabled": false}}}"#).unwrap();
+
+    assert!(diagnose(content, &config).is_empty());
+}
+
+#[test]
+fn test_config_defaults() {
+    let config = Config::from_json(r#"{"systemd": {"unitSearchPaths": ["/srv/units"]}}"#).unwrap();
+
+    assert_eq!(config.unit_search_paths.len(), 1);
+    assert!(config.diagnostics.enabled);
+    assert_eq!(config.target_systemd_version, None);
+    assert!(Config::from_json("{}").unwrap().diagnostics.enabled);
+}
+
+#[test]
+fn test_unknown_key_suggests_closest_name() {
+    let content = "[Service]\nRestrat=always\nX-Custom=1\n";
+    let diagnostics = diagnose(content, &Config::default());
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].message,
+        "Unknown key 'Restrat' in section [Service]. Did you mean 'Restart'?"
+    );
+    let fix = Fix::from_data(diagnostics[0].data.as_ref().unwrap()).unwrap();
+    assert_eq!(
+        fix,
+        Fix::Replace {
+            range: diagnostics[0].range,
+            text: "Restart".into()
+        }
+    );
+    assert_eq!(diagnostics[0].range.start, Position::new(1, 0));
+    assert_eq!(diagnostics[0].range.end, Position::new(1, 7));
+}
+
+#[test]
+fn test_unknown_section() {
+    let content = "[Serivce]\nRestrat=always\n\n[X-Vendor]\nFoo=bar\n";
+    let diagnostics = diagnose(content, &Config::default());
+
+    // Keys in unknown sections are not reported individually
+    assert_eq!(diagnostics.len(), 1);
+    assert!(diagnostics[0].message.contains("Did you mean 'Service'?"));
+    assert_eq!(diagnostics[0].range.start, Position::new(0, 1));
+    assert_eq!(diagnostics[0].range.end, Position::new(0, 8));
+
+    // No suggestion for names far from any section
+    let diagnostics = diagnose("[Frobnicate]\n", &Config::default());
+    assert_eq!(diagnostics[0].message, "Unknown section [Frobnicate]");
+    assert!(diagnostics[0].data.is_none());
+}
+
+#[test]
+fn test_sections_depend_on_unit_type() {
+    let content = "[Unit]\nDescription=Test\n\n[Service]\nType=simple\n";
+    let document = Document::new(content, 1);
+    let config = Config::default();
+
+    assert!(generate_diagnostics(&document, Some(UnitType::Service), &config).is_empty());
+    let diagnostics = generate_diagnostics(&document, Some(UnitType::Socket), &config);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+    assert!(
+        diagnostics[0]
+            .message
+            .starts_with("Section [Service] is not valid in .socket units")
+    );
+}
+
+#[test]
+fn test_value_errors_point_at_the_value() {
+    let content =
+        "[Service]\nRestartSec=5min 30x\nMemoryMax=512M\nCPUQuota=150%\nRemainAfterExit=maybe\n";
+    let diagnostics = diagnose(content, &Config::default());
+
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(diagnostics[0].range.start, Position::new(1, 16));
+    assert_eq!(diagnostics[0].range.end, Position::new(1, 19));
+    assert!(diagnostics[0].message.ends_with("in RestartSec="));
+    assert_eq!(diagnostics[1].range.start, Position::new(4, 16));
+    assert!(
+        diagnostics[1]
+            .message
+            .starts_with("Invalid boolean 'maybe'")
+    );
+}
+
+#[test]
+fn test_invalid_calendar_expression() {
+    let content = "[Timer]\nOnCalendar=Mon..Fry *-*-* 02:00\nOnCalendar=weekly\n";
+    let diagnostics = diagnose(content, &Config::default());
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].range.start, Position::new(1, 16));
+    assert_eq!(diagnostics[0].range.end, Position::new(1, 19));
+    assert!(diagnostics[0].message.starts_with("Invalid weekday 'Fry'"));
+}
+
+#[test]
+fn test_empty_assignment_resets_lists() {
+    let content = "[Service]\nExecStart=\nExecStart=/usr/bin/true\nUser=\n";
+    let diagnostics = diagnose(content, &Config::default());
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].range.start.line, 3);
+    assert_eq!(
+        Fix::all_from_data(diagnostics[0].data.as_ref().unwrap()),
+        [Fix::DeleteAssignment {
+            range: Range::new(Position::new(3, 0), Position::new(4, 0))
+        }]
+    );
+
+    // Without a final newline the deletion stops at the end of the file;
+    // settings with a default can be set to it instead
+    let diagnostics = diagnose("[Service]\nType=", &Config::default());
+    assert_eq!(
+        Fix::all_from_data(diagnostics[0].data.as_ref().unwrap()),
+        [
+            Fix::DeleteAssignment {
+                range: Range::new(Position::new(1, 0), Position::new(1, 5))
+            },
+            Fix::SetDefault {
+                position: Position::new(1, 5),
+                key: "Type".to_string(),
+                value: "simple".to_string()
+            }
+        ]
+    );
+}
+
+#[test]
+fn test_dropin_exec_start_without_reset() {
+    let base = Document::new("[Service]\nExecStart=/usr/bin/app\n", 0);
+    let dropin = Document::new("[Service]\nExecStart=/usr/bin/app --debug\n", 0);
+
+    let diagnostics = dropin_diagnostics(&dropin, "app.service", Some(&base));
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].range.start, Position::new(1, 0));
+    assert!(diagnostics[0].message.contains("for app.service"));
+    assert_eq!(
+        Fix::from_data(diagnostics[0].data.as_ref().unwrap()).unwrap(),
+        Fix::InsertReset {
+            position: Position::new(1, 0),
+            key: "ExecStart".to_string()
+        }
+    );
+
+    let reset = Document::new("[Service]\nExecStart=\nExecStart=/usr/bin/app --debug\n", 0);
+    assert!(dropin_diagnostics(&reset, "app.service", Some(&base)).is_empty());
+
+    let oneshot = Document::new("[Service]\nType=oneshot\nExecStart=/usr/bin/app\n", 0);
+    assert!(dropin_diagnostics(&dropin, "app.service", Some(&oneshot)).is_empty());
+    assert!(dropin_diagnostics(&dropin, "app.service", None).is_empty());
+}
+
+#[test]
+fn test_relative_command_resolved_from_path() {
+    let dir = std::env::temp_dir().join(format!("systemd-ls-path-{}", std::process::id()));
+    let bin = dir.join("bin");
+    std::fs::create_dir_all(&bin).unwrap();
+    std::fs::write(bin.join("app"), "#!/bin/sh\n").unwrap();
+    std::fs::write(bin.join("data"), "").unwrap();
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let executable = std::fs::Permissions::from_mode(0o755);
+        std::fs::set_permissions(bin.join("app"), executable).unwrap();
+    }
+
+    let search_path = std::env::join_paths([dir.join("missing"), bin.clone()]).unwrap();
+    assert_eq!(find_executable("app", &search_path), Some(bin.join("app")));
+    assert_eq!(find_executable("bin/app", &search_path), None);
+    #[cfg(unix)]
+    assert_eq!(find_executable("data", &search_path), None);
+    std::fs::remove_dir_all(&dir).unwrap();
+
+    // Bare names systemd 239 resolves are fine, unless they can't be found
+    assert!(diagnose("[Service]\nExecStart=-sh -c true\n", &Config::default()).is_empty());
+    let diagnostics = diagnose(
+        "[Service]\nExecStart=no-such-command-here\n",
+        &Config::default(),
+    );
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::HINT));
+    assert!(diagnostics[0].data.is_none());
+
+    let config = Config {
+        target_systemd_version: Some(237),
+        ..Default::default()
+    };
+    let diagnostics = diagnose("[Service]\nExecStart=-sh -c true\n", &config);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::WARNING));
+    let Some(Fix::Replace { range, text }) = Fix::from_data(diagnostics[0].data.as_ref().unwrap())
+    else {
+        panic!("expected a replacement");
+    };
+    assert!(text.starts_with('/') && text.ends_with("/sh"));
+    assert_eq!(
+        range,
+        Range::new(Position::new(1, 11), Position::new(1, 13))
+    );
+}
+
+#[test]
+fn test_exec_command_lines() {
+    let content = "[Service]\nExecStartPre=@/bin/app app-argv0 ; +-/bin/true\nExecStart=!!/usr/bin/app $OPTS\nExecStop=/bin/kill \"$MAINPID\nExecReload=bin/app --reload\n";
+    let diagnostics = diagnose(content, &Config::default());
+    let ranges: Vec<(Range, &str)> = diagnostics
+        .iter()
+        .map(|d| (d.range, d.message.as_str()))
+        .collect();
+    assert_eq!(diagnostics.len(), 2, "{:?}", ranges);
+    assert_eq!(
+        diagnostics[0].range,
+        Range::new(Position::new(3, 19), Position::new(3, 28))
+    );
+    assert!(diagnostics[0].message.contains("Unterminated"));
+    assert_eq!(
+        diagnostics[1].range,
+        Range::new(Position::new(4, 11), Position::new(4, 18))
+    );
+    assert_eq!(diagnostics[1].severity, Some(DiagnosticSeverity::ERROR));
+
+    // Bare names need systemd 239
+    let config = Config {
+        target_systemd_version: Some(237),
+        ..Default::default()
+    };
+    let diagnostics = diagnose("[Service]\nExecStart=app\n", &config);
+    assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::WARNING));
+}
+
+#[test]
+fn test_specifier_diagnostics() {
+    let content =
+        "[Unit]\nDescription=%n at 100%\n[Service]\nExecStart=/usr/bin/app %z %%\nCPUQuota=50%\n";
+    let diagnostics = diagnose(content, &Config::default());
+    assert_eq!(diagnostics.len(), 2, "{:?}", diagnostics);
+
+    assert_eq!(
+        diagnostics[0].range,
+        Range::new(Position::new(1, 21), Position::new(1, 22))
+    );
+    let Some(Fix::Replace { text, .. }) = Fix::from_data(diagnostics[0].data.as_ref().unwrap())
+    else {
+        panic!("expected a replacement");
+    };
+    assert_eq!(text, "%%");
+
+    assert_eq!(
+        diagnostics[1].message,
+        "Unknown specifier '%z' in ExecStart="
+    );
+    assert_eq!(
+        diagnostics[1].range,
+        Range::new(Position::new(3, 23), Position::new(3, 25))
+    );
+}
+
+#[test]
+fn test_template_diagnostics() {
+    let document = Document::new(
+        "[Service]\nExecStart=/usr/bin/app --name=%i\n[Install]\nDefaultInstance=main\n",
+        0,
+    );
+    let diagnostics = template_diagnostics(&document, "app.service");
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(
+        diagnostics[0].range,
+        Range::new(Position::new(1, 30), Position::new(1, 32))
+    );
+    assert!(
+        diagnostics[1]
+            .message
+            .starts_with("DefaultInstance= only applies")
+    );
+
+    assert!(template_diagnostics(&document, "app@.service").is_empty());
+    assert!(template_diagnostics(&document, "app@main.service").is_empty());
+
+    let invalid = Document::new("[Install]\nDefaultInstance=a b\n", 0);
+    let diagnostics = template_diagnostics(&invalid, "app@.service");
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(
+        diagnostics[0].range,
+        Range::new(Position::new(1, 16), Position::new(1, 19))
+    );
+}
@@ -0,0 +1,99 @@
+use systemd_language_server::document::{Document, DocumentStore};
+use tower_lsp::lsp_types::{Position, Range, TextDocumentContentChangeEvent, Url};
+
+fn edit(start: (u32, u32), end: (u32, u32), text: &str) -> TextDocumentContentChangeEvent {
+    TextDocumentContentChangeEvent {
+        range: Some(Range::new(
+            Position::new(start.0, start.1),
+            Position::new(end.0, end.1),
+        )),
+        range_length: None,
+        text: text.to_string(),
+    }
+}
+
+#[test]
+fn test_incremental_changes_applied_in_order() {
+    let document = Document::new("[Unit]\nDescription=Test\n", 1);
+
+    let changes = vec![
+        // Replace "Test" with "My Service"
+        edit((1, 12), (1, 16), "My Service"),
+        // Then insert a new line that relies on the previous edit's result
+        edit((2, 0), (2, 0), "After=network.target\n"),
+    ];
+    let document = document.apply_changes(&changes, 2);
+
+    assert_eq!(
+        document.text(),
+        "[Unit]\nDescription=My Service\nAfter=network.target\n"
+    );
+    assert_eq!(document.version, 2);
+    assert_eq!(document.unit.get("Unit", "After"), Some("network.target"));
+}
+
+#[test]
+fn test_incremental_change_with_utf16_positions() {
+    // '☕' is one UTF-16 unit, '𝄞' is two
+    let document = Document::new("[Unit]\nDescription=☕𝄞x\n", 1);
+
+    let document = document.apply_changes(&[edit((1, 15), (1, 16), "y")], 2);
+
+    assert_eq!(document.text(), "[Unit]\nDescription=☕𝄞y\n");
+}
+
+#[test]
+fn test_reversed_range_is_normalized() {
+    let document = Document::new("[Unit]\nDescription=Test\n", 1);
+
+    let document = document.apply_changes(&[edit((1, 16), (1, 12), "Other")], 2);
+
+    assert_eq!(document.text(), "[Unit]\nDescription=Other\n");
+}
+
+#[test]
+fn test_ranged_change_after_full_change() {
+    let document = Document::new("[Unit]\n", 1);
+    let full = TextDocumentContentChangeEvent {
+        range: None,
+        range_length: None,
+        text: "[Service]\nType=simple\n".to_string(),
+    };
+
+    // The ranged edit addresses the replaced text, not the original
+    let document = document.apply_changes(&[full, edit((1, 5), (1, 11), "oneshot")], 2);
+
+    assert_eq!(document.text(), "[Service]\nType=oneshot\n");
+}
+
+#[test]
+fn test_full_change_replaces_text() {
+    let document = Document::new("[Unit]\n", 1);
+    let change = TextDocumentContentChangeEvent {
+        range: None,
+        range_length: None,
+        text: "[Service]\n".to_string(),
+    };
+
+    let document = document.apply_changes(&[change], 2);
+
+    assert_eq!(document.text(), "[Service]\n");
+}
+
+#[test]
+fn test_store_ignores_stale_versions() {
+    let store = DocumentStore::new();
+    let uri = Url::parse("file:///tmp/test.service").unwrap();
+    store.insert(uri.clone(), "[Unit]\n", 5);
+
+    assert!(
+        store
+            .update(&uri, &[edit((0, 0), (0, 0), "#")], 4)
+            .is_none()
+    );
+    assert_eq!(store.get(&uri).unwrap().text(), "[Unit]\n");
+
+    let updated = store.update(&uri, &[edit((0, 0), (0, 0), "#")], 6).unwrap();
+    assert_eq!(updated.text(), "#[Unit]\n");
+    assert_eq!(updated.version, 6);
+}
@@ -0,0 +1,89 @@
+use systemd_language_server::exec::parse;
+use systemd_language_server::parser::Span;
+
+#[test]
+fn test_prefixes_and_argv0() {
+    let line = parse("@-/bin/foo argv0 --bar");
+    assert!(line.errors.is_empty(), "{:?}", line.errors);
+    let command = &line.commands[0];
+    assert_eq!(command.prefixes, "@-");
+    assert_eq!(command.prefix_span, Span::new(0, 2));
+    let executable = command.executable.as_ref().unwrap();
+    assert_eq!(executable.text, "/bin/foo");
+    assert_eq!(executable.span, Span::new(2, 10));
+    let arguments: Vec<&str> = command.arguments.iter().map(|a| a.text.as_str()).collect();
+    assert_eq!(arguments, ["argv0", "--bar"]);
+
+    for valid in [
+        "+/bin/foo",
+        "!/bin/foo",
+        "!!/bin/foo",
+        ":-!!/bin/foo",
+        "!-!/bin/foo",
+    ] {
+        assert!(parse(valid).errors.is_empty(), "{}", valid);
+    }
+
+    let errors = parse("-+!/bin/foo").errors;
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].span, Span::new(2, 3));
+    assert_eq!(parse("--/bin/foo").errors[0].span, Span::new(1, 2));
+    assert_eq!(parse("!!!/bin/foo").errors[0].span, Span::new(2, 3));
+
+    // `@` without argv[0]
+    assert_eq!(parse("@/bin/foo").errors[0].span, Span::new(1, 9));
+    assert_eq!(parse("-").errors[0].span, Span::new(0, 1));
+}
+
+#[test]
+fn test_quoting_and_escapes() {
+    let line = parse(r#"/bin/echo "a b" 'c\'d' e"f g"h \x41\t\s"#);
+    assert!(line.errors.is_empty(), "{:?}", line.errors);
+    let arguments: Vec<&str> = line.commands[0]
+        .arguments
+        .iter()
+        .map(|a| a.text.as_str())
+        .collect();
+    assert_eq!(arguments, ["a b", "c'd", "ef gh", "A\t "]);
+
+    let line = parse(r#"/bin/echo "open"#);
+    assert_eq!(line.errors.len(), 1);
+    assert_eq!(line.errors[0].span, Span::new(10, 15));
+
+    let line = parse(r"/bin/echo a\qb \x4");
+    assert_eq!(line.errors.len(), 2);
+    assert_eq!(line.errors[0].span, Span::new(11, 13));
+    assert_eq!(line.errors[1].span, Span::new(15, 18));
+}
+
+#[test]
+fn test_command_separators() {
+    let line = parse(r"/bin/a x ; -/bin/b \; y");
+    assert!(line.errors.is_empty(), "{:?}", line.errors);
+    assert_eq!(line.commands.len(), 2);
+    assert_eq!(line.separators, [Span::new(9, 10)]);
+    let second = &line.commands[1];
+    assert_eq!(second.prefixes, "-");
+    let arguments: Vec<&str> = second.arguments.iter().map(|a| a.text.as_str()).collect();
+    assert_eq!(arguments, [";", "y"]);
+
+    assert_eq!(parse("; /bin/a").errors[0].span, Span::new(0, 1));
+}
+
+#[test]
+fn test_variables_and_specifiers() {
+    let line = parse("/bin/app $OPTS --dir=${DIR}/x $$HOME --name=%n 100%%");
+    assert!(line.errors.is_empty(), "{:?}", line.errors);
+    let arguments = &line.commands[0].arguments;
+    assert_eq!(arguments[0].variables[0].name, "OPTS");
+    assert!(arguments[0].variables[0].split);
+    assert_eq!(arguments[1].variables[0].span, Span::new(21, 27));
+    assert!(!arguments[1].variables[0].split);
+    assert!(arguments[2].variables.is_empty());
+    assert_eq!(arguments[3].specifiers, [Span::new(44, 46)]);
+    assert!(arguments[4].specifiers.is_empty());
+
+    // The executable can't be a variable, unless expansion is off
+    assert_eq!(parse("${APP} --x").errors[0].span, Span::new(0, 6));
+    assert!(parse(":${APP} --x").errors.is_empty());
+}
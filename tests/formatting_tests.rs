@@ -0,0 +1,88 @@
+use systemd_language_server::config::FormattingConfig;
+use systemd_language_server::document::Document;
+use systemd_language_server::formatting::{edits, format};
+use tower_lsp::lsp_types::{Position, Range};
+
+const SORTED: FormattingConfig = FormattingConfig {
+    sort_sections: true,
+};
+
+#[test]
+fn test_format_normalizes_spacing_and_blank_lines() {
+    let text = "# Managed by deploy\n\n\n[Unit]  \nDescription = Web server   \n\n\n\nAfter=network.target\n[ Service ]\nExecStart = /usr/bin/app \\\n    --port 8080   \\\n    --verbose\n\n";
+    let expected = "# Managed by deploy\n\n[Unit]\nDescription=Web server\n\nAfter=network.target\n\n[Service]\nExecStart=/usr/bin/app \\\n    --port 8080   \\\n    --verbose\n";
+
+    assert_eq!(format(text, &FormattingConfig::default()), expected);
+    assert_eq!(format(expected, &FormattingConfig::default()), expected);
+}
+
+#[test]
+fn test_format_sorts_sections_with_their_comments() {
+    let text = "[Install]\nWantedBy=multi-user.target\n\n# What runs\n[Service]\n# The daemon\nExecStart=/usr/bin/app\n[Unit]\nDescription=App\n";
+    let expected = "[Unit]\nDescription=App\n\n# What runs\n[Service]\n# The daemon\nExecStart=/usr/bin/app\n\n[Install]\nWantedBy=multi-user.target\n";
+
+    assert_eq!(format(text, &SORTED), expected);
+    assert!(format(text, &FormattingConfig::default()).starts_with("[Install]\n"));
+}
+
+#[test]
+fn test_format_keeps_comments_above_the_first_header() {
+    let text = "# Managed by deploy\n\n# The app\n[Unit]\nDescription=App\n";
+    assert_eq!(format(text, &FormattingConfig::default()), text);
+
+    let text = "# Install first\n[Install]\nWantedBy=x.target\n[Unit]\nDescription=App\n";
+    let expected = "[Unit]\nDescription=App\n\n# Install first\n[Install]\nWantedBy=x.target\n";
+    assert_eq!(format(text, &SORTED), expected);
+}
+
+#[test]
+fn test_range_formatting_only_touches_the_range() {
+    let document = Document::new(
+        "[Unit]\nDescription = A\nAfter = b.target\n[Install]\nWantedBy=x.target",
+        0,
+    );
+
+    let all = edits(&document, &SORTED, None);
+    let text: Vec<&str> = all.iter().map(|edit| edit.new_text.as_str()).collect();
+    assert_eq!(text, ["Description=A\nAfter=b.target\n\n", "\n"]);
+    assert_eq!(
+        all[0].range,
+        Range::new(Position::new(1, 0), Position::new(3, 0))
+    );
+    assert_eq!(
+        all[1].range,
+        Range::new(Position::new(4, 17), Position::new(4, 17))
+    );
+
+    let range = Range::new(Position::new(2, 0), Position::new(2, 3));
+    let partial = edits(&document, &SORTED, Some(range));
+    assert_eq!(partial.len(), 1);
+    assert_eq!(partial[0].range.start, Position::new(1, 0));
+}
+
+#[test]
+fn test_edits_for_large_files_cover_only_the_changes() {
+    let mut text = String::from("[Service]\n");
+    for i in 0..5000 {
+        text.push_str(&format!("Environment=VAR{}=1\n", i));
+    }
+    text.push_str("Nice = 5\n");
+    let document = Document::new(&text, 0);
+
+    let all = edits(&document, &SORTED, None);
+    assert_eq!(all.len(), 1);
+    assert_eq!(all[0].new_text, "Nice=5\n");
+    assert_eq!(all[0].range.start, Position::new(5001, 0));
+}
+
+#[test]
+fn test_edits_keep_crlf_and_skip_hunks_next_to_the_range() {
+    let document = Document::new("[Unit]\r\nDescription = A\r\nAfter=b.target\r\n", 0);
+    let all = edits(&document, &SORTED, None);
+    assert_eq!(all.len(), 1);
+    assert_eq!(all[0].new_text, "Description=A\r\n");
+
+    // The hunk on line 1 ends right before a range starting on line 2
+    let range = Range::new(Position::new(2, 0), Position::new(2, 3));
+    assert!(edits(&document, &SORTED, Some(range)).is_empty());
+}
@@ -1,9 +1,710 @@
-// Simplified integration test
-// Due to environment limitations, we're not performing complex integration tests at this time
-// This file can be expanded in the future as needed
-
-#[test]
-fn test_integration_placeholder() {
-    // This is a placeholder test to ensure the integration test file compiles
-    assert!(true);
+// Integration tests driving the language server through its LSP handlers
+use systemd_language_server::Backend;
+use systemd_language_server::dropins::EffectiveUnitParams;
+use systemd_language_server::graph::{DependencyGraphParams, GraphFormat, GraphOutput};
+use tower_lsp::lsp_types::*;
+use tower_lsp::{LanguageServer, LspService};
+
+const SERVICE: &str = "[Unit]\nDescription=Test\n\n[Service]\nType=simple\n";
+
+async fn open(backend: &Backend, uri: &Url, text: &str) {
+    backend
+        .did_open(DidOpenTextDocumentParams {
+            text_document: TextDocumentItem::new(uri.clone(), "systemd".into(), 1, text.into()),
+        })
+        .await;
+}
+
+async fn completion_labels(backend: &Backend, uri: &Url, line: u32, character: u32) -> Vec<String> {
+    let response = backend
+        .completion(CompletionParams {
+            text_document_position: TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(uri.clone()),
+                Position::new(line, character),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: None,
+        })
+        .await
+        .unwrap();
+
+    match response {
+        Some(CompletionResponse::Array(items)) => items.into_iter().map(|i| i.label).collect(),
+        _ => vec![],
+    }
+}
+
+async fn hover(backend: &Backend, uri: &Url, line: u32, character: u32) -> Option<Hover> {
+    backend
+        .hover(HoverParams {
+            text_document_position_params: TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(uri.clone()),
+                Position::new(line, character),
+            ),
+            work_done_progress_params: Default::default(),
+        })
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn test_completion_uses_opened_document() {
+    let (service, _) = LspService::new(Backend::new);
+    let uri = Url::parse("file:///tmp/test.service").unwrap();
+    open(service.inner(), &uri, SERVICE).await;
+
+    let labels = completion_labels(service.inner(), &uri, 4, 0).await;
+    assert!(labels.contains(&"ExecStart=".to_string()));
+}
+
+#[tokio::test]
+async fn test_hover_uses_opened_document() {
+    let (service, _) = LspService::new(Backend::new);
+    let uri = Url::parse("file:///tmp/test.service").unwrap();
+    open(service.inner(), &uri, SERVICE).await;
+
+    assert!(hover(service.inner(), &uri, 4, 1).await.is_some());
+}
+
+#[tokio::test]
+async fn test_document_updates_are_persisted() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let uri = Url::parse("file:///tmp/test.service").unwrap();
+    open(backend, &uri, "[Unit]\n").await;
+
+    backend
+        .did_change(DidChangeTextDocumentParams {
+            text_document: VersionedTextDocumentIdentifier::new(uri.clone(), 2),
+            content_changes: vec![TextDocumentContentChangeEvent {
+                range: None,
+                range_length: None,
+                text: SERVICE.into(),
+            }],
+        })
+        .await;
+    assert!(hover(backend, &uri, 4, 1).await.is_some());
+
+    backend
+        .did_close(DidCloseTextDocumentParams {
+            text_document: TextDocumentIdentifier::new(uri.clone()),
+        })
+        .await;
+    assert!(hover(backend, &uri, 4, 1).await.is_none());
+    assert!(completion_labels(backend, &uri, 4, 0).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_completion_hides_directives_newer_than_target() {
+    let root = std::env::temp_dir().join(format!("systemd-ls-target-{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(
+        root.join(".systemd-ls.json"),
+        r#"{"systemd": {"targetSystemdVersion": 239}}"#,
+    )
+    .unwrap();
+
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    backend
+        .initialize(InitializeParams {
+            root_uri: Some(Url::from_directory_path(&root).unwrap()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let uri = Url::parse("file:///tmp/test.service").unwrap();
+    open(backend, &uri, SERVICE).await;
+    let labels = completion_labels(backend, &uri, 4, 0).await;
+
+    assert!(labels.contains(&"MemoryMax=".to_string()));
+    assert!(!labels.contains(&"MemoryZSwapMax=".to_string()));
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn test_code_action_fixes_unknown_key() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let uri = Url::parse("file:///tmp/test.service").unwrap();
+    let diagnostics = systemd_language_server::diagnostics::generate_diagnostics(
+        &systemd_language_server::document::Document::new("[Service]\nRestrat=always\n", 1),
+        None,
+        &Default::default(),
+    );
+
+    let actions = backend
+        .code_action(CodeActionParams {
+            text_document: TextDocumentIdentifier::new(uri.clone()),
+            range: diagnostics[0].range,
+            context: CodeActionContext {
+                diagnostics,
+                only: None,
+                trigger_kind: None,
+            },
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    let CodeActionOrCommand::CodeAction(action) = &actions[0] else {
+        panic!("expected a code action");
+    };
+    assert_eq!(action.title, "Change to 'Restart'");
+    let edits = &action.edit.as_ref().unwrap().changes.as_ref().unwrap()[&uri];
+    assert_eq!(edits[0].new_text, "Restart");
+}
+
+#[tokio::test]
+async fn test_calendar_preview_in_hover_and_inlay_hints() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let uri = Url::parse("file:///tmp/backup.timer").unwrap();
+    open(backend, &uri, "[Timer]\nOnCalendar=Mon..Fri 2:00 UTC\n").await;
+
+    let Some(Hover {
+        contents: HoverContents::Markup(markup),
+        ..
+    }) = hover(backend, &uri, 1, 14).await
+    else {
+        panic!("expected a hover");
+    };
+    assert!(markup.value.contains("`Mon..Fri *-*-* 02:00:00 UTC`"));
+    assert_eq!(markup.value.matches("\n- ").count(), 5);
+
+    let hints = backend
+        .inlay_hint(InlayHintParams {
+            text_document: TextDocumentIdentifier::new(uri.clone()),
+            range: Range::new(Position::new(0, 0), Position::new(2, 0)),
+            work_done_progress_params: Default::default(),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(hints.len(), 1);
+    assert_eq!(hints[0].position, Position::new(1, 28));
+    let InlayHintLabel::String(label) = &hints[0].label else {
+        panic!("expected a string label");
+    };
+    assert!(label.starts_with("Mon..Fri *-*-* 02:00:00 UTC (next: "));
+}
+
+#[tokio::test]
+async fn test_goto_definition_of_unit_reference() {
+    let root = std::env::temp_dir().join(format!("systemd-ls-definition-{}", std::process::id()));
+    let search_path = root.join("system");
+    std::fs::create_dir_all(&search_path).unwrap();
+    std::fs::write(search_path.join("network-online.target"), "[Unit]\n").unwrap();
+    std::fs::write(
+        root.join(".systemd-ls.json"),
+        format!(
+            r#"{{"systemd": {{"unitSearchPaths": [{:?}]}}}}"#,
+            search_path.to_str().unwrap()
+        ),
+    )
+    .unwrap();
+
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    backend
+        .initialize(InitializeParams {
+            root_uri: Some(Url::from_directory_path(&root).unwrap()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+
+    let uri = Url::from_file_path(root.join("app.service")).unwrap();
+    open(backend, &uri, "[Unit]\nAfter=network-online.target\n").await;
+    let definition = |character| {
+        backend.goto_definition(GotoDefinitionParams {
+            text_document_position_params: TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(uri.clone()),
+                Position::new(1, character),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+    };
+
+    let Some(GotoDefinitionResponse::Scalar(location)) = definition(10).await.unwrap() else {
+        panic!("expected a location");
+    };
+    assert_eq!(
+        location.uri.to_file_path().unwrap(),
+        search_path.join("network-online.target")
+    );
+    assert!(definition(2).await.unwrap().is_none());
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn test_references_to_unit() {
+    let root = std::env::temp_dir().join(format!("systemd-ls-references-{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("app-db.service"), "[Unit]\nDescription=DB\n").unwrap();
+    std::fs::write(root.join("app.service"), "[Unit]\nBindsTo=app-db.service\n").unwrap();
+
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    backend
+        .initialize(InitializeParams {
+            root_uri: Some(Url::from_directory_path(&root).unwrap()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    backend.initialized(InitializedParams {}).await;
+
+    // With the cursor outside a unit name, references go to the document's unit
+    let uri = Url::from_file_path(root.join("app-db.service")).unwrap();
+    open(backend, &uri, "[Unit]\nDescription=DB\n").await;
+    let locations = backend
+        .references(ReferenceParams {
+            text_document_position: TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(uri.clone()),
+                Position::new(0, 1),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: ReferenceContext {
+                include_declaration: true,
+            },
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(locations.len(), 2);
+    assert_eq!(locations[0].uri, uri);
+    assert_eq!(
+        locations[1].uri,
+        Url::from_file_path(root.join("app.service")).unwrap()
+    );
+    assert_eq!(locations[1].range.start, Position::new(1, 8));
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn test_prepare_rename_on_unit_name() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let uri = Url::parse("file:///tmp/app.service").unwrap();
+    open(backend, &uri, "[Unit]\nAfter=network.target\n").await;
+    let prepare = |line, character| {
+        backend.prepare_rename(TextDocumentPositionParams::new(
+            TextDocumentIdentifier::new(uri.clone()),
+            Position::new(line, character),
+        ))
+    };
+
+    let Some(PrepareRenameResponse::Range(range)) = prepare(1, 8).await.unwrap() else {
+        panic!("expected a range");
+    };
+    assert_eq!(range, Range::new(Position::new(1, 6), Position::new(1, 20)));
+
+    // Elsewhere the unit the file defines is renamed
+    let Some(PrepareRenameResponse::RangeWithPlaceholder { range, placeholder }) =
+        prepare(0, 1).await.unwrap()
+    else {
+        panic!("expected the unit name as placeholder");
+    };
+    assert_eq!(range, Range::new(Position::new(0, 1), Position::new(0, 1)));
+    assert_eq!(placeholder, "app.service");
+
+    let dropin = Url::parse("file:///tmp/app.service.d/override.conf").unwrap();
+    open(backend, &dropin, "[Service]\nNice=5\n").await;
+    let prepare = backend.prepare_rename(TextDocumentPositionParams::new(
+        TextDocumentIdentifier::new(dropin),
+        Position::new(1, 1),
+    ));
+    assert!(prepare.await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn test_effective_unit_merges_dropins() {
+    let root = std::env::temp_dir().join(format!("systemd-ls-effective-{}", std::process::id()));
+    std::fs::create_dir_all(root.join("app.service.d")).unwrap();
+    std::fs::write(
+        root.join("app.service"),
+        "[Service]\nExecStart=/usr/bin/app\nRestart=no\n",
+    )
+    .unwrap();
+    std::fs::write(
+        root.join("app.service.d/restart.conf"),
+        "[Service]\nRestart=always\n",
+    )
+    .unwrap();
+
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let uri = Url::from_file_path(root.join("app.service.d/restart.conf")).unwrap();
+    open(backend, &uri, "[Service]\nRestart=on-failure\n").await;
+
+    let unit = backend
+        .effective_unit(EffectiveUnitParams {
+            text_document: TextDocumentIdentifier::new(uri.clone()),
+        })
+        .await
+        .unwrap()
+        .unwrap();
+
+    assert_eq!(unit.unit, "app.service");
+    assert_eq!(unit.files.len(), 2);
+    assert_eq!(unit.files[1], uri);
+    assert!(
+        unit.text
+            .ends_with("restart.conf\n[Service]\nRestart=on-failure\n")
+    );
+    let restart = &unit.sections[0].directives[1];
+    assert_eq!(
+        (restart.key.as_str(), restart.value.as_str()),
+        ("Restart", "on-failure")
+    );
+    assert_eq!(restart.uri, uri);
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn test_document_symbols_outline() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let uri = Url::parse("file:///tmp/outline.service").unwrap();
+    let text = "[Unit]\nDescription=Test\n\n[Service]\nExecStartPre=/bin/a\nExecStartPre=/bin/b\nExecStart=/bin/app\n[]\n";
+    open(backend, &uri, text).await;
+
+    let Some(DocumentSymbolResponse::Nested(symbols)) = backend
+        .document_symbol(DocumentSymbolParams {
+            text_document: TextDocumentIdentifier::new(uri),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .await
+        .unwrap()
+    else {
+        panic!("expected nested symbols");
+    };
+
+    let names: Vec<&str> = symbols.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(names, ["Unit", "Service", "[]"]);
+    assert_eq!(symbols[1].kind, SymbolKind::NAMESPACE);
+    assert_eq!(
+        symbols[1].selection_range,
+        Range::new(Position::new(3, 1), Position::new(3, 8))
+    );
+
+    let children = symbols[1].children.as_ref().unwrap();
+    let keys: Vec<&str> = children.iter().map(|s| s.name.as_str()).collect();
+    assert_eq!(keys, ["ExecStartPre", "ExecStartPre", "ExecStart"]);
+    assert_eq!(children[1].kind, SymbolKind::PROPERTY);
+    assert_eq!(children[1].detail.as_deref(), Some("/bin/b"));
+    assert_eq!(children[1].range.start, Position::new(5, 0));
+    assert_eq!(
+        children[1].selection_range,
+        Range::new(Position::new(5, 0), Position::new(5, 12))
+    );
+}
+
+#[tokio::test]
+async fn test_semantic_tokens() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let uri = Url::parse("file:///tmp/tokens.service").unwrap();
+    let text = "[Unit]\nAfter=%i.target\n[Service]\nExecStart=-/bin/app $OPTS\nMemoryLimit=512M\nX-Foo=yes\n";
+    open(backend, &uri, text).await;
+
+    let Some(SemanticTokensResult::Tokens(tokens)) = backend
+        .semantic_tokens_full(SemanticTokensParams {
+            text_document: TextDocumentIdentifier::new(uri.clone()),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .await
+        .unwrap()
+    else {
+        panic!("expected tokens");
+    };
+
+    // Resolve the relative encoding to (line, start, length, type, modifiers)
+    let legend = systemd_language_server::semantic_tokens::legend();
+    let (mut line, mut start) = (0, 0);
+    let decoded: Vec<(u32, u32, u32, &str, u32)> = tokens
+        .data
+        .iter()
+        .map(|token| {
+            if token.delta_line > 0 {
+                start = 0;
+            }
+            line += token.delta_line;
+            start += token.delta_start;
+            (
+                line,
+                start,
+                token.length,
+                legend.token_types[token.token_type as usize].as_str(),
+                token.token_modifiers_bitset,
+            )
+        })
+        .collect();
+
+    assert_eq!(
+        decoded,
+        [
+            (0, 1, 4, "namespace", 0),
+            (1, 0, 5, "property", 0),
+            (1, 6, 2, "macro", 0),
+            (1, 8, 7, "type", 0),
+            (2, 1, 7, "namespace", 0),
+            (3, 0, 9, "property", 0),
+            (3, 10, 1, "operator", 0),
+            (3, 11, 8, "string", 0),
+            (3, 20, 5, "parameter", 0),
+            (4, 0, 11, "property", 1),
+            (4, 12, 3, "number", 0),
+            (4, 15, 1, "keyword", 0),
+            (5, 0, 5, "variable", 0),
+        ]
+    );
+
+    let Some(SemanticTokensRangeResult::Tokens(range)) = backend
+        .semantic_tokens_range(SemanticTokensRangeParams {
+            text_document: TextDocumentIdentifier::new(uri),
+            range: Range::new(Position::new(4, 0), Position::new(4, 5)),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+        })
+        .await
+        .unwrap()
+    else {
+        panic!("expected tokens");
+    };
+    assert_eq!(range.data.len(), 1);
+    assert_eq!(range.data[0].delta_line, 4);
+}
+
+#[tokio::test]
+async fn test_value_completion() {
+    let root = std::env::temp_dir().join(format!("systemd-ls-values-{}", std::process::id()));
+    std::fs::create_dir_all(&root).unwrap();
+    std::fs::write(root.join("app-db.service"), "[Unit]\nDescription=DB\n").unwrap();
+
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    backend
+        .initialize(InitializeParams {
+            root_uri: Some(Url::from_directory_path(&root).unwrap()),
+            ..Default::default()
+        })
+        .await
+        .unwrap();
+    backend.initialized(InitializedParams {}).await;
+
+    let uri = Url::from_file_path(root.join("app.service")).unwrap();
+    let text = "[Unit]\nAfter=network.target app\n[Service]\nType=no\nRemainAfterExit=\n";
+    open(backend, &uri, text).await;
+
+    let types = completion_labels(backend, &uri, 3, 7).await;
+    assert!(types.contains(&"notify".to_string()));
+    assert!(!types.contains(&"ExecStart=".to_string()));
+    assert_eq!(completion_labels(backend, &uri, 4, 16).await, ["yes", "no"]);
+
+    let units = completion_labels(backend, &uri, 1, 24).await;
+    assert!(units.contains(&"app-db.service".to_string()));
+    assert!(units.contains(&"multi-user.target".to_string()));
+    assert!(!units.contains(&"network.target".to_string()));
+
+    // The name under the cursor is replaced, and values carry documentation
+    let Some(CompletionResponse::Array(items)) = backend
+        .completion(CompletionParams {
+            text_document_position: TextDocumentPositionParams::new(
+                TextDocumentIdentifier::new(uri.clone()),
+                Position::new(3, 7),
+            ),
+            work_done_progress_params: Default::default(),
+            partial_result_params: Default::default(),
+            context: None,
+        })
+        .await
+        .unwrap()
+    else {
+        panic!("expected completion items");
+    };
+    let notify = items.iter().find(|item| item.label == "notify").unwrap();
+    assert!(notify.documentation.is_some());
+    let Some(CompletionTextEdit::Edit(edit)) = &notify.text_edit else {
+        panic!("expected a text edit");
+    };
+    assert_eq!(
+        edit.range,
+        Range::new(Position::new(3, 5), Position::new(3, 7))
+    );
+    std::fs::remove_dir_all(&root).unwrap();
+}
+
+#[tokio::test]
+async fn test_section_completion_depends_on_unit_type() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let sections = |name: &'static str| async move {
+        let uri = Url::parse(&format!("file:///tmp/{}", name)).unwrap();
+        open(backend, &uri, "[").await;
+        completion_labels(backend, &uri, 0, 1).await
+    };
+
+    assert_eq!(
+        sections("backup.timer").await,
+        ["Unit]", "Install]", "Timer]"]
+    );
+    assert_eq!(sections("spool.path").await, ["Unit]", "Install]", "Path]"]);
+    assert_eq!(sections("app.target").await, ["Unit]", "Install]"]);
+    assert!(sections("notes.conf").await.contains(&"Swap]".to_string()));
+}
+
+#[tokio::test]
+async fn test_hover_renders_directive_and_value_docs() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let uri = Url::parse("file:///tmp/hover.service").unwrap();
+    open(
+        backend,
+        &uri,
+        "[Service]\nRestart=on-failure\nProtectSystem=strict\n",
+    )
+    .await;
+    let markdown = |hover: Option<Hover>| match hover.unwrap().contents {
+        HoverContents::Markup(content) => content.value,
+        _ => panic!("expected markdown"),
+    };
+
+    let key = markdown(hover(backend, &uri, 1, 2).await);
+    assert!(key.starts_with("**Restart=** · [systemd.service(5)](https://www.freedesktop.org/software/systemd/man/latest/systemd.service.html#Restart=)"));
+    assert!(key.contains("\n- `on-failure`: Restart when"));
+    assert!(key.contains("**Default:** `no`"));
+
+    let value = markdown(hover(backend, &uri, 1, 10).await);
+    assert!(
+        value.starts_with("**Restart=on-failure**\n\nRestart when the service exits uncleanly")
+    );
+
+    let exec = markdown(hover(backend, &uri, 2, 3).await);
+    assert!(exec.contains("[systemd.exec(5)]"));
+}
+
+#[tokio::test]
+async fn test_specifier_completion_and_hover() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let uri = Url::parse("file:///tmp/units/foo@bar-baz.service").unwrap();
+    open(
+        backend,
+        &uri,
+        "[Service]\nExecStart=/usr/bin/app --name=%i --dir=%f\nUser=%\nCPUQuota=50%\n",
+    )
+    .await;
+
+    let labels = completion_labels(backend, &uri, 2, 6).await;
+    for specifier in ["%n", "%i", "%H", "%y", "%%"] {
+        assert!(labels.contains(&specifier.to_string()), "{}", specifier);
+    }
+    // `%` is a percent sign in numbers
+    let labels = completion_labels(backend, &uri, 3, 12).await;
+    assert!(!labels.contains(&"%n".to_string()));
+
+    let markdown = |hover: Option<Hover>| match hover.unwrap().contents {
+        HoverContents::Markup(content) => content.value,
+        _ => panic!("expected markdown"),
+    };
+    let instance = markdown(hover(backend, &uri, 1, 32).await);
+    assert!(instance.starts_with("**%i**"));
+    assert!(instance.contains("**Expands to:** `bar-baz` in `foo@bar-baz.service`"));
+    let path = markdown(hover(backend, &uri, 1, 41).await);
+    assert!(path.contains("**Expands to:** `/bar/baz`"));
+}
+
+#[tokio::test]
+async fn test_template_instances_code_lens() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let template = Url::parse("file:///tmp/lens/worker@.service").unwrap();
+    let target = Url::parse("file:///tmp/lens/workers.target").unwrap();
+    open(backend, &template, "[Unit]\nDescription=Worker %i\n").await;
+    open(
+        backend,
+        &target,
+        "[Unit]\nWants=worker@b.service worker@a.service\nAfter=worker@a.service\n",
+    )
+    .await;
+
+    let lens = |uri: Url| async move {
+        backend
+            .code_lens(CodeLensParams {
+                text_document: TextDocumentIdentifier::new(uri),
+                work_done_progress_params: Default::default(),
+                partial_result_params: Default::default(),
+            })
+            .await
+            .unwrap()
+            .unwrap()
+    };
+    let lenses = lens(template.clone()).await;
+    assert_eq!(lenses.len(), 1);
+    assert_eq!(
+        lenses[0].command.as_ref().unwrap().title,
+        "2 instances: worker@a.service, worker@b.service"
+    );
+    assert_eq!(
+        lenses[0].range,
+        Range::new(Position::new(0, 0), Position::new(0, 6))
+    );
+    assert!(lens(target).await.is_empty());
+}
+
+#[tokio::test]
+async fn test_dependency_graph_request() {
+    let (service, _) = LspService::new(Backend::new);
+    let backend = service.inner();
+    let app = Url::parse("file:///tmp/deps/app.service").unwrap();
+    open(
+        backend,
+        &app,
+        "[Unit]\nWants=db.service\n[Install]\nWantedBy=multi-user.target\n",
+    )
+    .await;
+
+    let dot = backend
+        .dependency_graph(DependencyGraphParams {
+            unit: "multi-user.target".into(),
+            format: GraphFormat::Dot,
+        })
+        .await
+        .unwrap();
+    let GraphOutput::Dot(dot) = dot else {
+        panic!("expected DOT output");
+    };
+    assert!(dot.contains("\"multi-user.target\" -> \"app.service\" [label=\"Wants\""));
+    assert!(dot.contains("\"app.service\" -> \"db.service\" [label=\"Wants\""));
+
+    let json = backend
+        .dependency_graph(DependencyGraphParams {
+            unit: "app.service".into(),
+            format: GraphFormat::Json,
+        })
+        .await
+        .unwrap();
+    let json = serde_json::to_value(json).unwrap();
+    assert_eq!(
+        json["nodes"],
+        serde_json::json!(["app.service", "db.service"])
+    );
+    assert_eq!(json["edges"][0]["kind"], "Wants");
+
+    let invalid = DependencyGraphParams {
+        unit: "app".into(),
+        format: GraphFormat::Json,
+    };
+    assert!(backend.dependency_graph(invalid).await.is_err());
 }
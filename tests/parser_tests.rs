@@ -0,0 +1,126 @@
+use systemd_language_server::line_index::LineIndex;
+use systemd_language_server::parser::{Entry, parse};
+
+#[test]
+fn test_keeps_every_line() {
+    let content =
+        "# header comment\n\n[Unit]\nDescription=Test\n; note\n\n[Service]\nExecStart=/bin/true\n";
+    let unit = parse(content);
+
+    assert!(unit.errors.is_empty());
+    assert_eq!(unit.preamble.len(), 2);
+    assert!(matches!(unit.preamble[0], Entry::Comment(_)));
+    assert!(matches!(unit.preamble[1], Entry::Blank(_)));
+
+    assert_eq!(unit.sections.len(), 2);
+    assert_eq!(unit.sections[0].entries.len(), 3);
+    assert_eq!(unit.sections[1].entries.len(), 1);
+}
+
+#[test]
+fn test_spans_point_into_source() {
+    let content = "[Unit]\nDescription = My Service\n";
+    let unit = parse(content);
+
+    let section = &unit.sections[0];
+    assert_eq!(
+        &content[section.name_span.start..section.name_span.end],
+        "Unit"
+    );
+
+    let directive = section.directives().next().unwrap();
+    assert_eq!(
+        &content[directive.key_span.start..directive.key_span.end],
+        "Description"
+    );
+    assert_eq!(
+        &content[directive.value_span.start..directive.value_span.end],
+        "My Service"
+    );
+}
+
+#[test]
+fn test_repeated_keys_preserved_in_order() {
+    let content = "[Service]\nExecStartPre=/bin/a\nExecStartPre=/bin/b\nExecStartPre=\n";
+    let unit = parse(content);
+
+    assert_eq!(
+        unit.get_all("Service", "ExecStartPre"),
+        vec!["/bin/a", "/bin/b", ""]
+    );
+    assert_eq!(unit.get("Service", "ExecStartPre"), Some(""));
+}
+
+#[test]
+fn test_keys_are_case_sensitive() {
+    let content = "[Unit]\nDescription=Test\n";
+    let unit = parse(content);
+
+    assert_eq!(unit.get("Unit", "Description"), Some("Test"));
+    assert_eq!(unit.get("Unit", "description"), None);
+}
+
+#[test]
+fn test_no_inline_comments() {
+    let content = "[Unit]\nAfter=network.target # not a comment\n";
+    let unit = parse(content);
+
+    assert_eq!(
+        unit.get("Unit", "After"),
+        Some("network.target # not a comment")
+    );
+}
+
+#[test]
+fn test_line_continuation() {
+    let content =
+        "[Service]\nExecStart=/usr/bin/foo \\\n    --bar \\\n# skipped\n    --baz\nUser=nobody\n";
+    let unit = parse(content);
+
+    assert!(unit.errors.is_empty());
+    let directive = unit.sections[0].directives().next().unwrap();
+    assert_eq!(directive.value, "/usr/bin/foo --bar --baz");
+    assert!(directive.is_continued());
+
+    // Logical offsets map back to the continuation lines
+    let start = directive.value.find("--baz").unwrap();
+    let span = directive.source_span(start, start + "--baz".len());
+    assert_eq!(&content[span.start..span.end], "--baz");
+
+    assert_eq!(unit.get("Service", "User"), Some("nobody"));
+}
+
+#[test]
+fn test_error_positions() {
+    let content = "[Unit]\nDescription=Test\nThis line has no equals\n";
+    let unit = parse(content);
+    let index = LineIndex::new(content);
+
+    assert_eq!(unit.errors.len(), 1);
+    let range = index.range(unit.errors[0].span);
+    assert_eq!(range.start.line, 2);
+    assert_eq!(range.start.character, 0);
+    assert_eq!(range.end.character, 23);
+    assert!(matches!(unit.sections[0].entries[1], Entry::Invalid(_)));
+}
+
+#[test]
+fn test_assignment_outside_section() {
+    let content = "Description=Test\n[Unit]\n";
+    let unit = parse(content);
+
+    assert_eq!(unit.errors.len(), 1);
+    assert!(unit.errors[0].message.contains("outside of section"));
+}
+
+#[test]
+fn test_utf16_positions() {
+    let content = "[Unit]\nDescription=Café ☕ service\n";
+    let index = LineIndex::new(content);
+
+    let offset = content.find("service").unwrap();
+    let position = index.position(offset);
+    assert_eq!(position.line, 1);
+    assert_eq!(position.character, 19);
+    assert_eq!(index.offset(position), offset);
+}
@@ -0,0 +1,118 @@
+use std::collections::HashSet;
+use std::path::Path;
+
+use systemd_language_server::schema::{self, SECTIONS, UnitType, ValueType};
+
+#[test]
+fn test_exec_kill_and_cgroup_directives_shared() {
+    for section in ["Service", "Socket", "Mount", "Swap"] {
+        assert!(schema::lookup(section, "User").is_some(), "{}", section);
+        assert!(schema::lookup(section, "KillMode").is_some(), "{}", section);
+        assert!(
+            schema::lookup(section, "MemoryMax").is_some(),
+            "{}",
+            section
+        );
+    }
+    assert!(schema::lookup("Slice", "MemoryMax").is_some());
+    assert!(schema::lookup("Slice", "User").is_none());
+    assert!(schema::lookup("Timer", "ExecStart").is_none());
+}
+
+#[test]
+fn test_no_duplicate_directives_in_a_section() {
+    for section in SECTIONS {
+        let mut seen = HashSet::new();
+        for directive in section.directives() {
+            assert!(
+                seen.insert(directive.name),
+                "{} listed twice in [{}]",
+                directive.name,
+                section.name
+            );
+        }
+    }
+}
+
+#[test]
+fn test_every_directive_documented() {
+    for section in SECTIONS {
+        assert!(!section.doc.is_empty());
+        for directive in section.directives() {
+            assert!(!directive.doc.is_empty(), "{}", directive.name);
+        }
+    }
+}
+
+#[test]
+fn test_versions_within_pinned_release() {
+    for section in SECTIONS {
+        for directive in section.directives() {
+            assert!(
+                directive.since <= schema::SYSTEMD_VERSION,
+                "{}",
+                directive.name
+            );
+            for value in directive.enum_values() {
+                assert!(
+                    value.since <= schema::SYSTEMD_VERSION,
+                    "{}={}",
+                    directive.name,
+                    value.name
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_directive_metadata() {
+    let exec_start = schema::lookup("Service", "ExecStart").unwrap();
+    assert_eq!(exec_start.value, ValueType::Command);
+    assert!(exec_start.repeatable);
+    assert!(exec_start.empty_resets);
+
+    let zswap = schema::lookup("Service", "MemoryZSwapMax").unwrap();
+    assert_eq!(zswap.value, ValueType::Size);
+    assert_eq!(zswap.since, 253);
+
+    let service_type = schema::lookup("Service", "Type").unwrap();
+    assert!(service_type.find_enum_value("notify").is_some());
+    assert_eq!(
+        service_type.find_enum_value("notify-reload").unwrap().since,
+        253
+    );
+    assert!(service_type.find_enum_value("daemon").is_none());
+}
+
+#[test]
+fn test_enum_values_with_arguments_and_booleans() {
+    let output = schema::lookup("Service", "StandardOutput").unwrap();
+    assert!(output.find_enum_value("journal").is_some());
+    assert!(output.find_enum_value("file:/var/log/app.log").is_some());
+    assert!(output.find_enum_value("file").is_none());
+
+    let protect = schema::lookup("Service", "ProtectSystem").unwrap();
+    assert!(protect.find_enum_value("strict").is_some());
+    assert!(protect.find_enum_value("true").is_some());
+    assert!(protect.find_enum_value("off").is_some());
+}
+
+#[test]
+fn test_unit_type_from_path() {
+    assert_eq!(
+        UnitType::from_path(Path::new("/etc/systemd/system/foo.service")),
+        Some(UnitType::Service)
+    );
+    assert_eq!(
+        UnitType::from_path(Path::new("foo@.timer")),
+        Some(UnitType::Timer)
+    );
+    assert_eq!(
+        UnitType::from_path(Path::new("/etc/systemd/system/foo.socket.d/override.conf")),
+        Some(UnitType::Socket)
+    );
+    assert_eq!(UnitType::from_path(Path::new("README.md")), None);
+    assert_eq!(UnitType::Target.section(), None);
+    assert_eq!(UnitType::Path.section(), Some("Path"));
+}
@@ -0,0 +1,71 @@
+use systemd_language_server::parser::Span;
+use systemd_language_server::schema::ValueType;
+use systemd_language_server::validators::{parse_percent, parse_size, parse_timespan, validate};
+
+#[test]
+fn test_timespans() {
+    assert_eq!(parse_timespan("5min 30s").unwrap(), Some(330_000_000));
+    assert_eq!(parse_timespan("1h30min").unwrap(), Some(5_400_000_000));
+    assert_eq!(parse_timespan("100ms").unwrap(), Some(100_000));
+    assert_eq!(parse_timespan("90").unwrap(), Some(90_000_000));
+    assert_eq!(parse_timespan("2 weeks").unwrap(), Some(1_209_600_000_000));
+    assert_eq!(parse_timespan("infinity").unwrap(), None);
+
+    let error = parse_timespan("5min 30x").unwrap_err();
+    assert_eq!(error.span, Span::new(5, 8));
+    assert!(error.message.contains("Unknown unit 'x'"));
+    assert_eq!(parse_timespan("soon").unwrap_err().span, Span::new(0, 4));
+}
+
+#[test]
+fn test_sizes_and_limits() {
+    assert_eq!(parse_size("512M").unwrap(), Some(512 * 1024 * 1024));
+    assert_eq!(parse_size("1.5K").unwrap(), Some(1536));
+    assert_eq!(parse_size("infinity").unwrap(), None);
+    assert!(validate(ValueType::Size, "40%").is_ok());
+    assert_eq!(
+        validate(ValueType::Size, "512MB").unwrap_err().span,
+        Span::new(0, 5)
+    );
+
+    assert!(validate(ValueType::Limit, "65536").is_ok());
+    assert!(validate(ValueType::Limit, "65536:524288").is_ok());
+    assert!(validate(ValueType::Limit, "4G:infinity").is_ok());
+    // Time spans only compare with time spans
+    assert!(validate(ValueType::Limit, "1h:10").is_ok());
+    assert!(validate(ValueType::Limit, "1h:2h").is_ok());
+    assert!(validate(ValueType::Limit, "2h:1h").is_err());
+    assert_eq!(
+        validate(ValueType::Limit, "65536:lots").unwrap_err().span,
+        Span::new(6, 10)
+    );
+    let error = validate(ValueType::Limit, "524288:65536").unwrap_err();
+    assert!(error.message.contains("exceeds hard limit"));
+}
+
+#[test]
+fn test_booleans_and_percentages() {
+    for value in ["yes", "no", "true", "false", "on", "off", "1", "0", "Y"] {
+        assert!(validate(ValueType::Boolean, value).is_ok(), "{}", value);
+    }
+    assert!(validate(ValueType::Boolean, "enabled").is_err());
+
+    assert_eq!(parse_percent("150%").unwrap(), 1.5);
+    assert_eq!(parse_percent("12.5%").unwrap(), 0.125);
+    assert_eq!(parse_percent("500‰").unwrap(), 0.5);
+    assert!(parse_percent("150").is_err());
+    assert!(parse_percent("%").is_err());
+    assert!(parse_percent("a%").is_err());
+}
+
+#[test]
+fn test_other_numeric_types() {
+    assert!(validate(ValueType::Integer, "-5").is_ok());
+    assert!(validate(ValueType::Integer, "five").is_err());
+    assert!(validate(ValueType::Weight, "100").is_ok());
+    assert!(validate(ValueType::Weight, "0").is_err());
+    assert!(validate(ValueType::Tasks, "80%").is_ok());
+    assert!(validate(ValueType::Tasks, "infinity").is_ok());
+    assert!(validate(ValueType::Mode, "0755").is_ok());
+    assert!(validate(ValueType::Mode, "0855").is_err());
+}
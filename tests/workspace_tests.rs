@@ -0,0 +1,596 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use systemd_language_server::document::Document;
+use systemd_language_server::dropins::{self, dropin_dir_names, extended_unit, find_dropins};
+use systemd_language_server::graph::{DependencyGraph, EdgeKind, check_unit, cycle_diagnostics};
+use systemd_language_server::index::{UnitIndex, refers_to};
+use systemd_language_server::parser::{self, Span};
+use systemd_language_server::rename::{check_new_name, rename_unit, renamed_reference};
+use systemd_language_server::units::{self, template_of};
+use systemd_language_server::workspace::{find_unit, unit_files};
+use tower_lsp::lsp_types::{
+    DocumentChangeOperation, DocumentChanges, OneOf, Position, RenameFile, ResourceOp, Url,
+};
+
+// Fresh directory under the system temp dir, removed when dropped
+struct TempDir(PathBuf);
+
+impl TempDir {
+    fn new(name: &str) -> Self {
+        let path = std::env::temp_dir().join(format!("systemd-ls-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&path);
+        fs::create_dir_all(&path).unwrap();
+        Self(path)
+    }
+
+    fn write(&self, relative: &str, text: &str) -> PathBuf {
+        let path = self.0.join(relative);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, text).unwrap();
+        path
+    }
+}
+
+impl Drop for TempDir {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+#[test]
+fn test_unit_references_in_a_directive() {
+    let text = "[Unit]\nAfter=network-online.target  foo@%i.service \\\n  bar.socket\n";
+    let unit = parser::parse(text);
+    let (section, directive) = unit.directives().next().unwrap();
+    let references = units::references(section, directive);
+
+    let names: Vec<&str> = references.iter().map(|r| r.name).collect();
+    assert_eq!(names, ["network-online.target", "bar.socket"]);
+    assert_eq!(references[0].span, Span::new(13, 34));
+    let Span { start, end } = references[1].span;
+    assert_eq!(&text[start..end], "bar.socket");
+
+    let unit = parser::parse("[Unit]\nDescription=foo.service\n");
+    let (section, directive) = unit.directives().next().unwrap();
+    assert!(units::references(section, directive).is_empty());
+}
+
+#[test]
+fn test_template_of() {
+    assert_eq!(template_of("getty@tty1.service").unwrap(), "getty@.service");
+    assert_eq!(template_of("getty@.service"), None);
+    assert_eq!(template_of("sshd.service"), None);
+}
+
+#[test]
+fn test_find_unit_precedence() {
+    let dir = TempDir::new("find-unit");
+    let project = dir.write("project/units/app.service", "[Unit]\n");
+    dir.write("project/.git/app.service", "[Unit]\n");
+    let etc = dir.write("etc/network-online.target", "[Unit]\n");
+    dir.write("usr/network-online.target", "[Unit]\n");
+    let template = dir.write("usr/getty@.service", "[Unit]\n");
+
+    let folders = [dir.0.join("project")];
+    let search_paths = [dir.0.join("etc"), dir.0.join("run"), dir.0.join("usr")];
+    assert_eq!(unit_files(&folders[0]), std::slice::from_ref(&project));
+
+    // A link in a `.wants/` directory sorting first isn't the definition
+    #[cfg(unix)]
+    {
+        fs::create_dir_all(dir.0.join("project/a.target.wants")).unwrap();
+        std::os::unix::fs::symlink(&project, dir.0.join("project/a.target.wants/app.service"))
+            .unwrap();
+    }
+    let index = UnitIndex::new();
+    index.index_folder(&folders[0]);
+    let find = |name: &str| find_unit(name, &index, &search_paths);
+
+    assert_eq!(find("app.service"), Some(project));
+    assert_eq!(find("network-online.target"), Some(etc));
+    assert_eq!(find("getty@tty1.service"), Some(template));
+    assert_eq!(find("missing.service"), None);
+    assert!(Path::new(&dir.0).exists());
+}
+
+#[test]
+fn test_refers_to_templates() {
+    assert!(refers_to("app-db.service", "app-db.service"));
+    assert!(refers_to("worker@1.service", "worker@.service"));
+    assert!(refers_to("worker@1.service", "worker@1.service"));
+    assert!(!refers_to("worker@.service", "worker@1.service"));
+    assert!(!refers_to("app-db.socket", "app-db.service"));
+}
+
+#[test]
+fn test_index_references_across_the_workspace() {
+    let dir = TempDir::new("index");
+    dir.write("db/app-db.service", "[Unit]\nDescription=Database\n");
+    dir.write(
+        "web/app-web.service",
+        "[Unit]\nRequires=app-db.service\nAfter=network.target app-db.service\n",
+    );
+    dir.write("web/app-web.timer", "[Timer]\nUnit=app-web.service\n");
+    dir.write(
+        "app-db.service.d/override.conf",
+        "[Unit]\nPartOf=app.target\nOnFailure=notify@app-db.service\n",
+    );
+
+    let index = UnitIndex::new();
+    index.index_folder(&dir.0);
+    assert_eq!(index.uris().len(), 4);
+
+    let web = Url::from_file_path(dir.0.join("web/app-web.service")).unwrap();
+    let references = index.references("app-db.service");
+    assert_eq!(references.len(), 2);
+    assert!(references.iter().all(|location| location.uri == web));
+    assert_eq!(references[1].range.start, Position::new(2, 21));
+    assert_eq!(
+        index.definitions("app-db.service"),
+        [Url::from_file_path(dir.0.join("db/app-db.service")).unwrap()]
+    );
+    assert_eq!(index.references("notify@.service").len(), 1);
+
+    // Open buffers replace their file on disk
+    index.update(
+        web.clone(),
+        &Document::new("[Unit]\nWants=app-db.service\n", 1),
+    );
+    let references = index.references("app-db.service");
+    assert_eq!(references.len(), 1);
+    assert_eq!(index.get(&web).unwrap().references[0].key, "Wants");
+
+    // Only changes to the references invalidate the dependency graph, not
+    // edits moving them
+    let generation = index.generation();
+    let mut graph = DependencyGraph::build(&index);
+    index.update(
+        web.clone(),
+        &Document::new("[Unit]\nDescription=Web\n\nWants=app-db.service\n", 2),
+    );
+    assert_eq!(index.generation(), generation);
+    graph.relocate(&web, &index.get(&web).unwrap());
+    let edge = graph
+        .edges()
+        .iter()
+        .find(|edge| edge.location.uri == web)
+        .unwrap();
+    assert_eq!(edge.location.range.start, Position::new(3, 6));
+    index.update(web.clone(), &Document::new("[Unit]\n", 3));
+    assert_ne!(index.generation(), generation);
+}
+
+#[test]
+fn test_dropins_elsewhere_in_the_workspace() {
+    let dir = TempDir::new("workspace-dropins");
+    dir.write("units/app.service", "[Service]\nExecStart=/usr/bin/app\n");
+    let env = dir.write("overrides/app.service.d/env.conf", "[Service]\nNice=5\n");
+    let wide = dir.write(
+        "defaults/service.d/10-limits.conf",
+        "[Service]\nLimitNOFILE=4096\n",
+    );
+    dir.write("overrides/other.service.d/env.conf", "[Service]\nNice=1\n");
+
+    let index = UnitIndex::new();
+    index.index_folder(&dir.0);
+    let dirs = dropins::workspace_dirs(&index, "app.service");
+    assert_eq!(dirs, [dir.0.join("defaults"), dir.0.join("overrides")]);
+    assert_eq!(find_dropins("app.service", &dirs), [wide, env]);
+}
+
+#[test]
+fn test_rename_names() {
+    assert!(check_new_name("worker.service", "ingest-worker.service").is_ok());
+    assert!(check_new_name("worker.service", "ingest-worker.socket").is_err());
+    assert!(check_new_name("worker@.service", "worker.service").is_err());
+    assert!(check_new_name("worker.service", "in gest.service").is_err());
+
+    assert_eq!(
+        renamed_reference("worker@1.service", "worker@.service", "ingest@.service").unwrap(),
+        "ingest@1.service"
+    );
+    assert_eq!(
+        renamed_reference("other.service", "worker.service", "x.service"),
+        None
+    );
+}
+
+#[test]
+fn test_rename_unit_edits_references_and_renames_files() {
+    let dir = TempDir::new("rename");
+    dir.write("worker.service", "[Install]\nAlias=jobs.service\n");
+    dir.write("worker.service.d/limits.conf", "[Service]\nMemoryMax=1G\n");
+    dir.write(
+        "app.target",
+        "[Unit]\nWants=worker.service\nAfter=db.service worker.service\n",
+    );
+    dir.write("other.service", "[Install]\nAlias=worker.service\n");
+
+    let index = UnitIndex::new();
+    index.index_folder(&dir.0);
+    let uri = |path: &str| Url::from_file_path(dir.0.join(path)).unwrap();
+
+    let edit = rename_unit(&index, "worker.service", "ingest-worker.service", |u| {
+        (*u == uri("app.target")).then_some(7)
+    });
+    let Some(DocumentChanges::Operations(operations)) = edit.document_changes else {
+        panic!("expected document change operations");
+    };
+    assert_eq!(operations.len(), 4);
+
+    let DocumentChangeOperation::Edit(app) = &operations[0] else {
+        panic!("expected a text edit first");
+    };
+    assert_eq!(app.text_document.uri, uri("app.target"));
+    assert_eq!(app.text_document.version, Some(7));
+    assert_eq!(app.edits.len(), 2);
+    let OneOf::Left(second) = &app.edits[1] else {
+        panic!("expected a plain text edit");
+    };
+    assert_eq!(second.new_text, "ingest-worker.service");
+    assert_eq!(second.range.start, Position::new(2, 17));
+
+    let DocumentChangeOperation::Edit(alias) = &operations[1] else {
+        panic!("expected the Alias= edit");
+    };
+    assert_eq!(alias.text_document.uri, uri("other.service"));
+
+    let renames: Vec<(Url, Url)> = operations[2..]
+        .iter()
+        .map(|operation| match operation {
+            DocumentChangeOperation::Op(ResourceOp::Rename(RenameFile {
+                old_uri,
+                new_uri,
+                ..
+            })) => (old_uri.clone(), new_uri.clone()),
+            _ => panic!("expected a rename"),
+        })
+        .collect();
+    assert_eq!(
+        renames,
+        [
+            (uri("worker.service"), uri("ingest-worker.service")),
+            (uri("worker.service.d"), uri("ingest-worker.service.d")),
+        ]
+    );
+}
+
+#[test]
+fn test_dropin_dir_names() {
+    assert_eq!(
+        dropin_dir_names("foo-bar@baz.service"),
+        [
+            "service.d",
+            "foo-.service.d",
+            "foo-bar@.service.d",
+            "foo-bar@baz.service.d",
+        ]
+    );
+    assert_eq!(
+        extended_unit(Path::new(
+            "/etc/systemd/system/app.service.d/10-limits.conf"
+        ))
+        .unwrap(),
+        "app.service"
+    );
+    assert_eq!(
+        extended_unit(Path::new("/etc/systemd/system/service.d/x.conf")),
+        None
+    );
+    assert_eq!(
+        extended_unit(Path::new("/etc/systemd/system/app-.service.d/x.conf")),
+        None
+    );
+}
+
+#[test]
+fn test_find_dropins_and_merge() {
+    let dir = TempDir::new("dropins");
+    let etc = dir.0.join("etc");
+    let lib = dir.0.join("lib");
+    dir.write(
+        "etc/app.service.d/20-env.conf",
+        "[Service]\nEnvironment=B=2\n",
+    );
+    dir.write(
+        "lib/app.service.d/20-env.conf",
+        "[Service]\nEnvironment=SHADOWED=1\n",
+    );
+    dir.write(
+        "lib/service.d/10-exec.conf",
+        "[Service]\nExecStart=\nExecStart=/usr/bin/app --debug\n",
+    );
+    // Within one directory the unit's own drop-in shadows a type-wide one
+    dir.write("etc/service.d/30-same.conf", "[Service]\nNice=1\n");
+    dir.write("etc/app.service.d/30-same.conf", "[Service]\nNice=5\n");
+    dir.write("lib/app.service.d/notes.txt", "ignored");
+
+    let files = find_dropins("app.service", &[etc.clone(), lib.clone()]);
+    assert_eq!(
+        files,
+        [
+            lib.join("service.d/10-exec.conf"),
+            etc.join("app.service.d/20-env.conf"),
+            etc.join("app.service.d/30-same.conf"),
+        ]
+    );
+
+    let uri = |name: &str| Url::parse(&format!("file:///{}", name)).unwrap();
+    let base = Document::new(
+        "[Service]\nType=simple\nExecStart=/usr/bin/app\nEnvironment=A=1\n",
+        0,
+    );
+    let exec = Document::new(&fs::read_to_string(&files[0]).unwrap(), 0);
+    let env = Document::new("[Service]\nType=notify\nEnvironment=B=2\n", 0);
+    let sections = dropins::merge(&[
+        (uri("app.service"), &base),
+        (uri("10-exec.conf"), &exec),
+        (uri("20-env.conf"), &env),
+    ]);
+
+    assert_eq!(sections.len(), 1);
+    let values: Vec<(&str, &str)> = sections[0]
+        .directives
+        .iter()
+        .map(|d| (d.key.as_str(), d.value.as_str()))
+        .collect();
+    assert_eq!(
+        values,
+        [
+            ("Environment", "A=1"),
+            ("ExecStart", "/usr/bin/app --debug"),
+            ("Type", "notify"),
+            ("Environment", "B=2"),
+        ]
+    );
+    assert_eq!(sections[0].directives[1].uri, uri("10-exec.conf"));
+    assert_eq!(sections[0].directives[1].range.start, Position::new(2, 0));
+}
+
+#[test]
+fn test_workspace_symbols() {
+    let dir = TempDir::new("symbols");
+    dir.write(
+        "web.service",
+        "[Unit]\nDescription=Nginx web server\n\n[Service]\nExecStart=/usr/sbin/nginx\n",
+    );
+    dir.write(
+        "proxy.service",
+        "[Service]\nExecStartPre=/bin/true\n[Install]\nAlias=nginx-proxy.service\n",
+    );
+    dir.write("web.service.d/debug.conf", "[Service]\nExecStart=\n");
+    dir.write("db.service", "[Unit]\nDescription=Database\n");
+
+    let index = UnitIndex::new();
+    index.index_folder(&dir.0);
+    let names = |query: &str| -> Vec<(String, Option<String>)> {
+        index
+            .symbols(query)
+            .into_iter()
+            .map(|symbol| (symbol.name, symbol.container_name))
+            .collect()
+    };
+
+    assert_eq!(
+        names("NGINX"),
+        [
+            ("proxy.service".to_string(), None),
+            (
+                "web.service".to_string(),
+                Some("Nginx web server".to_string())
+            ),
+        ]
+    );
+    assert_eq!(names("").len(), 3);
+
+    let exec = index.symbols("execstart");
+    let located: Vec<(&str, Position)> = exec
+        .iter()
+        .map(|symbol| (symbol.name.as_str(), symbol.location.range.start))
+        .collect();
+    assert_eq!(
+        located,
+        [
+            ("ExecStartPre=/bin/true", Position::new(1, 0)),
+            ("ExecStart=/usr/sbin/nginx", Position::new(4, 0)),
+            ("ExecStart=", Position::new(1, 0)),
+        ]
+    );
+    assert_eq!(exec[2].container_name.as_deref(), Some("debug.conf"));
+}
+
+#[test]
+fn test_dependency_graph_and_ordering_cycles() {
+    let dir = TempDir::new("graph");
+    let a = dir.write("a.service", "[Unit]\nAfter=b.service\nWants=b.service\n");
+    let b = dir.write("b.service", "[Unit]\nDescription=B\n");
+    dir.write("b.service.d/order.conf", "[Unit]\nAfter=c.service\n");
+    let c = dir.write("c.service", "[Unit]\nBefore=b.service\nAfter=a.service\n");
+    dir.write(
+        "app.service",
+        "[Unit]\nAfter=network.target\n[Install]\nWantedBy=multi-user.target\n",
+    );
+    #[cfg(unix)]
+    {
+        fs::create_dir_all(dir.0.join("multi-user.target.wants")).unwrap();
+        std::os::unix::fs::symlink(&b, dir.0.join("multi-user.target.wants/b.service")).unwrap();
+    }
+
+    let index = UnitIndex::new();
+    index.index_folder(&dir.0);
+    let graph = DependencyGraph::build(&index);
+    let edges = |from: &str| -> Vec<(EdgeKind, String)> {
+        let mut edges: Vec<(EdgeKind, String)> = graph
+            .edges_from(from)
+            .map(|edge| (edge.kind, edge.to.clone()))
+            .collect();
+        edges.sort();
+        edges
+    };
+
+    #[cfg(unix)]
+    assert_eq!(
+        edges("multi-user.target"),
+        [
+            (EdgeKind::Wants, "app.service".to_string()),
+            (EdgeKind::Wants, "b.service".to_string())
+        ]
+    );
+    assert_eq!(
+        edges("b.service"),
+        [(EdgeKind::After, "c.service".to_string())]
+    );
+
+    // a after b, b after c (drop-in), c after a: every ordering directive of
+    // the cycle is reported, with the others as related information
+    let diagnostics = cycle_diagnostics(&graph, &Url::from_file_path(&a).unwrap());
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].range,
+        tower_lsp::lsp_types::Range::new(Position::new(1, 6), Position::new(1, 15))
+    );
+    let related = diagnostics[0].related_information.as_ref().unwrap();
+    let related: Vec<&str> = related.iter().map(|r| r.message.as_str()).collect();
+    assert_eq!(
+        related,
+        [
+            "After=a.service in c.service",
+            "After=c.service in b.service"
+        ]
+    );
+
+    // c.service takes part twice: Before=b.service and After=a.service
+    let diagnostics = cycle_diagnostics(&graph, &Url::from_file_path(&c).unwrap());
+    assert_eq!(diagnostics.len(), 2);
+    let app = dir.0.join("app.service");
+    assert!(cycle_diagnostics(&graph, &Url::from_file_path(app).unwrap()).is_empty());
+}
+
+#[test]
+fn test_template_ordered_after_own_instance() {
+    let dir = TempDir::new("graph-template");
+    let worker = dir.write(
+        "worker@.service",
+        "[Unit]\nAfter=worker@primary.service\nBefore=worker@.service\n",
+    );
+
+    let index = UnitIndex::new();
+    index.index_folder(&dir.0);
+    let graph = DependencyGraph::build(&index);
+
+    // Instances are separate units, but a template ordered against itself
+    // still loops
+    let uri = Url::from_file_path(&worker).unwrap();
+    let diagnostics = cycle_diagnostics(&graph, &uri);
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].range.start, Position::new(2, 7));
+    assert_eq!(graph.cycle_uris().into_iter().collect::<Vec<_>>(), [uri]);
+}
+
+#[test]
+fn test_dependency_graph_export() {
+    let dir = TempDir::new("graph-export");
+    dir.write(
+        "app@.service",
+        "[Unit]\nRequires=db.service\nAfter=db.service\nConflicts=shutdown.target\n",
+    );
+    dir.write("db.service", "[Unit]\nPartOf=web.target\n");
+    dir.write("web.target", "[Unit]\nWants=app@main.service\n");
+    dir.write("other.service", "[Unit]\nBefore=web.target\n");
+
+    let index = UnitIndex::new();
+    index.index_folder(&dir.0);
+    let export = DependencyGraph::build(&index).export("web.target");
+    assert!(check_unit("web.target").is_ok());
+    assert!(check_unit("web").is_err());
+
+    assert_eq!(
+        export.nodes,
+        ["app@main.service", "db.service", "web.target"]
+    );
+    let edges: Vec<(&str, &str, EdgeKind)> = export
+        .edges
+        .iter()
+        .map(|edge| (edge.from.as_str(), edge.to.as_str(), edge.kind))
+        .collect();
+    assert_eq!(
+        edges,
+        [
+            ("app@main.service", "db.service", EdgeKind::Requires),
+            ("app@main.service", "db.service", EdgeKind::After),
+            ("db.service", "web.target", EdgeKind::PartOf),
+            ("web.target", "app@main.service", EdgeKind::Wants),
+        ]
+    );
+
+    let dot = export.to_dot();
+    assert!(dot.starts_with("digraph \"web.target\" {\n"));
+    assert!(dot.contains(
+        "    \"app@main.service\" -> \"db.service\" [label=\"Requires\", color=\"black\"];\n"
+    ));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_rename_template_renames_links_and_instance_dropins() {
+    let dir = TempDir::new("rename-template");
+    let template = dir.write("worker@.service", "[Service]\nExecStart=/bin/work %i\n");
+    dir.write("worker@a.service.d/limits.conf", "[Service]\nNice=5\n");
+    dir.write("worker.target", "[Unit]\nDescription=Workers\n");
+    fs::create_dir_all(dir.0.join("multi-user.target.wants")).unwrap();
+    std::os::unix::fs::symlink(
+        &template,
+        dir.0.join("multi-user.target.wants/worker@a.service"),
+    )
+    .unwrap();
+    fs::create_dir_all(dir.0.join("worker.target.wants")).unwrap();
+    std::os::unix::fs::symlink(
+        &template,
+        dir.0.join("worker.target.wants/worker@b.service"),
+    )
+    .unwrap();
+
+    let index = UnitIndex::new();
+    index.index_folder(&dir.0);
+    let uri = |path: &str| Url::from_file_path(dir.0.join(path)).unwrap();
+    let renames = |old: &str, new: &str| -> Vec<(Url, Url)> {
+        let edit = rename_unit(&index, old, new, |_| None);
+        let Some(DocumentChanges::Operations(operations)) = edit.document_changes else {
+            panic!("expected document change operations");
+        };
+        operations
+            .into_iter()
+            .filter_map(|operation| match operation {
+                DocumentChangeOperation::Op(ResourceOp::Rename(rename)) => {
+                    Some((rename.old_uri, rename.new_uri))
+                }
+                _ => None,
+            })
+            .collect()
+    };
+
+    assert_eq!(
+        renames("worker@.service", "ingest@.service"),
+        [
+            (uri("worker@.service"), uri("ingest@.service")),
+            (
+                uri("multi-user.target.wants/worker@a.service"),
+                uri("multi-user.target.wants/ingest@a.service")
+            ),
+            (
+                uri("worker.target.wants/worker@b.service"),
+                uri("worker.target.wants/ingest@b.service")
+            ),
+            (uri("worker@a.service.d"), uri("ingest@a.service.d")),
+        ]
+    );
+    // A unit's own `.wants/` directory follows it
+    assert_eq!(
+        renames("worker.target", "jobs.target"),
+        [
+            (uri("worker.target"), uri("jobs.target")),
+            (uri("worker.target.wants"), uri("jobs.target.wants")),
+        ]
+    );
+}